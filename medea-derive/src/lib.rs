@@ -0,0 +1,225 @@
+//! Derive macros for `medea::FromJson` and `medea::ToJson`. See `medea::ToJson` for the JSON
+//! representation of structs and enums.
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Fields, Generics, Ident, LitStr, Result};
+
+#[proc_macro_derive(FromJson, attributes(json))]
+pub fn derive_from_json(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    from_json(&input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+#[proc_macro_derive(ToJson, attributes(json))]
+pub fn derive_to_json(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    to_json(&input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+fn from_json(input: &DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+    let generics = add_bounds(&input.generics, quote!(::medea::FromJson));
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let value = Ident::new("value", Span::call_site());
+            fields_from_json(&quote!(#name), &data.fields, &value)?
+        }
+        Data::Enum(data) => {
+            let content = Ident::new("content", Span::call_site());
+            let mut unit_arms = Vec::new();
+            let mut content_arms = Vec::new();
+            for variant in &data.variants {
+                let ident = &variant.ident;
+                let json_name = json_name(&variant.attrs, ident)?;
+                if matches!(variant.fields, Fields::Unit) {
+                    unit_arms.push(quote!(#json_name => ::std::result::Result::Ok(Self::#ident),));
+                }
+                let conversion = fields_from_json(&quote!(Self::#ident), &variant.fields, &content)?;
+                content_arms.push(quote!(#json_name => ::medea::__private::in_variant(name, || { #conversion }),));
+            }
+            quote! {
+                if let ::medea::JsonValue::String(name) = value {
+                    return match name.as_str() {
+                        #(#unit_arms)*
+                        _ => ::std::result::Result::Err(::medea::__private::unknown_variant(name)),
+                    };
+                }
+                let (name, content) = ::medea::__private::variant(value)?;
+                match name {
+                    #(#content_arms)*
+                    _ => ::std::result::Result::Err(::medea::__private::unknown_variant(name)),
+                }
+            }
+        }
+        Data::Union(_) => return Err(syn::Error::new_spanned(input, "FromJson cannot be derived for unions")),
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::medea::FromJson for #name #type_generics #where_clause {
+            fn from_json(value: &::medea::JsonValue) -> ::std::result::Result<Self, ::medea::ConversionError> {
+                #body
+            }
+        }
+    })
+}
+
+/// Converts `value` into the struct or variant `path` with the given fields
+fn fields_from_json(path: &TokenStream, fields: &Fields, value: &Ident) -> Result<TokenStream> {
+    Ok(match fields {
+        Fields::Named(fields) => {
+            let members = fields
+                .named
+                .iter()
+                .map(|field| {
+                    let ident = field.ident.as_ref().unwrap();
+                    let json_name = json_name(&field.attrs, ident)?;
+                    Ok(quote!(#ident: ::medea::__private::member(members, #json_name)?))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            quote! {
+                let members = ::medea::__private::members(#value)?;
+                ::std::result::Result::Ok(#path { #(#members),* })
+            }
+        }
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            no_attributes(&fields.unnamed[0].attrs)?;
+            quote!(::std::result::Result::Ok(#path(::medea::FromJson::from_json(#value)?)))
+        }
+        Fields::Unnamed(fields) => {
+            let len = fields.unnamed.len();
+            let elements = fields
+                .unnamed
+                .iter()
+                .enumerate()
+                .map(|(index, field)| {
+                    no_attributes(&field.attrs)?;
+                    Ok(quote!(::medea::__private::element(elements, #index)?))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            quote! {
+                let elements = ::medea::__private::elements(#value, #len)?;
+                ::std::result::Result::Ok(#path(#(#elements),*))
+            }
+        }
+        Fields::Unit => quote! {
+            <() as ::medea::FromJson>::from_json(#value)?;
+            ::std::result::Result::Ok(#path)
+        },
+    })
+}
+
+fn to_json(input: &DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+    let generics = add_bounds(&input.generics, quote!(::medea::ToJson));
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let (pattern, conversion) = fields_to_json(&data.fields)?;
+            quote! {
+                let Self #pattern = self;
+                #conversion
+            }
+        }
+        Data::Enum(data) => {
+            let arms = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let ident = &variant.ident;
+                    let json_name = json_name(&variant.attrs, ident)?;
+                    if matches!(variant.fields, Fields::Unit) {
+                        return Ok(quote!(Self::#ident => ::medea::JsonValue::String(#json_name.to_string()),));
+                    }
+                    let (pattern, conversion) = fields_to_json(&variant.fields)?;
+                    Ok(quote!(Self::#ident #pattern => ::medea::__private::object(vec![(#json_name.to_string(), #conversion)]),))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            if arms.is_empty() {
+                quote!(match *self {})
+            } else {
+                quote!(match self { #(#arms)* })
+            }
+        }
+        Data::Union(_) => return Err(syn::Error::new_spanned(input, "ToJson cannot be derived for unions")),
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::medea::ToJson for #name #type_generics #where_clause {
+            fn to_json(&self) -> ::medea::JsonValue {
+                #body
+            }
+        }
+    })
+}
+
+/// Pattern binding the fields to `__field0`, `__field1`, ... and the conversion of the bound
+/// fields
+fn fields_to_json(fields: &Fields) -> Result<(TokenStream, TokenStream)> {
+    let bindings: Vec<Ident> = (0..fields.len()).map(|index| format_ident!("__field{}", index)).collect();
+    Ok(match fields {
+        Fields::Named(fields) => {
+            let idents: Vec<&Ident> = fields.named.iter().map(|field| field.ident.as_ref().unwrap()).collect();
+            let json_names = fields
+                .named
+                .iter()
+                .map(|field| json_name(&field.attrs, field.ident.as_ref().unwrap()))
+                .collect::<Result<Vec<_>>>()?;
+            (
+                quote!({ #(#idents: #bindings),* }),
+                quote!(::medea::__private::object(vec![#((#json_names.to_string(), ::medea::ToJson::to_json(#bindings))),*])),
+            )
+        }
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            no_attributes(&fields.unnamed[0].attrs)?;
+            (quote!((__field0)), quote!(::medea::ToJson::to_json(__field0)))
+        }
+        Fields::Unnamed(fields) => {
+            for field in &fields.unnamed {
+                no_attributes(&field.attrs)?;
+            }
+            (
+                quote!((#(#bindings),*)),
+                quote!(::medea::JsonValue::Array(vec![#(::medea::ToJson::to_json(#bindings)),*])),
+            )
+        }
+        Fields::Unit => (quote!(), quote!(::medea::JsonValue::Null)),
+    })
+}
+
+/// Requires the trait for every type parameter
+fn add_bounds(generics: &Generics, bound: TokenStream) -> Generics {
+    let mut generics = generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#bound));
+    }
+    generics
+}
+
+/// The JSON name of a field or variant: the identifier or the name given by
+/// `#[json(rename = "name")]`
+fn json_name(attrs: &[Attribute], ident: &Ident) -> Result<String> {
+    let mut name = ident.to_string().trim_start_matches("r#").to_string();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("json")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                name = meta.value()?.parse::<LitStr>()?.value();
+                Ok(())
+            } else {
+                Err(meta.error("unsupported json attribute, expected `rename`"))
+            }
+        })?;
+    }
+    Ok(name)
+}
+
+/// Fields without a name have nothing to rename
+fn no_attributes(attrs: &[Attribute]) -> Result<()> {
+    match attrs.iter().find(|attr| attr.path().is_ident("json")) {
+        Some(attr) => Err(syn::Error::new_spanned(attr, "json attributes are only supported on named fields and variants")),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,119 @@
+use anyhow::{Result, anyhow};
+use crate::format_options::FormatOptions;
+use crate::json_value::JsonValue;
+use crate::pretty_printer::{KeyOrder, PrettyPrinter};
+
+impl JsonValue {
+    /// Serializes the value according to the JSON Canonicalization Scheme (RFC 8785).
+    ///
+    /// Fails if the value contains a number that is not finite.
+    pub fn to_canonical_string(&self) -> Result<String> {
+        check_numbers(self)?;
+
+        let options = FormatOptions {
+            final_newline: false,
+            ..FormatOptions::default()
+        };
+        let mut printer = PrettyPrinter::new(0, false)
+            .with_format_options(options)
+            .with_compact(true)
+            .with_key_order(KeyOrder::Utf16);
+
+        Ok(printer.pretty_print(self).iter().map(|cs| cs.to_string()).collect())
+    }
+}
+
+fn check_numbers(value: &JsonValue) -> Result<()> {
+    match value {
+        JsonValue::Number(n) if !n.is_finite() => {
+            Err(anyhow!("Number {} cannot be canonicalized", n))
+        }
+        JsonValue::Array(elements) => elements.iter().try_for_each(check_numbers),
+        JsonValue::Object(_, members) => members.values().try_for_each(check_numbers),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    fn canonicalize(json: &str) -> String {
+        parse_json(json).unwrap().to_canonical_string().unwrap()
+    }
+
+    #[test]
+    fn test_canonical_sample() {
+        // RFC 8785, section 3.2.2
+        let json = r#"{
+            "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+            "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+            "literals": [null, true, false]
+        }"#;
+        let expected = r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#;
+
+        assert_eq!(canonicalize(json), expected);
+    }
+
+    #[test]
+    fn test_canonical_sorting() {
+        // RFC 8785, section 3.2.3
+        let json = r#"{
+            "\u20ac": "Euro Sign",
+            "\r": "Carriage Return",
+            "\ufb33": "Hebrew Letter Dalet With Dagesh",
+            "1": "One",
+            "\ud83d\ude00": "Emoji: Grinning Face",
+            "\u0080": "Control",
+            "\u00f6": "Latin Small Letter O With Diaeresis"
+        }"#;
+        let expected = "{\"\\r\":\"Carriage Return\",\"1\":\"One\",\"\u{80}\":\"Control\",\
+            \"ö\":\"Latin Small Letter O With Diaeresis\",\"€\":\"Euro Sign\",\
+            \"😀\":\"Emoji: Grinning Face\",\"\u{fb33}\":\"Hebrew Letter Dalet With Dagesh\"}";
+
+        assert_eq!(canonicalize(json), expected);
+    }
+
+    #[test]
+    fn test_canonical_numbers() {
+        // RFC 8785, appendix B
+        let vectors: [(u64, &str); 24] = [
+            (0x0000000000000000, "0"),
+            (0x8000000000000000, "0"),
+            (0x0000000000000001, "5e-324"),
+            (0x8000000000000001, "-5e-324"),
+            (0x7fefffffffffffff, "1.7976931348623157e+308"),
+            (0xffefffffffffffff, "-1.7976931348623157e+308"),
+            (0x4340000000000000, "9007199254740992"),
+            (0xc340000000000000, "-9007199254740992"),
+            (0x4430000000000000, "295147905179352830000"),
+            (0x44b52d02c7e14af5, "9.999999999999997e+22"),
+            (0x44b52d02c7e14af6, "1e+23"),
+            (0x44b52d02c7e14af7, "1.0000000000000001e+23"),
+            (0x444b1ae4d6e2ef4e, "999999999999999700000"),
+            (0x444b1ae4d6e2ef4f, "999999999999999900000"),
+            (0x444b1ae4d6e2ef50, "1e+21"),
+            (0x3eb0c6f7a0b5ed8c, "9.999999999999997e-7"),
+            (0x3eb0c6f7a0b5ed8d, "0.000001"),
+            (0x41b3de4355555553, "333333333.3333332"),
+            (0x41b3de4355555554, "333333333.33333325"),
+            (0x41b3de4355555555, "333333333.3333333"),
+            (0x41b3de4355555556, "333333333.3333334"),
+            (0x41b3de4355555557, "333333333.33333343"),
+            (0xbecbf647612f3696, "-0.0000033333333333333333"),
+            (0x43143ff3c1cb0959, "1424953923781206.2"),
+        ];
+
+        for (bits, expected) in vectors {
+            let value = JsonValue::Number(f64::from_bits(bits));
+            assert_eq!(value.to_canonical_string().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_canonical_non_finite_number() {
+        assert!(JsonValue::Number(f64::NAN).to_canonical_string().is_err());
+        assert!(JsonValue::Array(vec![JsonValue::Number(f64::INFINITY)]).to_canonical_string().is_err());
+    }
+}
\ No newline at end of file
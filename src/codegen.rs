@@ -0,0 +1,237 @@
+mod rust;
+mod typescript;
+
+use std::collections::HashSet;
+use anyhow::{Result, anyhow};
+use crate::json_value::JsonValue;
+use crate::shape::{ObjectShape, Shape};
+
+pub use rust::generate_rust;
+pub use typescript::generate_typescript;
+
+#[derive(Debug, Clone)]
+pub struct CodegenOptions {
+    /// Name of the type of the whole document
+    pub root_name: String,
+    /// Strings with at most this many distinct values that repeat across the samples become
+    /// literal types where the language has them; 0 disables them
+    pub max_enum_values: usize,
+}
+
+impl Default for CodegenOptions {
+    fn default() -> Self {
+        CodegenOptions {
+            root_name: "Root".to_string(),
+            max_enum_values: 5,
+        }
+    }
+}
+
+/// Language independent description of the types of a set of samples
+#[derive(Debug)]
+struct Model {
+    root_name: String,
+    root: TypeRef,
+    /// Named object types, parents before their children
+    types: Vec<TypeDef>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TypeRef {
+    /// Nothing but null was observed, e.g. for the elements of arrays that were always empty
+    Any,
+    Bool,
+    Integer,
+    Number,
+    String,
+    StringLiterals(Vec<String>),
+    Array(Box<TypeRef>),
+    Named(String),
+    Union(Vec<TypeRef>),
+    Nullable(Box<TypeRef>),
+}
+
+#[derive(Debug)]
+struct TypeDef {
+    name: String,
+    fields: Vec<Field>,
+}
+
+#[derive(Debug)]
+struct Field {
+    key: String,
+    ty: TypeRef,
+    /// The member is missing in some of the objects
+    optional: bool,
+}
+
+impl Model {
+    /// Unifies the shapes of the samples and names object types after the keys they appear
+    /// under. `reserved` holds names that type names must not clash with.
+    fn build(samples: &[JsonValue], options: &CodegenOptions, reserved: &[&str]) -> Result<Self> {
+        if samples.is_empty() {
+            return Err(anyhow!("At least one sample is needed to generate types"));
+        }
+        let shape = Shape::from_samples(samples);
+        let mut builder = ModelBuilder {
+            options,
+            types: Vec::new(),
+            used_names: reserved.iter().map(|name| name.to_string()).collect(),
+            pending_name: None,
+        };
+        // The root object type or an alias for the root type gets the root name
+        let root_name = builder.unique_name(&options.root_name);
+        if shape.object.is_some() {
+            builder.pending_name = Some(root_name.clone());
+        }
+        let root = builder.type_of(&shape, &root_name);
+        Ok(Model { root_name, root, types: builder.types })
+    }
+}
+
+struct ModelBuilder<'a> {
+    options: &'a CodegenOptions,
+    types: Vec<TypeDef>,
+    used_names: HashSet<String>,
+    /// Name for the next object type instead of one derived from its key
+    pending_name: Option<String>,
+}
+
+impl ModelBuilder<'_> {
+    fn type_of(&mut self, shape: &Shape, name_hint: &str) -> TypeRef {
+        let mut variants = Vec::new();
+        if let Some(object) = &shape.object {
+            variants.push(TypeRef::Named(self.define(object, name_hint)));
+        }
+        if let Some(items) = &shape.array {
+            let item_type = if items.is_empty() { TypeRef::Any } else { self.type_of(items, &singular(name_hint)) };
+            variants.push(TypeRef::Array(Box::new(item_type)));
+        }
+        if let Some(strings) = &shape.strings {
+            match strings.enum_values(self.options.max_enum_values) {
+                Some(values) => variants.push(TypeRef::StringLiterals(values.to_vec())),
+                None => variants.push(TypeRef::String),
+            }
+        }
+        if let Some(range) = &shape.numbers {
+            variants.push(if range.integers { TypeRef::Integer } else { TypeRef::Number });
+        }
+        if shape.boolean {
+            variants.push(TypeRef::Bool);
+        }
+
+        let ty = match variants.len() {
+            0 => TypeRef::Any,
+            1 => variants.remove(0),
+            _ => TypeRef::Union(variants),
+        };
+        if shape.null { TypeRef::Nullable(Box::new(ty)) } else { ty }
+    }
+
+    fn define(&mut self, object: &ObjectShape, name_hint: &str) -> String {
+        let name = self.pending_name.take().unwrap_or_else(|| self.unique_name(name_hint));
+        let index = self.types.len();
+        self.types.push(TypeDef { name: name.clone(), fields: Vec::new() });
+
+        let fields = object
+            .names
+            .iter()
+            .map(|key| Field {
+                key: key.clone(),
+                ty: self.type_of(object.property(key), key),
+                optional: !object.is_required(key),
+            })
+            .collect();
+        self.types[index].fields = fields;
+        name
+    }
+
+    fn unique_name(&mut self, name_hint: &str) -> String {
+        let mut base = pascal_case(name_hint);
+        if !base.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            base.insert_str(0, "Type");
+        }
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while !self.used_names.insert(name.clone()) {
+            name = format!("{}{}", base, suffix);
+            suffix += 1;
+        }
+        name
+    }
+}
+
+/// Splits a key into words at non-alphanumeric characters and at lower to upper case changes,
+/// e.g. `firstName` and `first-name` into `first` and `name`
+fn words(key: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut previous: Option<char> = None;
+    for c in key.chars() {
+        if !c.is_ascii_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+        } else {
+            if c.is_ascii_uppercase() && previous.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit()) && !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            word.push(c);
+        }
+        previous = Some(c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+fn pascal_case(key: &str) -> String {
+    words(key)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            let first = chars.next().map(|c| c.to_ascii_uppercase()).into_iter();
+            first.chain(chars.map(|c| c.to_ascii_lowercase())).collect::<String>()
+        })
+        .collect()
+}
+
+fn snake_case(key: &str) -> String {
+    words(key).iter().map(|word| word.to_ascii_lowercase()).collect::<Vec<_>>().join("_")
+}
+
+/// Derives the name for the elements of an array from the key of the array, e.g. `Category`
+/// from `categories`
+fn singular(name: &str) -> String {
+    let lower = name.to_ascii_lowercase();
+    if let Some(stem) = name.strip_suffix("ies").filter(|stem| !stem.is_empty()) {
+        format!("{}y", stem)
+    } else if ["sses", "ches", "shes", "xes"].iter().any(|suffix| lower.ends_with(suffix)) {
+        name[..name.len() - 2].to_string()
+    } else if lower.ends_with('s') && !["ss", "us", "is"].iter().any(|suffix| lower.ends_with(suffix)) && name.len() > 1 {
+        name[..name.len() - 1].to_string()
+    } else {
+        format!("{}Item", pascal_case(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        assert_eq!(words("firstName"), vec!["first", "Name"]);
+        assert_eq!(words("HTTP-status code2"), vec!["HTTP", "status", "code2"]);
+        assert_eq!(pascal_case("first_name"), "FirstName");
+        assert_eq!(pascal_case("URL"), "Url");
+        assert_eq!(snake_case("firstName"), "first_name");
+        assert_eq!(snake_case("@id"), "id");
+        assert_eq!(singular("categories"), "category");
+        assert_eq!(singular("addresses"), "address");
+        assert_eq!(singular("items"), "item");
+        assert_eq!(singular("status"), "StatusItem");
+        assert_eq!(singular("data"), "DataItem");
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+use anyhow::Result;
+use crate::json_value::JsonValue;
+use super::{snake_case, CodegenOptions, Model, TypeRef};
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate", "do", "dyn", "else",
+    "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "macro", "match", "mod",
+    "move", "mut", "override", "priv", "pub", "ref", "return", "self", "static", "struct", "super", "trait", "true",
+    "try", "type", "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Keywords that cannot be used as raw identifiers
+const NON_RAW_KEYWORDS: &[&str] = &["crate", "self", "super"];
+
+/// Type names from the prelude and the derives the generated code uses
+const RESERVED_TYPE_NAMES: &[&str] = &["Box", "Deserialize", "Err", "None", "Ok", "Option", "Result", "Self", "Serialize", "Some", "String", "Vec"];
+
+/// Generates serde structs for the samples. Values of different types at the same path and
+/// values never observed become `serde_json::Value`, members that are null or missing in some
+/// samples become `Option`s.
+pub fn generate_rust(samples: &[JsonValue], options: &CodegenOptions) -> Result<String> {
+    let model = Model::build(samples, options, RESERVED_TYPE_NAMES)?;
+
+    let mut code = String::from("use serde::{Deserialize, Serialize};\n");
+    if model.root != TypeRef::Named(model.root_name.clone()) {
+        let _ = write!(code, "\npub type {} = {};\n", model.root_name, type_name(&model.root));
+    }
+
+    for def in &model.types {
+        let _ = write!(code, "\n#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\npub struct {} {{\n", def.name);
+        let mut used_idents = HashSet::new();
+        for field in &def.fields {
+            let ident = unique_ident(&field_ident(&field.key), &mut used_idents);
+            if ident.trim_start_matches("r#") != field.key {
+                let _ = writeln!(code, "    #[serde(rename = {:?})]", field.key);
+            }
+            let ty = match &field.ty {
+                TypeRef::Nullable(_) => type_name(&field.ty),
+                ty if field.optional => {
+                    code.push_str("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n");
+                    format!("Option<{}>", type_name(ty))
+                }
+                ty => type_name(ty),
+            };
+            let _ = writeln!(code, "    pub {}: {},", ident, ty);
+        }
+        code.push_str("}\n");
+    }
+    Ok(code)
+}
+
+fn type_name(ty: &TypeRef) -> String {
+    match ty {
+        TypeRef::Any | TypeRef::Union(_) => "serde_json::Value".to_string(),
+        TypeRef::Bool => "bool".to_string(),
+        TypeRef::Integer => "i64".to_string(),
+        TypeRef::Number => "f64".to_string(),
+        TypeRef::String | TypeRef::StringLiterals(_) => "String".to_string(),
+        TypeRef::Array(items) => format!("Vec<{}>", type_name(items)),
+        TypeRef::Named(name) => name.clone(),
+        TypeRef::Nullable(ty) => format!("Option<{}>", type_name(ty)),
+    }
+}
+
+/// Converts a key into a snake case identifier, e.g. `r#type` for `type` or `_1st` for `1st`
+fn field_ident(key: &str) -> String {
+    let ident = snake_case(key);
+    if ident.is_empty() {
+        return "field".to_string();
+    }
+    if ident.starts_with(|c: char| c.is_ascii_digit()) {
+        return format!("_{}", ident);
+    }
+    if NON_RAW_KEYWORDS.contains(&ident.as_str()) {
+        return format!("{}_", ident);
+    }
+    if KEYWORDS.contains(&ident.as_str()) {
+        return format!("r#{}", ident);
+    }
+    ident
+}
+
+fn unique_ident(ident: &str, used: &mut HashSet<String>) -> String {
+    let mut unique = ident.to_string();
+    let mut suffix = 2;
+    while !used.insert(unique.clone()) {
+        unique = format!("{}_{}", ident, suffix);
+        suffix += 1;
+    }
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    fn generate(samples: &[&str]) -> String {
+        let samples: Vec<JsonValue> = samples.iter().map(|sample| parse_json(sample).unwrap()).collect();
+        generate_rust(&samples, &CodegenOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn test_structs() {
+        let code = generate(&[
+            r#"{"id": 1, "firstName": "Ann", "type": "admin", "address": {"city": "Berlin", "zip-code": "10115"},
+                "tags": ["a"], "items": [{"sku": "x", "price": 2.5}, {"sku": "y", "price": 3, "note": null}]}"#,
+            r#"{"id": 2, "firstName": "Bob", "type": "user", "address": null, "tags": [], "items": [], "extra": [1, "a"]}"#,
+        ]);
+        assert_eq!(code, r#"use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Root {
+    pub id: i64,
+    #[serde(rename = "firstName")]
+    pub first_name: String,
+    pub r#type: String,
+    pub address: Option<Address>,
+    pub tags: Vec<String>,
+    pub items: Vec<Item>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra: Option<Vec<serde_json::Value>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Address {
+    pub city: String,
+    #[serde(rename = "zip-code")]
+    pub zip_code: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Item {
+    pub sku: String,
+    pub price: f64,
+    pub note: Option<serde_json::Value>,
+}
+"#);
+    }
+
+    #[test]
+    fn test_names() {
+        let code = generate(&[r#"[{"self": 1, "1st": true, "a_b": 1, "a-b": 2, "string": {"x": null}, "": []}]"#]);
+        assert_eq!(code, r#"use serde::{Deserialize, Serialize};
+
+pub type Root = Vec<RootItem>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RootItem {
+    #[serde(rename = "self")]
+    pub self_: i64,
+    #[serde(rename = "1st")]
+    pub _1st: bool,
+    pub a_b: i64,
+    #[serde(rename = "a-b")]
+    pub a_b_2: i64,
+    pub string: String2,
+    #[serde(rename = "")]
+    pub field: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct String2 {
+    pub x: Option<serde_json::Value>,
+}
+"#);
+        assert_eq!(generate(&["1.5"]), "use serde::{Deserialize, Serialize};\n\npub type Root = f64;\n");
+    }
+}
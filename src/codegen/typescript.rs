@@ -0,0 +1,127 @@
+use std::fmt::Write;
+use anyhow::Result;
+use crate::json_value::JsonValue;
+use crate::pretty_printer::escape_string;
+use super::{CodegenOptions, Model, TypeRef};
+
+/// Global types that generated declarations must not shadow
+const RESERVED_TYPE_NAMES: &[&str] = &[
+    "Array", "Boolean", "Date", "Error", "Function", "Map", "Number", "Object", "Partial", "Promise", "Readonly",
+    "Record", "RegExp", "Set", "String", "Symbol",
+];
+
+/// Generates TypeScript declarations for the samples. Members missing in some samples become
+/// optional properties, values of different types become unions and repeating strings with few
+/// distinct values become unions of string literals.
+pub fn generate_typescript(samples: &[JsonValue], options: &CodegenOptions) -> Result<String> {
+    let model = Model::build(samples, options, RESERVED_TYPE_NAMES)?;
+
+    let mut code = String::new();
+    if model.root != TypeRef::Named(model.root_name.clone()) {
+        let _ = writeln!(code, "export type {} = {};", model.root_name, type_name(&model.root));
+    }
+
+    for def in &model.types {
+        if !code.is_empty() {
+            code.push('\n');
+        }
+        let _ = writeln!(code, "export interface {} {{", def.name);
+        for field in &def.fields {
+            let optional = if field.optional { "?" } else { "" };
+            let _ = writeln!(code, "  {}{}: {};", property_name(&field.key), optional, type_name(&field.ty));
+        }
+        code.push_str("}\n");
+    }
+    Ok(code)
+}
+
+fn type_name(ty: &TypeRef) -> String {
+    match ty {
+        TypeRef::Any => "unknown".to_string(),
+        TypeRef::Bool => "boolean".to_string(),
+        TypeRef::Integer | TypeRef::Number => "number".to_string(),
+        TypeRef::String => "string".to_string(),
+        TypeRef::StringLiterals(values) => {
+            values.iter().map(|value| format!("\"{}\"", escape_string(value))).collect::<Vec<_>>().join(" | ")
+        }
+        TypeRef::Array(items) if needs_parentheses(items) => format!("({})[]", type_name(items)),
+        TypeRef::Array(items) => format!("{}[]", type_name(items)),
+        TypeRef::Named(name) => name.clone(),
+        TypeRef::Union(variants) => variants.iter().map(type_name).collect::<Vec<_>>().join(" | "),
+        TypeRef::Nullable(ty) if **ty == TypeRef::Any => "null".to_string(),
+        TypeRef::Nullable(ty) => format!("{} | null", type_name(ty)),
+    }
+}
+
+/// True if the type is written as a union and has to be parenthesized as an array element
+fn needs_parentheses(ty: &TypeRef) -> bool {
+    match ty {
+        TypeRef::Nullable(ty) => **ty != TypeRef::Any,
+        TypeRef::Union(_) => true,
+        TypeRef::StringLiterals(values) => values.len() > 1,
+        _ => false,
+    }
+}
+
+/// Keeps keys that are valid identifiers and quotes all others
+fn property_name(key: &str) -> String {
+    let mut chars = key.chars();
+    let is_identifier = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if is_identifier { key.to_string() } else { format!("\"{}\"", escape_string(key)) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    fn generate(samples: &[&str]) -> String {
+        let samples: Vec<JsonValue> = samples.iter().map(|sample| parse_json(sample).unwrap()).collect();
+        generate_typescript(&samples, &CodegenOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn test_interfaces() {
+        let code = generate(&[
+            r#"{"id": 1, "status": "open", "owner": {"login": "ann"}, "tags": ["a"], "zip-code": "1",
+                "items": [{"sku": "x"}, {"sku": "y", "price": 3}], "mixed": [1, "a", null]}"#,
+            r#"{"id": 2, "status": "closed", "owner": null, "tags": [], "items": [], "mixed": [], "extra": [[true]]}"#,
+            r#"{"id": 3, "status": "open", "owner": {"login": "bob", "admin": true}, "tags": [], "items": [], "mixed": []}"#,
+        ]);
+        assert_eq!(code, r#"export interface Root {
+  id: number;
+  status: "open" | "closed";
+  owner: Owner | null;
+  tags: string[];
+  "zip-code"?: string;
+  items: Item[];
+  mixed: (string | number | null)[];
+  extra?: boolean[][];
+}
+
+export interface Owner {
+  login: string;
+  admin?: boolean;
+}
+
+export interface Item {
+  sku: string;
+  price?: number;
+}
+"#);
+    }
+
+    #[test]
+    fn test_root_alias() {
+        assert_eq!(generate(&[r#"[{"String": "a\"b"}, 1]"#]), r#"export type Root = (RootItem | number)[];
+
+export interface RootItem {
+  String: string;
+}
+"#);
+        assert_eq!(generate(&[r#"["a", "b", "a"]"#]), "export type Root = (\"a\" | \"b\")[];\n");
+        assert_eq!(generate(&["[]"]), "export type Root = unknown[];\n");
+        assert_eq!(generate(&["[null]"]), "export type Root = null[];\n");
+    }
+}
@@ -0,0 +1,510 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::BuildHasher;
+use crate::json_path::{NormalizedPath, PathElement};
+use crate::json_value::{object_from_entries, JsonValue};
+
+/// Conversion of a `JsonValue` into a Rust value. It can be derived for structs and enums
+/// with the `derive` feature, see [`ToJson`] for the representation.
+pub trait FromJson: Sized {
+    fn from_json(value: &JsonValue) -> Result<Self, ConversionError>;
+
+    /// Value for a missing object member; only `Option`s may be missing
+    fn from_missing() -> Option<Self> {
+        None
+    }
+}
+
+/// Conversion of a Rust value into a `JsonValue`.
+///
+/// Derived implementations convert structs with named fields into objects, tuple structs into
+/// arrays, newtype structs into their only field and unit structs into null. Enums are
+/// externally tagged: unit variants become strings, other variants objects with the variant
+/// name as their only member. `#[json(rename = "name")]` changes the name of a field or
+/// variant.
+pub trait ToJson {
+    fn to_json(&self) -> JsonValue;
+}
+
+/// Error of converting a `JsonValue`, located by the path of the value that failed to convert
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversionError {
+    pub path: NormalizedPath,
+    pub message: String,
+}
+
+impl ConversionError {
+    pub fn new(message: impl Into<String>) -> Self {
+        ConversionError { path: NormalizedPath::default(), message: message.into() }
+    }
+
+    pub fn invalid_type(expected: &str, found: &JsonValue) -> Self {
+        ConversionError::new(format!("expected {}, found {}", expected, found.kind()))
+    }
+
+    /// Prefixes the path with the location of the value within its parent
+    pub fn at(mut self, element: PathElement) -> Self {
+        self.path.0.insert(0, element);
+        self
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+/// Helpers for derived implementations
+#[doc(hidden)]
+pub mod __private {
+    use super::*;
+
+    pub fn members(value: &JsonValue) -> Result<&HashMap<String, JsonValue>, ConversionError> {
+        match value {
+            JsonValue::Object(_, members) => Ok(members),
+            _ => Err(ConversionError::invalid_type("an object", value)),
+        }
+    }
+
+    pub fn member<T: FromJson>(members: &HashMap<String, JsonValue>, name: &str) -> Result<T, ConversionError> {
+        match members.get(name) {
+            Some(value) => T::from_json(value).map_err(|error| error.at(PathElement::Name(name.to_string()))),
+            None => T::from_missing()
+                .ok_or_else(|| ConversionError::new("missing member").at(PathElement::Name(name.to_string()))),
+        }
+    }
+
+    pub fn elements(value: &JsonValue, len: usize) -> Result<&[JsonValue], ConversionError> {
+        match value {
+            JsonValue::Array(elements) if elements.len() == len => Ok(elements),
+            JsonValue::Array(elements) => {
+                Err(ConversionError::new(format!("expected an array of {} elements, found {}", len, elements.len())))
+            }
+            _ => Err(ConversionError::invalid_type("an array", value)),
+        }
+    }
+
+    pub fn element<T: FromJson>(elements: &[JsonValue], index: usize) -> Result<T, ConversionError> {
+        T::from_json(&elements[index]).map_err(|error| error.at(PathElement::Index(index)))
+    }
+
+    /// The name and the content of an externally tagged variant with content
+    pub fn variant(value: &JsonValue) -> Result<(&str, &JsonValue), ConversionError> {
+        match value {
+            JsonValue::Object(names, members) if names.len() == 1 => Ok((&names[0], &members[&names[0]])),
+            _ => Err(ConversionError::invalid_type("a string or an object with a single member", value)),
+        }
+    }
+
+    /// Locates errors of converting the content of a variant at the variant name
+    pub fn in_variant<T>(name: &str, convert: impl FnOnce() -> Result<T, ConversionError>) -> Result<T, ConversionError> {
+        convert().map_err(|error| error.at(PathElement::Name(name.to_string())))
+    }
+
+    pub fn unknown_variant(name: &str) -> ConversionError {
+        ConversionError::new(format!("unknown variant '{}'", name))
+    }
+
+    pub fn object(entries: Vec<(String, JsonValue)>) -> JsonValue {
+        object_from_entries(entries)
+    }
+}
+
+impl FromJson for JsonValue {
+    fn from_json(value: &JsonValue) -> Result<Self, ConversionError> {
+        Ok(value.clone())
+    }
+}
+
+impl ToJson for JsonValue {
+    fn to_json(&self) -> JsonValue {
+        self.clone()
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(value: &JsonValue) -> Result<Self, ConversionError> {
+        match value {
+            JsonValue::Bool(b) => Ok(*b),
+            _ => Err(ConversionError::invalid_type("a boolean", value)),
+        }
+    }
+}
+
+impl ToJson for bool {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Bool(*self)
+    }
+}
+
+/// Exclusive upper bound of an integer type as an exact power of two; `MAX as f64` rounds up
+/// to that bound for 64-bit types
+fn integer_end(signed: bool, bits: u32) -> f64 {
+    2f64.powi((bits - signed as u32) as i32)
+}
+
+macro_rules! integer_conversions {
+    ($($type:ty),*) => {
+        $(
+            impl FromJson for $type {
+                fn from_json(value: &JsonValue) -> Result<Self, ConversionError> {
+                    match value {
+                        JsonValue::Number(n) if n.fract() != 0.0 => {
+                            Err(ConversionError::new(format!("expected an integer, found {}", n)))
+                        }
+                        JsonValue::Number(n)
+                            if !(<$type>::MIN as f64..integer_end(<$type>::MIN != 0, <$type>::BITS)).contains(n) =>
+                        {
+                            Err(ConversionError::new(format!("{} is out of range for {}", n, stringify!($type))))
+                        }
+                        JsonValue::Number(n) => Ok(*n as $type),
+                        _ => Err(ConversionError::invalid_type("an integer", value)),
+                    }
+                }
+            }
+
+            impl ToJson for $type {
+                fn to_json(&self) -> JsonValue {
+                    JsonValue::Number(*self as f64)
+                }
+            }
+        )*
+    };
+}
+
+integer_conversions!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+macro_rules! float_conversions {
+    ($($type:ty),*) => {
+        $(
+            impl FromJson for $type {
+                fn from_json(value: &JsonValue) -> Result<Self, ConversionError> {
+                    match value {
+                        JsonValue::Number(n) => Ok(*n as $type),
+                        _ => Err(ConversionError::invalid_type("a number", value)),
+                    }
+                }
+            }
+
+            /// Non-finite numbers have no JSON representation and become null
+            impl ToJson for $type {
+                fn to_json(&self) -> JsonValue {
+                    if self.is_finite() { JsonValue::Number(*self as f64) } else { JsonValue::Null }
+                }
+            }
+        )*
+    };
+}
+
+float_conversions!(f32, f64);
+
+impl FromJson for String {
+    fn from_json(value: &JsonValue) -> Result<Self, ConversionError> {
+        match value {
+            JsonValue::String(s) => Ok(s.clone()),
+            _ => Err(ConversionError::invalid_type("a string", value)),
+        }
+    }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::String(self.clone())
+    }
+}
+
+impl ToJson for str {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::String(self.to_string())
+    }
+}
+
+impl FromJson for char {
+    fn from_json(value: &JsonValue) -> Result<Self, ConversionError> {
+        let s = String::from_json(value)?;
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(ConversionError::new(format!("expected a single character, found {:?}", s))),
+        }
+    }
+}
+
+impl ToJson for char {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::String(self.to_string())
+    }
+}
+
+impl FromJson for () {
+    fn from_json(value: &JsonValue) -> Result<Self, ConversionError> {
+        match value {
+            JsonValue::Null => Ok(()),
+            _ => Err(ConversionError::invalid_type("null", value)),
+        }
+    }
+}
+
+impl ToJson for () {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Null
+    }
+}
+
+/// Null and missing object members are `None`
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, ConversionError> {
+        match value {
+            JsonValue::Null => Ok(None),
+            _ => T::from_json(value).map(Some),
+        }
+    }
+
+    fn from_missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> JsonValue {
+        self.as_ref().map_or(JsonValue::Null, T::to_json)
+    }
+}
+
+impl<T: FromJson> FromJson for Box<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, ConversionError> {
+        T::from_json(value).map(Box::new)
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for Box<T> {
+    fn to_json(&self) -> JsonValue {
+        (**self).to_json()
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> JsonValue {
+        (**self).to_json()
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, ConversionError> {
+        match value {
+            JsonValue::Array(elements) => (0..elements.len()).map(|i| __private::element(elements, i)).collect(),
+            _ => Err(ConversionError::invalid_type("an array", value)),
+        }
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> JsonValue {
+        self.as_slice().to_json()
+    }
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Array(self.iter().map(T::to_json).collect())
+    }
+}
+
+/// Members of the object in document order
+fn entries<T: FromJson>(
+    value: &JsonValue,
+) -> Result<impl Iterator<Item = Result<(String, T), ConversionError>>, ConversionError> {
+    match value {
+        JsonValue::Object(names, members) => Ok(names
+            .iter()
+            .map(|name| __private::member(members, name).map(|member| (name.clone(), member)))),
+        _ => Err(ConversionError::invalid_type("an object", value)),
+    }
+}
+
+impl<T: FromJson, S: BuildHasher + Default> FromJson for HashMap<String, T, S> {
+    fn from_json(value: &JsonValue) -> Result<Self, ConversionError> {
+        entries(value)?.collect()
+    }
+}
+
+/// Members are sorted by name since hash maps have no order
+impl<T: ToJson, S> ToJson for HashMap<String, T, S> {
+    fn to_json(&self) -> JsonValue {
+        let mut entries: Vec<(String, JsonValue)> = self.iter().map(|(name, value)| (name.clone(), value.to_json())).collect();
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        object_from_entries(entries)
+    }
+}
+
+impl<T: FromJson> FromJson for BTreeMap<String, T> {
+    fn from_json(value: &JsonValue) -> Result<Self, ConversionError> {
+        entries(value)?.collect()
+    }
+}
+
+impl<T: ToJson> ToJson for BTreeMap<String, T> {
+    fn to_json(&self) -> JsonValue {
+        object_from_entries(self.iter().map(|(name, value)| (name.clone(), value.to_json())).collect())
+    }
+}
+
+/// Tuples are arrays with one element per field
+macro_rules! tuple_conversions {
+    ($($len:literal => ($($type:ident $index:tt),+)),*) => {
+        $(
+            impl<$($type: FromJson),+> FromJson for ($($type,)+) {
+                fn from_json(value: &JsonValue) -> Result<Self, ConversionError> {
+                    let elements = __private::elements(value, $len)?;
+                    Ok(($(__private::element::<$type>(elements, $index)?,)+))
+                }
+            }
+
+            impl<$($type: ToJson),+> ToJson for ($($type,)+) {
+                fn to_json(&self) -> JsonValue {
+                    JsonValue::Array(vec![$(self.$index.to_json()),+])
+                }
+            }
+        )*
+    };
+}
+
+tuple_conversions!(
+    1 => (A 0),
+    2 => (A 0, B 1),
+    3 => (A 0, B 1, C 2),
+    4 => (A 0, B 1, C 2, D 3),
+    5 => (A 0, B 1, C 2, D 3, E 4),
+    6 => (A 0, B 1, C 2, D 3, E 4, F 5)
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+    use medea_derive::{FromJson, ToJson};
+
+    #[derive(Debug, PartialEq, FromJson, ToJson)]
+    struct Person {
+        name: String,
+        age: u8,
+        #[json(rename = "e-mail")]
+        email: Option<String>,
+        address: Address,
+        scores: Vec<(String, f64)>,
+        role: Role,
+    }
+
+    #[derive(Debug, PartialEq, FromJson, ToJson)]
+    struct Address {
+        city: String,
+        zip: Option<u32>,
+    }
+
+    #[derive(Debug, PartialEq, FromJson, ToJson)]
+    enum Role {
+        Guest,
+        #[json(rename = "member")]
+        Member(u32),
+        Admin { level: i64 },
+        Pair(String, char),
+    }
+
+    #[derive(Debug, PartialEq, FromJson, ToJson)]
+    struct Id(u64);
+
+    #[derive(Debug, PartialEq, FromJson, ToJson)]
+    struct Wrapper<T> {
+        items: Vec<T>,
+    }
+
+    fn convert<T: FromJson>(json: &str) -> Result<T, ConversionError> {
+        T::from_json(&parse_json(json).unwrap())
+    }
+
+    #[test]
+    fn test_primitives() {
+        assert_eq!(convert::<i32>("-3"), Ok(-3));
+        assert_eq!(convert::<f32>("1.5"), Ok(1.5));
+        assert_eq!(convert::<char>(r#""x""#), Ok('x'));
+        assert_eq!(convert::<Option<bool>>("null"), Ok(None));
+        assert_eq!(convert::<(u8, String)>(r#"[1, "a"]"#), Ok((1, "a".to_string())));
+        assert_eq!(convert::<u8>("256").unwrap_err().to_string(), "$: 256 is out of range for u8");
+        assert_eq!(convert::<u8>("255"), Ok(255));
+        assert_eq!(convert::<i8>("-128"), Ok(-128));
+        assert_eq!(convert::<i64>("-9223372036854775808"), Ok(i64::MIN));
+        assert!(convert::<i64>("9223372036854775808").is_err());
+        assert!(convert::<u64>("18446744073709551616").is_err());
+        assert!(convert::<u64>("-1").is_err());
+        assert_eq!(convert::<u8>("1.5").unwrap_err().to_string(), "$: expected an integer, found 1.5");
+        assert_eq!(convert::<String>("[]").unwrap_err().to_string(), "$: expected a string, found an array");
+        assert_eq!(convert::<(u8, u8)>("[1]").unwrap_err().to_string(), "$: expected an array of 2 elements, found 1");
+        assert_eq!(f64::NAN.to_json(), JsonValue::Null);
+        assert_eq!((1, "a", [true].as_slice()).to_json(), parse_json(r#"[1, "a", [true]]"#).unwrap());
+    }
+
+    #[test]
+    fn test_collections() {
+        let map: BTreeMap<String, Vec<u8>> = convert(r#"{"b": [1], "a": []}"#).unwrap();
+        assert_eq!(map, BTreeMap::from([("a".to_string(), vec![]), ("b".to_string(), vec![1])]));
+        assert_eq!(map.to_json(), parse_json(r#"{"a": [], "b": [1]}"#).unwrap());
+
+        let map: HashMap<String, Option<String>> = convert(r#"{"y": null, "x": "1"}"#).unwrap();
+        assert_eq!(map.to_json(), parse_json(r#"{"x": "1", "y": null}"#).unwrap());
+
+        let error = convert::<HashMap<String, Vec<bool>>>(r#"{"a": [true, 1]}"#).unwrap_err();
+        assert_eq!(error.path.to_string(), "$['a'][1]");
+        assert_eq!(error.message, "expected a boolean, found a number");
+    }
+
+    #[test]
+    fn test_derive() {
+        let json = parse_json(
+            r#"{"name": "Ann", "age": 42, "e-mail": null, "address": {"city": "Berlin", "zip": 10115},
+                "scores": [["math", 1.5]], "role": {"Admin": {"level": -1}}}"#,
+        )
+        .unwrap();
+        let person = Person::from_json(&json).unwrap();
+        assert_eq!(person, Person {
+            name: "Ann".to_string(),
+            age: 42,
+            email: None,
+            address: Address { city: "Berlin".to_string(), zip: Some(10115) },
+            scores: vec![("math".to_string(), 1.5)],
+            role: Role::Admin { level: -1 },
+        });
+        assert_eq!(person.to_json(), json);
+
+        for (role, json) in [
+            (Role::Guest, r#""Guest""#),
+            (Role::Member(7), r#"{"member": 7}"#),
+            (Role::Pair("x".to_string(), 'y'), r#"{"Pair": ["x", "y"]}"#),
+        ] {
+            assert_eq!(role.to_json(), parse_json(json).unwrap());
+            assert_eq!(convert::<Role>(json), Ok(role));
+        }
+        assert_eq!(Id(3).to_json(), JsonValue::Number(3.0));
+        assert_eq!(convert::<Id>("3"), Ok(Id(3)));
+        assert_eq!(convert::<Wrapper<bool>>(r#"{"items": [true]}"#), Ok(Wrapper { items: vec![true] }));
+        assert_eq!(convert::<Address>(r#"{"city": "Bonn"}"#), Ok(Address { city: "Bonn".to_string(), zip: None }));
+    }
+
+    #[test]
+    fn test_derive_errors() {
+        let error = convert::<Person>(
+            r#"{"name": "Ann", "age": 42, "address": {"city": "Berlin", "zip": "x"}, "scores": [], "role": "Guest"}"#,
+        )
+        .unwrap_err();
+        assert_eq!(error.to_string(), "$['address']['zip']: expected an integer, found a string");
+
+        let error = convert::<Address>(r#"{"zip": 1}"#).unwrap_err();
+        assert_eq!(error.to_string(), "$['city']: missing member");
+        let error = convert::<Role>(r#"{"Pair": ["x", "yz"]}"#).unwrap_err();
+        assert_eq!(error.to_string(), "$['Pair'][1]: expected a single character, found \"yz\"");
+        assert_eq!(convert::<Role>(r#""Admin""#).unwrap_err().to_string(), "$: unknown variant 'Admin'");
+        assert_eq!(convert::<Role>(r#"{"Nope": 1}"#).unwrap_err().to_string(), "$: unknown variant 'Nope'");
+    }
+}
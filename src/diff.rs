@@ -0,0 +1,321 @@
+use std::fmt;
+use crate::json_path::{NormalizedPath, PathElement};
+use crate::json_value::JsonValue;
+use crate::pretty_printer::to_compact_string;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiffKind {
+    Added,
+    Removed,
+    Changed,
+    /// The members of an object are the same but in a different order. `old` and `new` hold
+    /// the member names in their respective order.
+    Reordered,
+}
+
+/// A single difference between two documents
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffEntry {
+    pub path: NormalizedPath,
+    pub kind: DiffKind,
+    pub old: Option<JsonValue>,
+    pub new: Option<JsonValue>,
+}
+
+impl fmt::Display for DiffEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = |value: &Option<JsonValue>| value.as_ref().map(to_compact_string).unwrap_or_default();
+        match self.kind {
+            DiffKind::Added => write!(f, "+ {}: {}", self.path, value(&self.new)),
+            DiffKind::Removed => write!(f, "- {}: {}", self.path, value(&self.old)),
+            DiffKind::Changed => write!(f, "~ {}: {} -> {}", self.path, value(&self.old), value(&self.new)),
+            DiffKind::Reordered => {
+                write!(f, "~ {}: key order {} -> {}", self.path, value(&self.old), value(&self.new))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    /// Do not report objects whose members only differ in order
+    pub ignore_key_order: bool,
+    /// Compare arrays as multisets
+    pub ignore_array_order: bool,
+    /// Numbers differing by at most this amount are considered equal
+    pub float_tolerance: f64,
+}
+
+/// Compares two documents structurally and returns their differences.
+///
+/// Array elements are aligned by their longest common subsequence, so inserting an element
+/// reports a single addition rather than changes to all following elements. Arrays whose
+/// differing parts are too long for that are compared element by element.
+pub fn diff(old: &JsonValue, new: &JsonValue, options: &DiffOptions) -> Vec<DiffEntry> {
+    let mut differ = Differ {
+        options,
+        path: Vec::new(),
+        entries: Vec::new(),
+    };
+    differ.diff_values(old, new);
+    differ.entries
+}
+
+/// Largest table of common subsequence lengths built for aligning two arrays (32 MiB)
+const MAX_LCS_CELLS: usize = 1 << 22;
+
+struct Differ<'a> {
+    options: &'a DiffOptions,
+    path: Vec<PathElement>,
+    entries: Vec<DiffEntry>,
+}
+
+impl Differ<'_> {
+    fn diff_values(&mut self, old: &JsonValue, new: &JsonValue) {
+        match (old, new) {
+            (JsonValue::Object(old_names, old_members), JsonValue::Object(new_names, new_members)) => {
+                if !self.options.ignore_key_order {
+                    let old_order: Vec<&String> = old_names.iter().filter(|name| new_members.contains_key(*name)).collect();
+                    let new_order: Vec<&String> = new_names.iter().filter(|name| old_members.contains_key(*name)).collect();
+                    if old_order != new_order {
+                        let names = |order: Vec<&String>| {
+                            JsonValue::Array(order.into_iter().map(|name| JsonValue::String(name.clone())).collect())
+                        };
+                        self.push(DiffKind::Reordered, Some(names(old_order)), Some(names(new_order)));
+                    }
+                }
+                for name in old_names {
+                    self.path.push(PathElement::Name(name.clone()));
+                    match new_members.get(name) {
+                        Some(new_value) => self.diff_values(&old_members[name], new_value),
+                        None => self.push(DiffKind::Removed, Some(old_members[name].clone()), None),
+                    }
+                    self.path.pop();
+                }
+                for name in new_names.iter().filter(|name| !old_members.contains_key(*name)) {
+                    self.path.push(PathElement::Name(name.clone()));
+                    self.push(DiffKind::Added, None, Some(new_members[name].clone()));
+                    self.path.pop();
+                }
+            }
+            (JsonValue::Array(old_elements), JsonValue::Array(new_elements)) => {
+                if self.options.ignore_array_order {
+                    self.diff_unordered(old_elements, new_elements);
+                } else {
+                    self.diff_ordered(old_elements, new_elements);
+                }
+            }
+            _ => {
+                if !self.equal(old, new) {
+                    self.push(DiffKind::Changed, Some(old.clone()), Some(new.clone()));
+                }
+            }
+        }
+    }
+
+    fn diff_ordered(&mut self, old: &[JsonValue], new: &[JsonValue]) {
+        // Equal elements at both ends need no alignment
+        let start = old.iter().zip(new).take_while(|(a, b)| self.equal(a, b)).count();
+        let common_end = old[start..].iter().rev().zip(new[start..].iter().rev()).take_while(|(a, b)| self.equal(a, b)).count();
+        let (old_end, new_end) = (old.len() - common_end, new.len() - common_end);
+
+        let mut removed = Vec::new();
+        let mut added = Vec::new();
+        if (old_end - start + 1).saturating_mul(new_end - start + 1) > MAX_LCS_CELLS {
+            removed.extend(start..old_end);
+            added.extend(start..new_end);
+            self.diff_replaced(old, new, &mut removed, &mut added);
+            return;
+        }
+
+        // lengths[i][j] is the length of the longest common subsequence of old[start + i..old_end]
+        // and new[start + j..new_end]
+        let (rows, columns) = (old_end - start, new_end - start);
+        let mut lengths = vec![vec![0usize; columns + 1]; rows + 1];
+        for i in (0..rows).rev() {
+            for j in (0..columns).rev() {
+                lengths[i][j] = if self.equal(&old[start + i], &new[start + j]) {
+                    lengths[i + 1][j + 1] + 1
+                } else {
+                    lengths[i + 1][j].max(lengths[i][j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < rows || j < columns {
+            if i < rows && j < columns && self.equal(&old[start + i], &new[start + j]) {
+                self.diff_replaced(old, new, &mut removed, &mut added);
+                i += 1;
+                j += 1;
+            } else if j < columns && (i == rows || lengths[i][j + 1] >= lengths[i + 1][j]) {
+                added.push(start + j);
+                j += 1;
+            } else {
+                removed.push(start + i);
+                i += 1;
+            }
+        }
+        self.diff_replaced(old, new, &mut removed, &mut added);
+    }
+
+    /// Compares the elements removed and added between two common elements pairwise, so a
+    /// modified element is reported as changes inside it.
+    fn diff_replaced(&mut self, old: &[JsonValue], new: &[JsonValue], removed: &mut Vec<usize>, added: &mut Vec<usize>) {
+        let paired = removed.len().min(added.len());
+        for (&i, &j) in removed.iter().zip(added.iter()) {
+            self.path.push(PathElement::Index(j));
+            self.diff_values(&old[i], &new[j]);
+            self.path.pop();
+        }
+        for &i in &removed[paired..] {
+            self.path.push(PathElement::Index(i));
+            self.push(DiffKind::Removed, Some(old[i].clone()), None);
+            self.path.pop();
+        }
+        for &j in &added[paired..] {
+            self.path.push(PathElement::Index(j));
+            self.push(DiffKind::Added, None, Some(new[j].clone()));
+            self.path.pop();
+        }
+        removed.clear();
+        added.clear();
+    }
+
+    fn diff_unordered(&mut self, old: &[JsonValue], new: &[JsonValue]) {
+        let mut matched = vec![false; old.len()];
+        let mut added = Vec::new();
+        for (j, new_element) in new.iter().enumerate() {
+            match (0..old.len()).find(|&i| !matched[i] && self.equal(&old[i], new_element)) {
+                Some(i) => matched[i] = true,
+                None => added.push(j),
+            }
+        }
+        for i in (0..old.len()).filter(|&i| !matched[i]) {
+            self.path.push(PathElement::Index(i));
+            self.push(DiffKind::Removed, Some(old[i].clone()), None);
+            self.path.pop();
+        }
+        for j in added {
+            self.path.push(PathElement::Index(j));
+            self.push(DiffKind::Added, None, Some(new[j].clone()));
+            self.path.pop();
+        }
+    }
+
+    /// Equality according to the options, i.e. no differences would be reported.
+    fn equal(&self, old: &JsonValue, new: &JsonValue) -> bool {
+        match (old, new) {
+            (JsonValue::Number(old), JsonValue::Number(new)) => (old - new).abs() <= self.options.float_tolerance,
+            (JsonValue::Array(old), JsonValue::Array(new)) => {
+                if old.len() != new.len() {
+                    return false;
+                }
+                if !self.options.ignore_array_order {
+                    return old.iter().zip(new).all(|(o, n)| self.equal(o, n));
+                }
+                let mut matched = vec![false; old.len()];
+                new.iter().all(|n| match (0..old.len()).find(|&i| !matched[i] && self.equal(&old[i], n)) {
+                    Some(i) => {
+                        matched[i] = true;
+                        true
+                    }
+                    None => false,
+                })
+            }
+            (JsonValue::Object(old_names, old_members), JsonValue::Object(new_names, new_members)) => {
+                old_members.len() == new_members.len()
+                    && (self.options.ignore_key_order || old_names == new_names)
+                    && old_members
+                        .iter()
+                        .all(|(name, o)| new_members.get(name).is_some_and(|n| self.equal(o, n)))
+            }
+            _ => old == new,
+        }
+    }
+
+    fn push(&mut self, kind: DiffKind, old: Option<JsonValue>, new: Option<JsonValue>) {
+        self.entries.push(DiffEntry {
+            path: NormalizedPath(self.path.clone()),
+            kind,
+            old,
+            new,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    fn lines(old: &str, new: &str, options: &DiffOptions) -> Vec<String> {
+        let old = parse_json(old).unwrap();
+        let new = parse_json(new).unwrap();
+        diff(&old, &new, options).iter().map(|entry| entry.to_string()).collect()
+    }
+
+    #[test]
+    fn test_objects() {
+        let old = r#"{"name": "medea", "version": 1, "owner": {"id": 7, "tags": ["a"]}}"#;
+        let new = r#"{"name": "medea", "owner": {"id": 8, "tags": ["a"]}, "license": "MIT"}"#;
+        assert_eq!(lines(old, new, &DiffOptions::default()), vec![
+            "- $['version']: 1",
+            "~ $['owner']['id']: 7 -> 8",
+            "+ $['license']: \"MIT\"",
+        ]);
+        assert!(lines(old, old, &DiffOptions::default()).is_empty());
+
+        let entries = diff(&parse_json(old).unwrap(), &parse_json(new).unwrap(), &DiffOptions::default());
+        assert_eq!(entries[1], DiffEntry {
+            path: NormalizedPath(vec![PathElement::Name("owner".to_string()), PathElement::Name("id".to_string())]),
+            kind: DiffKind::Changed,
+            old: Some(JsonValue::Number(7.0)),
+            new: Some(JsonValue::Number(8.0)),
+        });
+    }
+
+    #[test]
+    fn test_key_order() {
+        let old = r#"{"a": 1, "b": 2, "c": 3}"#;
+        let new = r#"{"c": 3, "a": 1, "b": 2}"#;
+        assert_eq!(lines(old, new, &DiffOptions::default()), vec![
+            r#"~ $: key order ["a","b","c"] -> ["c","a","b"]"#,
+        ]);
+        let options = DiffOptions { ignore_key_order: true, ..DiffOptions::default() };
+        assert!(lines(old, new, &options).is_empty());
+        assert!(lines(&format!("[{}]", old), &format!("[{}]", new), &options).is_empty());
+    }
+
+    #[test]
+    fn test_arrays() {
+        assert_eq!(lines("[1, 2, 3]", "[0, 1, 2, 3]", &DiffOptions::default()), vec!["+ $[0]: 0"]);
+        assert_eq!(lines("[1, 2, 3, 4]", "[1, 3, 4]", &DiffOptions::default()), vec!["- $[1]: 2"]);
+        assert_eq!(lines(r#"[{"id": 1, "v": "x"}, 5]"#, r#"[{"id": 1, "v": "y"}, 5]"#, &DiffOptions::default()),
+            vec![r#"~ $[0]['v']: "x" -> "y""#]);
+        assert_eq!(lines("[1, 2]", "[2, 1]", &DiffOptions::default()), vec!["+ $[0]: 2", "- $[1]: 2"]);
+        assert_eq!(lines("[0, 1, 2, 3, 4]", "[0, 1, 9, 2, 4]", &DiffOptions::default()), vec!["+ $[2]: 9", "- $[3]: 3"]);
+
+        // Too long to align: compared element by element after the common ends
+        let old: Vec<JsonValue> = (0..3000).map(JsonValue::from).collect();
+        let mut new: Vec<JsonValue> = old.iter().rev().cloned().collect();
+        new.push(JsonValue::from(-1));
+        let entries = diff(&JsonValue::Array(old), &JsonValue::Array(new), &DiffOptions::default());
+        assert_eq!(entries.len(), 3001);
+        assert_eq!(entries[0].to_string(), "~ $[0]: 0 -> 2999");
+        assert_eq!(entries[3000].to_string(), "+ $[3000]: -1");
+
+        let options = DiffOptions { ignore_array_order: true, ..DiffOptions::default() };
+        assert!(lines("[1, 2, [3, 4]]", "[[4, 3], 2, 1]", &options).is_empty());
+        assert_eq!(lines("[1, 2, 2]", "[2, 3, 1]", &options), vec!["- $[2]: 2", "+ $[1]: 3"]);
+    }
+
+    #[test]
+    fn test_float_tolerance() {
+        let options = DiffOptions { float_tolerance: 0.01, ..DiffOptions::default() };
+        assert!(lines(r#"{"pi": 3.14159}"#, r#"{"pi": 3.1416}"#, &options).is_empty());
+        assert_eq!(lines(r#"{"pi": 3.14159}"#, r#"{"pi": 3.2}"#, &options), vec!["~ $['pi']: 3.14159 -> 3.2"]);
+        assert_eq!(lines("[1.0001, 2]", "[1, 2]", &DiffOptions::default()), vec!["~ $[0]: 1.0001 -> 1"]);
+        assert_eq!(lines("1", r#""1""#, &options), vec![r#"~ $: 1 -> "1""#]);
+    }
+}
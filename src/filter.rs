@@ -0,0 +1,724 @@
+mod parser;
+
+use std::cmp::Ordering;
+use std::str::FromStr;
+use anyhow::{Result, anyhow};
+use crate::json_value::{object_from_entries, JsonValue};
+use crate::pretty_printer::{format_number, to_compact_string};
+
+/// A compiled filter expression in a subset of the jq language, e.g.
+/// `.items | map(select(.price < 10)) | length`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    expr: Expr,
+}
+
+impl Filter {
+    pub fn parse(expr: &str) -> Result<Self> {
+        Ok(Filter { expr: parser::parse_filter(expr)? })
+    }
+
+    /// Applies the filter to an input value and returns all outputs it produces.
+    pub fn apply(&self, input: &JsonValue) -> Result<Vec<JsonValue>> {
+        eval(&self.expr, input)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = anyhow::Error;
+
+    fn from_str(expr: &str) -> Result<Self> {
+        Filter::parse(expr)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Identity,
+    RecurseAll,
+    Literal(JsonValue),
+    Field(Box<Expr>, String),
+    Index(Box<Expr>, Box<Expr>),
+    Slice(Box<Expr>, Option<Box<Expr>>, Option<Box<Expr>>),
+    Iterate(Box<Expr>),
+    Try(Box<Expr>),
+    Array(Option<Box<Expr>>),
+    Object(Vec<(ObjectKey, Expr)>),
+    Pipe(Box<Expr>, Box<Expr>),
+    Comma(Box<Expr>, Box<Expr>),
+    Negate(Box<Expr>),
+    Binary(Box<Expr>, BinaryOp, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Alternative(Box<Expr>, Box<Expr>),
+    Call(Builtin, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ObjectKey {
+    Name(String),
+    Expr(Expr),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Builtin {
+    Length,
+    Keys,
+    KeysUnsorted,
+    ToEntries,
+    FromEntries,
+    WithEntries,
+    Map,
+    Select,
+    Not,
+    Add,
+    Has,
+    Type,
+    Empty,
+    Sort,
+    SortBy,
+    Reverse,
+    ToString,
+    ToNumber,
+}
+
+impl Builtin {
+    fn lookup(name: &str, arity: usize) -> Option<Self> {
+        let builtin = match (name, arity) {
+            ("length", 0) => Builtin::Length,
+            ("keys", 0) => Builtin::Keys,
+            ("keys_unsorted", 0) => Builtin::KeysUnsorted,
+            ("to_entries", 0) => Builtin::ToEntries,
+            ("from_entries", 0) => Builtin::FromEntries,
+            ("with_entries", 1) => Builtin::WithEntries,
+            ("map", 1) => Builtin::Map,
+            ("select", 1) => Builtin::Select,
+            ("not", 0) => Builtin::Not,
+            ("add", 0) => Builtin::Add,
+            ("has", 1) => Builtin::Has,
+            ("type", 0) => Builtin::Type,
+            ("empty", 0) => Builtin::Empty,
+            ("sort", 0) => Builtin::Sort,
+            ("sort_by", 1) => Builtin::SortBy,
+            ("reverse", 0) => Builtin::Reverse,
+            ("tostring", 0) => Builtin::ToString,
+            ("tonumber", 0) => Builtin::ToNumber,
+            _ => return None,
+        };
+        Some(builtin)
+    }
+}
+
+fn eval(expr: &Expr, input: &JsonValue) -> Result<Vec<JsonValue>> {
+    match expr {
+        Expr::Identity => Ok(vec![input.clone()]),
+        Expr::RecurseAll => {
+            let mut outputs = Vec::new();
+            collect_recursive(input, &mut outputs);
+            Ok(outputs)
+        }
+        Expr::Literal(value) => Ok(vec![value.clone()]),
+        Expr::Field(target, name) => eval(target, input)?
+            .iter()
+            .map(|value| index(value, &JsonValue::String(name.clone())))
+            .collect(),
+        Expr::Index(target, index_expr) => {
+            let mut outputs = Vec::new();
+            for value in eval(target, input)? {
+                for key in eval(index_expr, input)? {
+                    outputs.push(index(&value, &key)?);
+                }
+            }
+            Ok(outputs)
+        }
+        Expr::Slice(target, from, to) => {
+            let froms = eval_optional(from.as_deref(), input)?;
+            let tos = eval_optional(to.as_deref(), input)?;
+            let mut outputs = Vec::new();
+            for value in eval(target, input)? {
+                for from in &froms {
+                    for to in &tos {
+                        outputs.push(slice(&value, from, to)?);
+                    }
+                }
+            }
+            Ok(outputs)
+        }
+        Expr::Iterate(target) => {
+            let mut outputs = Vec::new();
+            for value in eval(target, input)? {
+                outputs.extend(iterate(&value)?);
+            }
+            Ok(outputs)
+        }
+        Expr::Try(target) => Ok(eval(target, input).unwrap_or_default()),
+        Expr::Array(None) => Ok(vec![JsonValue::Array(Vec::new())]),
+        Expr::Array(Some(elements)) => Ok(vec![JsonValue::Array(eval(elements, input)?)]),
+        Expr::Object(entries) => eval_object(entries, input),
+        Expr::Pipe(left, right) => {
+            let mut outputs = Vec::new();
+            for value in eval(left, input)? {
+                outputs.extend(eval(right, &value)?);
+            }
+            Ok(outputs)
+        }
+        Expr::Comma(left, right) => {
+            let mut outputs = eval(left, input)?;
+            outputs.extend(eval(right, input)?);
+            Ok(outputs)
+        }
+        Expr::Negate(operand) => eval(operand, input)?
+            .into_iter()
+            .map(|value| match value {
+                JsonValue::Number(n) => Ok(JsonValue::Number(-n)),
+                _ => Err(anyhow!("{} cannot be negated", type_name(&value))),
+            })
+            .collect(),
+        Expr::Binary(left, op, right) => {
+            let mut outputs = Vec::new();
+            for r in eval(right, input)? {
+                for l in eval(left, input)? {
+                    outputs.push(binary(*op, &l, &r)?);
+                }
+            }
+            Ok(outputs)
+        }
+        Expr::And(left, right) => {
+            let mut outputs = Vec::new();
+            for l in eval(left, input)? {
+                if !is_truthy(&l) {
+                    outputs.push(JsonValue::Bool(false));
+                    continue;
+                }
+                for r in eval(right, input)? {
+                    outputs.push(JsonValue::Bool(is_truthy(&r)));
+                }
+            }
+            Ok(outputs)
+        }
+        Expr::Or(left, right) => {
+            let mut outputs = Vec::new();
+            for l in eval(left, input)? {
+                if is_truthy(&l) {
+                    outputs.push(JsonValue::Bool(true));
+                    continue;
+                }
+                for r in eval(right, input)? {
+                    outputs.push(JsonValue::Bool(is_truthy(&r)));
+                }
+            }
+            Ok(outputs)
+        }
+        Expr::Alternative(left, right) => {
+            let outputs: Vec<JsonValue> = eval(left, input)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(is_truthy)
+                .collect();
+            if outputs.is_empty() { eval(right, input) } else { Ok(outputs) }
+        }
+        Expr::Call(builtin, arguments) => call(*builtin, arguments, input),
+    }
+}
+
+fn eval_optional(expr: Option<&Expr>, input: &JsonValue) -> Result<Vec<JsonValue>> {
+    match expr {
+        Some(expr) => eval(expr, input),
+        None => Ok(vec![JsonValue::Null]),
+    }
+}
+
+/// Builds objects from all combinations of the outputs of the keys and values.
+fn eval_object(entries: &[(ObjectKey, Expr)], input: &JsonValue) -> Result<Vec<JsonValue>> {
+    let mut partials: Vec<Vec<(String, JsonValue)>> = vec![Vec::new()];
+    for (key, value_expr) in entries {
+        let keys = match key {
+            ObjectKey::Name(name) => vec![name.clone()],
+            ObjectKey::Expr(expr) => eval(expr, input)?
+                .into_iter()
+                .map(|key| match key {
+                    JsonValue::String(key) => Ok(key),
+                    _ => Err(anyhow!("Object keys must be strings, found {}", type_name(&key))),
+                })
+                .collect::<Result<_>>()?,
+        };
+        let values = eval(value_expr, input)?;
+
+        let mut extended = Vec::new();
+        for partial in &partials {
+            for key in &keys {
+                for value in &values {
+                    let mut entries = partial.clone();
+                    entries.push((key.clone(), value.clone()));
+                    extended.push(entries);
+                }
+            }
+        }
+        partials = extended;
+    }
+    Ok(partials.into_iter().map(object_from_entries).collect())
+}
+
+fn call(builtin: Builtin, arguments: &[Expr], input: &JsonValue) -> Result<Vec<JsonValue>> {
+    let single = |value: JsonValue| Ok(vec![value]);
+    match builtin {
+        Builtin::Length => single(match input {
+            JsonValue::Null => JsonValue::Number(0.0),
+            JsonValue::Bool(_) => return Err(anyhow!("boolean has no length")),
+            JsonValue::Number(n) => JsonValue::Number(n.abs()),
+            JsonValue::String(s) => JsonValue::Number(s.chars().count() as f64),
+            JsonValue::Array(elements) => JsonValue::Number(elements.len() as f64),
+            JsonValue::Object(names, _) => JsonValue::Number(names.len() as f64),
+        }),
+        Builtin::Keys | Builtin::KeysUnsorted => single(match input {
+            JsonValue::Object(names, _) => {
+                let mut names = names.clone();
+                if builtin == Builtin::Keys {
+                    names.sort();
+                }
+                JsonValue::Array(names.into_iter().map(JsonValue::String).collect())
+            }
+            JsonValue::Array(elements) => {
+                JsonValue::Array((0..elements.len()).map(|i| JsonValue::Number(i as f64)).collect())
+            }
+            _ => return Err(anyhow!("{} has no keys", type_name(input))),
+        }),
+        Builtin::ToEntries => single(to_entries(input)?),
+        Builtin::FromEntries => single(from_entries(input)?),
+        Builtin::WithEntries => {
+            let JsonValue::Array(entries) = to_entries(input)? else {
+                unreachable!("to_entries returns an array");
+            };
+            let mut mapped = Vec::new();
+            for entry in &entries {
+                mapped.extend(eval(&arguments[0], entry)?);
+            }
+            single(from_entries(&JsonValue::Array(mapped))?)
+        }
+        Builtin::Map => {
+            let mut outputs = Vec::new();
+            for value in iterate(input)? {
+                outputs.extend(eval(&arguments[0], &value)?);
+            }
+            single(JsonValue::Array(outputs))
+        }
+        Builtin::Select => {
+            let selected = eval(&arguments[0], input)?.iter().filter(|value| is_truthy(value)).count();
+            Ok(vec![input.clone(); selected])
+        }
+        Builtin::Not => single(JsonValue::Bool(!is_truthy(input))),
+        Builtin::Add => {
+            let mut sum = JsonValue::Null;
+            for value in iterate(input)? {
+                sum = binary(BinaryOp::Add, &sum, &value)?;
+            }
+            single(sum)
+        }
+        Builtin::Has => eval(&arguments[0], input)?
+            .iter()
+            .map(|key| match (input, key) {
+                (JsonValue::Object(_, members), JsonValue::String(key)) => {
+                    Ok(JsonValue::Bool(members.contains_key(key)))
+                }
+                (JsonValue::Array(elements), JsonValue::Number(index)) => {
+                    Ok(JsonValue::Bool(*index >= 0.0 && (*index as usize) < elements.len()))
+                }
+                _ => Err(anyhow!("Cannot check whether {} has a {} key", type_name(input), type_name(key))),
+            })
+            .collect(),
+        Builtin::Type => single(JsonValue::String(type_name(input).to_string())),
+        Builtin::Empty => Ok(Vec::new()),
+        Builtin::Sort => match input {
+            JsonValue::Array(elements) => {
+                let mut sorted = elements.clone();
+                sorted.sort_by(compare);
+                single(JsonValue::Array(sorted))
+            }
+            _ => Err(anyhow!("{} cannot be sorted, as it is not an array", type_name(input))),
+        },
+        Builtin::SortBy => match input {
+            JsonValue::Array(elements) => {
+                let mut keyed = elements
+                    .iter()
+                    .map(|element| Ok((eval(&arguments[0], element)?, element.clone())))
+                    .collect::<Result<Vec<_>>>()?;
+                keyed.sort_by(|(a, _), (b, _)| compare(&JsonValue::Array(a.clone()), &JsonValue::Array(b.clone())));
+                single(JsonValue::Array(keyed.into_iter().map(|(_, element)| element).collect()))
+            }
+            _ => Err(anyhow!("{} cannot be sorted, as it is not an array", type_name(input))),
+        },
+        Builtin::Reverse => match input {
+            JsonValue::Array(elements) => single(JsonValue::Array(elements.iter().rev().cloned().collect())),
+            JsonValue::String(s) => single(JsonValue::String(s.chars().rev().collect())),
+            JsonValue::Null => single(JsonValue::Array(Vec::new())),
+            _ => Err(anyhow!("{} cannot be reversed", type_name(input))),
+        },
+        Builtin::ToString => single(match input {
+            JsonValue::String(_) => input.clone(),
+            _ => JsonValue::String(to_compact_string(input)),
+        }),
+        Builtin::ToNumber => single(match input {
+            JsonValue::Number(_) => input.clone(),
+            JsonValue::String(s) => s
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite())
+                .map(JsonValue::Number)
+                .ok_or_else(|| anyhow!("Cannot parse '{}' as a number", s))?,
+            _ => return Err(anyhow!("{} cannot be parsed as a number", type_name(input))),
+        }),
+    }
+}
+
+fn collect_recursive(value: &JsonValue, outputs: &mut Vec<JsonValue>) {
+    outputs.push(value.clone());
+    match value {
+        JsonValue::Array(elements) => elements.iter().for_each(|element| collect_recursive(element, outputs)),
+        JsonValue::Object(names, members) => {
+            for name in names {
+                if let Some(member) = members.get(name) {
+                    collect_recursive(member, outputs);
+                }
+            }
+        }
+        _ => (),
+    }
+}
+
+fn index(value: &JsonValue, key: &JsonValue) -> Result<JsonValue> {
+    match (value, key) {
+        (JsonValue::Null, JsonValue::String(_) | JsonValue::Number(_)) => Ok(JsonValue::Null),
+        (JsonValue::Object(_, members), JsonValue::String(name)) => {
+            Ok(members.get(name).cloned().unwrap_or(JsonValue::Null))
+        }
+        (JsonValue::Array(elements), JsonValue::Number(n)) => {
+            let len = elements.len() as i64;
+            let index = n.floor() as i64;
+            let index = if index < 0 { len + index } else { index };
+            Ok(usize::try_from(index)
+                .ok()
+                .and_then(|index| elements.get(index))
+                .cloned()
+                .unwrap_or(JsonValue::Null))
+        }
+        (_, JsonValue::String(name)) => {
+            Err(anyhow!("Cannot index {} with \"{}\"", type_name(value), name))
+        }
+        _ => Err(anyhow!("Cannot index {} with {}", type_name(value), type_name(key))),
+    }
+}
+
+fn slice(value: &JsonValue, from: &JsonValue, to: &JsonValue) -> Result<JsonValue> {
+    let len = match value {
+        JsonValue::Null => return Ok(JsonValue::Null),
+        JsonValue::Array(elements) => elements.len(),
+        JsonValue::String(s) => s.chars().count(),
+        _ => return Err(anyhow!("Cannot slice {}", type_name(value))),
+    };
+    let bound = |bound: &JsonValue, default: usize| -> Result<usize> {
+        match bound {
+            JsonValue::Null => Ok(default),
+            JsonValue::Number(n) => {
+                let n = n.floor() as i64;
+                let n = if n < 0 { len as i64 + n } else { n };
+                Ok(n.clamp(0, len as i64) as usize)
+            }
+            _ => Err(anyhow!("Slice bounds must be numbers, found {}", type_name(bound))),
+        }
+    };
+    let from = bound(from, 0)?;
+    let to = bound(to, len)?.max(from);
+    Ok(match value {
+        JsonValue::Array(elements) => JsonValue::Array(elements[from..to].to_vec()),
+        JsonValue::String(s) => JsonValue::String(s.chars().skip(from).take(to - from).collect()),
+        _ => unreachable!("only arrays and strings can be sliced"),
+    })
+}
+
+fn iterate(value: &JsonValue) -> Result<Vec<JsonValue>> {
+    match value {
+        JsonValue::Array(elements) => Ok(elements.clone()),
+        JsonValue::Object(names, members) => {
+            Ok(names.iter().filter_map(|name| members.get(name).cloned()).collect())
+        }
+        _ => Err(anyhow!("Cannot iterate over {}", type_name(value))),
+    }
+}
+
+fn binary(op: BinaryOp, left: &JsonValue, right: &JsonValue) -> Result<JsonValue> {
+    use JsonValue::{Array, Null, Number, Object, String};
+    let result = match (op, left, right) {
+        (BinaryOp::Eq, _, _) => JsonValue::Bool(compare(left, right) == Ordering::Equal),
+        (BinaryOp::Ne, _, _) => JsonValue::Bool(compare(left, right) != Ordering::Equal),
+        (BinaryOp::Lt, _, _) => JsonValue::Bool(compare(left, right) == Ordering::Less),
+        (BinaryOp::Le, _, _) => JsonValue::Bool(compare(left, right) != Ordering::Greater),
+        (BinaryOp::Gt, _, _) => JsonValue::Bool(compare(left, right) == Ordering::Greater),
+        (BinaryOp::Ge, _, _) => JsonValue::Bool(compare(left, right) != Ordering::Less),
+        (BinaryOp::Add, Null, value) | (BinaryOp::Add, value, Null) => value.clone(),
+        (BinaryOp::Add, Number(l), Number(r)) => Number(l + r),
+        (BinaryOp::Add, String(l), String(r)) => String(format!("{}{}", l, r)),
+        (BinaryOp::Add, Array(l), Array(r)) => Array(l.iter().chain(r).cloned().collect()),
+        (BinaryOp::Add, Object(l_names, l_members), Object(r_names, r_members)) => {
+            let mut entries: Vec<(std::string::String, JsonValue)> = l_names
+                .iter()
+                .filter_map(|name| Some((name.clone(), l_members.get(name)?.clone())))
+                .collect();
+            entries.extend(r_names.iter().filter_map(|name| Some((name.clone(), r_members.get(name)?.clone()))));
+            object_from_entries(entries)
+        }
+        (BinaryOp::Subtract, Number(l), Number(r)) => Number(l - r),
+        (BinaryOp::Subtract, Array(l), Array(r)) => {
+            Array(l.iter().filter(|element| !r.contains(element)).cloned().collect())
+        }
+        (BinaryOp::Multiply, Number(l), Number(r)) => Number(l * r),
+        (BinaryOp::Divide, Number(l), Number(r)) if *r == 0.0 => {
+            return Err(anyhow!("{} and {} cannot be divided because the divisor is zero", format_number(*l), format_number(*r)));
+        }
+        (BinaryOp::Divide, Number(l), Number(r)) => Number(l / r),
+        (BinaryOp::Divide, String(l), String(r)) => {
+            Array(l.split(r.as_str()).map(|part| String(part.to_string())).collect())
+        }
+        (BinaryOp::Modulo, Number(l), Number(r)) => {
+            let divisor = r.trunc() as i64;
+            if divisor == 0 {
+                return Err(anyhow!("{} and {} cannot be divided because the divisor is zero", format_number(*l), format_number(*r)));
+            }
+            Number((l.trunc() as i64).wrapping_rem(divisor) as f64)
+        }
+        _ => {
+            let verb = match op {
+                BinaryOp::Add => "added",
+                BinaryOp::Subtract => "subtracted",
+                BinaryOp::Multiply => "multiplied",
+                _ => "divided",
+            };
+            return Err(anyhow!("{} and {} cannot be {}", type_name(left), type_name(right), verb));
+        }
+    };
+    Ok(result)
+}
+
+/// Orders values like jq: null < false < true < numbers < strings < arrays < objects
+fn compare(left: &JsonValue, right: &JsonValue) -> Ordering {
+    fn rank(value: &JsonValue) -> u8 {
+        match value {
+            JsonValue::Null => 0,
+            JsonValue::Bool(false) => 1,
+            JsonValue::Bool(true) => 2,
+            JsonValue::Number(_) => 3,
+            JsonValue::String(_) => 4,
+            JsonValue::Array(_) => 5,
+            JsonValue::Object(_, _) => 6,
+        }
+    }
+
+    match (left, right) {
+        (JsonValue::Number(l), JsonValue::Number(r)) => l.partial_cmp(r).unwrap_or(Ordering::Equal),
+        (JsonValue::String(l), JsonValue::String(r)) => l.cmp(r),
+        (JsonValue::Array(l), JsonValue::Array(r)) => l
+            .iter()
+            .zip(r)
+            .map(|(l, r)| compare(l, r))
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or_else(|| l.len().cmp(&r.len())),
+        (JsonValue::Object(l_names, l_members), JsonValue::Object(r_names, r_members)) => {
+            let mut l_keys: Vec<&String> = l_names.iter().collect();
+            let mut r_keys: Vec<&String> = r_names.iter().collect();
+            l_keys.sort();
+            r_keys.sort();
+            l_keys.cmp(&r_keys).then_with(|| {
+                l_keys
+                    .iter()
+                    .map(|key| compare(&l_members[*key], &r_members[*key]))
+                    .find(|ordering| *ordering != Ordering::Equal)
+                    .unwrap_or(Ordering::Equal)
+            })
+        }
+        _ => rank(left).cmp(&rank(right)),
+    }
+}
+
+fn to_entries(value: &JsonValue) -> Result<JsonValue> {
+    let JsonValue::Object(names, members) = value else {
+        return Err(anyhow!("{} has no entries, as it is not an object", type_name(value)));
+    };
+    let entries = names
+        .iter()
+        .filter_map(|name| {
+            let member = members.get(name)?;
+            Some(object_from_entries(vec![
+                ("key".to_string(), JsonValue::String(name.clone())),
+                ("value".to_string(), member.clone()),
+            ]))
+        })
+        .collect();
+    Ok(JsonValue::Array(entries))
+}
+
+fn from_entries(value: &JsonValue) -> Result<JsonValue> {
+    let entries = iterate(value)?;
+    let mut members = Vec::new();
+    for entry in &entries {
+        let key = entry_field(entry, &["key", "k", "name"])?;
+        let value = entry_field(entry, &["value", "v"])?;
+        let key = match key {
+            JsonValue::String(key) => key,
+            JsonValue::Number(n) => format_number(n),
+            JsonValue::Bool(b) => b.to_string(),
+            _ => return Err(anyhow!("Entry keys must be strings, found {}", type_name(&key))),
+        };
+        members.push((key, value));
+    }
+    Ok(object_from_entries(members))
+}
+
+/// Returns the first non-null member of an entry among the accepted names.
+fn entry_field(entry: &JsonValue, names: &[&str]) -> Result<JsonValue> {
+    for name in names {
+        let value = index(entry, &JsonValue::String(name.to_string()))?;
+        if value != JsonValue::Null {
+            return Ok(value);
+        }
+    }
+    Ok(JsonValue::Null)
+}
+
+fn is_truthy(value: &JsonValue) -> bool {
+    !matches!(value, JsonValue::Null | JsonValue::Bool(false))
+}
+
+fn type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Bool(_) => "boolean",
+        JsonValue::Number(_) => "number",
+        JsonValue::String(_) => "string",
+        JsonValue::Array(_) => "array",
+        JsonValue::Object(_, _) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    const INPUT: &str = r#"{
+        "name": "medea",
+        "tags": ["json", "cli"],
+        "items": [
+            {"id": 1, "price": 12.5, "stock": 0},
+            {"id": 2, "price": 4, "stock": 10},
+            {"id": 3, "price": 7.25, "stock": 3}
+        ]
+    }"#;
+
+    fn run(expr: &str) -> Vec<JsonValue> {
+        let input = parse_json(INPUT).unwrap();
+        Filter::parse(expr).unwrap().apply(&input).unwrap()
+    }
+
+    fn json(text: &str) -> JsonValue {
+        parse_json(text).unwrap()
+    }
+
+    #[test]
+    fn test_paths() {
+        assert_eq!(run("."), vec![json(INPUT)]);
+        assert_eq!(run(".name"), vec![json(r#""medea""#)]);
+        assert_eq!(run(".items[1].price"), vec![json("4")]);
+        assert_eq!(run(".items[-1].id"), vec![json("3")]);
+        assert_eq!(run(".[\"name\"]"), vec![json(r#""medea""#)]);
+        assert_eq!(run(".missing.deeper"), vec![json("null")]);
+        assert_eq!(run(".tags[]"), vec![json(r#""json""#), json(r#""cli""#)]);
+        assert_eq!(run(".items[1:]|length"), vec![json("2")]);
+        assert_eq!(run(".name[1:3]"), vec![json(r#""ed""#)]);
+        assert_eq!(run(".name.foo?"), Vec::<JsonValue>::new());
+        assert_eq!(run("[.. | select(type == \"number\")] | length"), vec![json("9")]);
+    }
+
+    #[test]
+    fn test_pipes_and_construction() {
+        assert_eq!(run(".items | map(.id)"), vec![json("[1, 2, 3]")]);
+        assert_eq!(run("[.items[] | select(.stock > 0) | .id]"), vec![json("[2, 3]")]);
+        assert_eq!(run("{name, count: (.items | length)}"), vec![json(r#"{"name": "medea", "count": 3}"#)]);
+        assert_eq!(run("{(.name): .tags[0]}"), vec![json(r#"{"medea": "json"}"#)]);
+        assert_eq!(run("{tag: .tags[]}"), vec![json(r#"{"tag": "json"}"#), json(r#"{"tag": "cli"}"#)]);
+        assert_eq!(run(".tags[0], .name"), vec![json(r#""json""#), json(r#""medea""#)]);
+        assert_eq!(run("[.items[].price] | add"), vec![json("23.75")]);
+    }
+
+    #[test]
+    fn test_arithmetic_and_comparison() {
+        assert_eq!(run("1 + 2 * 3 - 4 / 2"), vec![json("5")]);
+        assert_eq!(run("(1 + 2) * 3 % 5"), vec![json("4")]);
+        assert_eq!(run("-9223372036854775808 % -1"), vec![json("0")]);
+        assert_eq!(run("-.items[0].price"), vec![json("-12.5")]);
+        assert_eq!(run(".name + \"-json\""), vec![json(r#""medea-json""#)]);
+        assert_eq!(run(".tags + [\"x\"] - [\"cli\"]"), vec![json(r#"["json", "x"]"#)]);
+        assert_eq!(run("{a: 1} + {b: 2}"), vec![json(r#"{"a": 1, "b": 2}"#)]);
+        assert_eq!(run("1 < 2, \"a\" >= \"b\", null < false, [1] == [1]"), vec![
+            json("true"), json("false"), json("true"), json("true"),
+        ]);
+        assert_eq!(run(".missing // \"default\""), vec![json(r#""default""#)]);
+        assert_eq!(run("true and (false or true), (null | not)"), vec![json("true"), json("true")]);
+    }
+
+    #[test]
+    fn test_builtins() {
+        assert_eq!(run("keys"), vec![json(r#"["items", "name", "tags"]"#)]);
+        assert_eq!(run("keys_unsorted"), vec![json(r#"["name", "tags", "items"]"#)]);
+        assert_eq!(run("{a: 1} | to_entries"), vec![json(r#"[{"key": "a", "value": 1}]"#)]);
+        assert_eq!(run("{a: 1, b: 2} | with_entries(select(.value > 1))"), vec![json(r#"{"b": 2}"#)]);
+        assert_eq!(run("[{k: \"x\", v: true}] | from_entries"), vec![json(r#"{"x": true}"#)]);
+        assert_eq!(run(".items | sort_by(-.price) | map(.id)"), vec![json("[1, 3, 2]")]);
+        assert_eq!(run("[3, null, \"a\", 1] | sort"), vec![json(r#"[null, 1, 3, "a"]"#)]);
+        assert_eq!(run("has(\"name\"), (.tags | has(2))"), vec![json("true"), json("false")]);
+        assert_eq!(run("[.name, .tags, 1, null] | map(type)"), vec![json(r#"["string", "array", "number", "null"]"#)]);
+        assert_eq!(run(".tags | tostring"), vec![json(r#""[\"json\",\"cli\"]""#)]);
+        assert_eq!(run("\"42\" | tonumber"), vec![json("42")]);
+        assert_eq!(run("empty"), Vec::<JsonValue>::new());
+        assert_eq!(run(".tags | reverse"), vec![json(r#"["cli", "json"]"#)]);
+    }
+
+    #[test]
+    fn test_runtime_errors() {
+        let input = parse_json(INPUT).unwrap();
+        let error = |expr: &str| Filter::parse(expr).unwrap().apply(&input).unwrap_err().to_string();
+        assert_eq!(error(".name.first"), "Cannot index string with \"first\"");
+        assert_eq!(error(".name[]"), "Cannot iterate over string");
+        assert_eq!(error(".name + 1"), "string and number cannot be added");
+        assert_eq!(error("{(.tags): 1}"), "Object keys must be strings, found array");
+        assert_eq!(error("5 / 0"), "5 and 0 cannot be divided because the divisor is zero");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |expr: &str| Filter::parse(expr).unwrap_err().to_string();
+        assert_eq!(error(".items[1"), "Invalid filter at position 8: Expected ']'\n    .items[1\n            ^");
+        assert_eq!(error("map(.a"), "Invalid filter at position 6: Expected ')'\n    map(.a\n          ^");
+        assert_eq!(error("foo(1)"), "Invalid filter at position 0: Unknown function foo/1\n    foo(1)\n    ^");
+        assert_eq!(error(".a | | .b"), "Invalid filter at position 5: Expected an expression\n    .a | | .b\n         ^");
+        assert_eq!(error("{a: 1"), "Invalid filter at position 5: Expected ',' or '}'\n    {a: 1\n         ^");
+        assert_eq!(error("\"abc"), "Invalid filter at position 4: Unterminated string\n    \"abc\n        ^");
+        assert_eq!(error(". 1"), "Invalid filter at position 2: Unexpected input\n    . 1\n      ^");
+        assert_eq!(error("1 < 2 < 3"), "Invalid filter at position 6: Comparisons cannot be chained, use parentheses\n    1 < 2 < 3\n          ^");
+        assert_eq!(error("map(1; 2)"), "Invalid filter at position 0: Unknown function map/2\n    map(1; 2)\n    ^");
+    }
+}
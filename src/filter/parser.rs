@@ -0,0 +1,514 @@
+use anyhow::{Result, anyhow};
+use crate::json_value::JsonValue;
+use super::{BinaryOp, Builtin, Expr, ObjectKey};
+
+pub(super) fn parse_filter(input: &str) -> Result<Expr> {
+    let mut parser = Parser::new(input);
+    parser.skip_blank();
+    let expr = parser.parse_pipe()?;
+    parser.skip_blank();
+    if parser.peek().is_some() {
+        return Err(parser.error("Unexpected input"));
+    }
+    Ok(expr)
+}
+
+struct Parser {
+    input: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn new(input: &str) -> Self {
+        Parser {
+            input: input.chars().collect(),
+            position: 0,
+        }
+    }
+
+    fn parse_pipe(&mut self) -> Result<Expr> {
+        let left = self.parse_comma()?;
+        self.skip_blank();
+        if !self.consume('|') {
+            return Ok(left);
+        }
+        self.skip_blank();
+        let right = self.parse_pipe()?;
+        Ok(Expr::Pipe(Box::new(left), Box::new(right)))
+    }
+
+    fn parse_comma(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_alternative()?;
+        loop {
+            self.skip_blank();
+            if !self.consume(',') {
+                return Ok(expr);
+            }
+            self.skip_blank();
+            let right = self.parse_alternative()?;
+            expr = Expr::Comma(Box::new(expr), Box::new(right));
+        }
+    }
+
+    fn parse_alternative(&mut self) -> Result<Expr> {
+        let left = self.parse_or()?;
+        self.skip_blank();
+        if !self.consume_str("//") {
+            return Ok(left);
+        }
+        self.skip_blank();
+        let right = self.parse_alternative()?;
+        Ok(Expr::Alternative(Box::new(left), Box::new(right)))
+    }
+
+    fn parse_or(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_and()?;
+        loop {
+            self.skip_blank();
+            if !self.consume_keyword("or") {
+                return Ok(expr);
+            }
+            self.skip_blank();
+            let right = self.parse_and()?;
+            expr = Expr::Or(Box::new(expr), Box::new(right));
+        }
+    }
+
+    fn parse_and(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_comparison()?;
+        loop {
+            self.skip_blank();
+            if !self.consume_keyword("and") {
+                return Ok(expr);
+            }
+            self.skip_blank();
+            let right = self.parse_comparison()?;
+            expr = Expr::And(Box::new(expr), Box::new(right));
+        }
+    }
+
+    /// Comparisons do not associate, so `1 < 2 < 3` is rejected.
+    fn parse_comparison(&mut self) -> Result<Expr> {
+        let left = self.parse_additive()?;
+        self.skip_blank();
+        let Some(op) = self.parse_comparison_op() else {
+            return Ok(left);
+        };
+        self.skip_blank();
+        let right = self.parse_additive()?;
+        self.skip_blank();
+        let position = self.position;
+        if self.parse_comparison_op().is_some() {
+            self.position = position;
+            return Err(self.error("Comparisons cannot be chained, use parentheses"));
+        }
+        Ok(Expr::Binary(Box::new(left), op, Box::new(right)))
+    }
+
+    fn parse_comparison_op(&mut self) -> Option<BinaryOp> {
+        let op = if self.consume_str("==") {
+            BinaryOp::Eq
+        } else if self.consume_str("!=") {
+            BinaryOp::Ne
+        } else if self.consume_str("<=") {
+            BinaryOp::Le
+        } else if self.consume_str(">=") {
+            BinaryOp::Ge
+        } else if self.consume('<') {
+            BinaryOp::Lt
+        } else if self.consume('>') {
+            BinaryOp::Gt
+        } else {
+            return None;
+        };
+        Some(op)
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_multiplicative()?;
+        loop {
+            self.skip_blank();
+            let op = match self.peek() {
+                Some('+') => BinaryOp::Add,
+                Some('-') => BinaryOp::Subtract,
+                _ => return Ok(expr),
+            };
+            self.advance();
+            self.skip_blank();
+            let right = self.parse_multiplicative()?;
+            expr = Expr::Binary(Box::new(expr), op, Box::new(right));
+        }
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_unary()?;
+        loop {
+            self.skip_blank();
+            let op = match self.peek() {
+                Some('*') => BinaryOp::Multiply,
+                Some('/') if self.peek_at(1) != Some('/') => BinaryOp::Divide,
+                Some('%') => BinaryOp::Modulo,
+                _ => return Ok(expr),
+            };
+            self.advance();
+            self.skip_blank();
+            let right = self.parse_unary()?;
+            expr = Expr::Binary(Box::new(expr), op, Box::new(right));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        if self.consume('-') {
+            self.skip_blank();
+            return Ok(Expr::Negate(Box::new(self.parse_unary()?)));
+        }
+        self.parse_postfix()
+    }
+
+    fn parse_postfix(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_primary()?;
+        loop {
+            expr = match self.peek() {
+                Some('.') if matches!(self.peek_at(1), Some('"') | Some('[')) || self.peek_at(1).is_some_and(is_ident_first) => {
+                    self.advance();
+                    self.parse_field_access(expr)?
+                }
+                Some('[') => self.parse_bracket_suffix(expr)?,
+                Some('?') => {
+                    self.advance();
+                    Expr::Try(Box::new(expr))
+                }
+                _ => return Ok(expr),
+            };
+        }
+    }
+
+    /// Parses what follows a '.', i.e. a name, a quoted name or a bracket suffix.
+    fn parse_field_access(&mut self, target: Expr) -> Result<Expr> {
+        match self.peek() {
+            Some('"') => Ok(Expr::Field(Box::new(target), self.parse_string()?)),
+            Some('[') => self.parse_bracket_suffix(target),
+            _ => Ok(Expr::Field(Box::new(target), self.parse_ident())),
+        }
+    }
+
+    fn parse_bracket_suffix(&mut self, target: Expr) -> Result<Expr> {
+        self.expect('[')?;
+        self.skip_blank();
+        if self.consume(']') {
+            return Ok(Expr::Iterate(Box::new(target)));
+        }
+        let from = if self.peek() == Some(':') { None } else { Some(Box::new(self.parse_pipe()?)) };
+        self.skip_blank();
+        if self.consume(':') {
+            self.skip_blank();
+            let to = if self.peek() == Some(']') { None } else { Some(Box::new(self.parse_pipe()?)) };
+            if from.is_none() && to.is_none() {
+                return Err(self.error("Expected a slice bound"));
+            }
+            self.skip_blank();
+            self.expect(']')?;
+            return Ok(Expr::Slice(Box::new(target), from, to));
+        }
+        self.expect(']')?;
+        let index = from.expect("an index is parsed unless the slice has no start");
+        Ok(Expr::Index(Box::new(target), index))
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr> {
+        match self.peek() {
+            Some('.') => {
+                self.advance();
+                if self.consume('.') {
+                    return Ok(Expr::RecurseAll);
+                }
+                match self.peek() {
+                    Some('"') | Some('[') => self.parse_field_access(Expr::Identity),
+                    Some(c) if is_ident_first(c) => self.parse_field_access(Expr::Identity),
+                    _ => Ok(Expr::Identity),
+                }
+            }
+            Some('"') => Ok(Expr::Literal(JsonValue::String(self.parse_string()?))),
+            Some(c) if c.is_ascii_digit() => Ok(Expr::Literal(self.parse_number()?)),
+            Some('(') => {
+                self.advance();
+                self.skip_blank();
+                let expr = self.parse_pipe()?;
+                self.skip_blank();
+                self.expect(')')?;
+                Ok(expr)
+            }
+            Some('[') => {
+                self.advance();
+                self.skip_blank();
+                if self.consume(']') {
+                    return Ok(Expr::Array(None));
+                }
+                let elements = self.parse_pipe()?;
+                self.skip_blank();
+                self.expect(']')?;
+                Ok(Expr::Array(Some(Box::new(elements))))
+            }
+            Some('{') => self.parse_object(),
+            Some(c) if is_ident_first(c) => self.parse_call(),
+            _ => Err(self.error("Expected an expression")),
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Expr> {
+        self.expect('{')?;
+        let mut entries = Vec::new();
+        self.skip_blank();
+        if self.consume('}') {
+            return Ok(Expr::Object(entries));
+        }
+        loop {
+            self.skip_blank();
+            let key = match self.peek() {
+                Some('"') => ObjectKey::Name(self.parse_string()?),
+                Some('(') => {
+                    self.advance();
+                    self.skip_blank();
+                    let key = self.parse_pipe()?;
+                    self.skip_blank();
+                    self.expect(')')?;
+                    ObjectKey::Expr(key)
+                }
+                Some(c) if is_ident_first(c) => ObjectKey::Name(self.parse_ident()),
+                _ => return Err(self.error("Expected an object key")),
+            };
+            self.skip_blank();
+            let value = if self.consume(':') {
+                self.skip_blank();
+                self.parse_alternative()?
+            } else {
+                match &key {
+                    // {name} is short for {name: .name}
+                    ObjectKey::Name(name) => Expr::Field(Box::new(Expr::Identity), name.clone()),
+                    ObjectKey::Expr(_) => return Err(self.error("Expected ':'")),
+                }
+            };
+            entries.push((key, value));
+            self.skip_blank();
+            if self.consume('}') {
+                return Ok(Expr::Object(entries));
+            }
+            if !self.consume(',') {
+                return Err(self.error("Expected ',' or '}'"));
+            }
+        }
+    }
+
+    fn parse_call(&mut self) -> Result<Expr> {
+        let start = self.position;
+        let name = self.parse_ident();
+        match name.as_str() {
+            "true" => return Ok(Expr::Literal(JsonValue::Bool(true))),
+            "false" => return Ok(Expr::Literal(JsonValue::Bool(false))),
+            "null" => return Ok(Expr::Literal(JsonValue::Null)),
+            "and" | "or" => {
+                self.position = start;
+                return Err(self.error("Expected an expression"));
+            }
+            _ => (),
+        }
+
+        let mut arguments = Vec::new();
+        if self.consume('(') {
+            loop {
+                self.skip_blank();
+                arguments.push(self.parse_pipe()?);
+                self.skip_blank();
+                if !self.consume(';') {
+                    break;
+                }
+            }
+            self.expect(')')?;
+        }
+
+        let builtin = Builtin::lookup(&name, arguments.len()).ok_or_else(|| {
+            self.position = start;
+            self.error(&format!("Unknown function {}/{}", name, arguments.len()))
+        })?;
+        Ok(Expr::Call(builtin, arguments))
+    }
+
+    fn parse_ident(&mut self) -> String {
+        let mut ident = String::new();
+        while let Some(c) = self.peek() {
+            if !is_ident_first(c) && !c.is_ascii_digit() {
+                break;
+            }
+            ident.push(c);
+            self.advance();
+        }
+        ident
+    }
+
+    fn parse_string(&mut self) -> Result<String> {
+        self.expect('"')?;
+        let mut literal = String::new();
+        loop {
+            match self.advance() {
+                None => return Err(self.error("Unterminated string")),
+                Some('"') => return Ok(literal),
+                Some('\\') => literal.push(self.parse_escape()?),
+                Some(c) => literal.push(c),
+            }
+        }
+    }
+
+    fn parse_escape(&mut self) -> Result<char> {
+        match self.advance() {
+            Some('"') => Ok('"'),
+            Some('b') => Ok('\u{0008}'),
+            Some('f') => Ok('\u{000C}'),
+            Some('n') => Ok('\n'),
+            Some('r') => Ok('\r'),
+            Some('t') => Ok('\t'),
+            Some('/') => Ok('/'),
+            Some('\\') => Ok('\\'),
+            Some('u') => {
+                let high = self.parse_hex4()?;
+                if !(0xD800..0xDC00).contains(&high) {
+                    return char::from_u32(high).ok_or_else(|| self.error("Invalid unicode escape"));
+                }
+                if !self.consume_str("\\u") {
+                    return Err(self.error("Expected low surrogate"));
+                }
+                let low = self.parse_hex4()?;
+                if !(0xDC00..0xE000).contains(&low) {
+                    return Err(self.error("Expected low surrogate"));
+                }
+                char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+                    .ok_or_else(|| self.error("Invalid unicode escape"))
+            }
+            _ => {
+                self.position -= 1;
+                Err(self.error("Invalid escape sequence"))
+            }
+        }
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self.peek().and_then(|c| c.to_digit(16));
+            code = code * 16 + digit.ok_or_else(|| self.error("Expected four hex digits"))?;
+            self.advance();
+        }
+        Ok(code)
+    }
+
+    fn parse_number(&mut self) -> Result<JsonValue> {
+        let start = self.position;
+        let mut literal = self.take_digits();
+        if self.peek() == Some('.') && self.peek_at(1).is_some_and(|c| c.is_ascii_digit()) {
+            self.advance();
+            literal.push('.');
+            literal.push_str(&self.take_digits());
+        }
+        if let Some(e) = self.peek().filter(|c| *c == 'e' || *c == 'E') {
+            self.advance();
+            literal.push(e);
+            if let Some(sign) = self.peek().filter(|c| *c == '+' || *c == '-') {
+                self.advance();
+                literal.push(sign);
+            }
+            let exp = self.take_digits();
+            if exp.is_empty() {
+                return Err(self.error("Expected digits in exponent"));
+            }
+            literal.push_str(&exp);
+        }
+        literal
+            .parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite())
+            .map(JsonValue::Number)
+            .ok_or_else(|| {
+                self.position = start;
+                self.error("Invalid number")
+            })
+    }
+
+    fn take_digits(&mut self) -> String {
+        let mut digits = String::new();
+        while let Some(c) = self.peek().filter(|c| c.is_ascii_digit()) {
+            digits.push(c);
+            self.advance();
+        }
+        digits
+    }
+
+    /// Reports the message together with the expression and a caret marking the position.
+    fn error(&self, message: &str) -> anyhow::Error {
+        let expr: String = self.input.iter().collect();
+        let caret = " ".repeat(self.position);
+        anyhow!("Invalid filter at position {}: {}\n    {}\n    {}^", self.position, message, expr, caret)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        if self.consume(expected) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{}'", expected)))
+        }
+    }
+
+    fn skip_blank(&mut self) {
+        while matches!(self.peek(), Some(' ') | Some('\t') | Some('\n') | Some('\r')) {
+            self.advance();
+        }
+    }
+
+    fn consume(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn consume_str(&mut self, expected: &str) -> bool {
+        let len = expected.chars().count();
+        let matches = self.input.len() >= self.position + len
+            && self.input[self.position..self.position + len].iter().copied().eq(expected.chars());
+        if matches {
+            self.position += len;
+        }
+        matches
+    }
+
+    /// Consumes a keyword unless it is only the prefix of a longer identifier.
+    fn consume_keyword(&mut self, keyword: &str) -> bool {
+        let start = self.position;
+        if !self.consume_str(keyword) {
+            return false;
+        }
+        if self.peek().is_some_and(|c| is_ident_first(c) || c.is_ascii_digit()) {
+            self.position = start;
+            return false;
+        }
+        true
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input.get(self.position).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.input.get(self.position + offset).copied()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+        Some(c)
+    }
+}
+
+fn is_ident_first(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
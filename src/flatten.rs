@@ -0,0 +1,351 @@
+use anyhow::{anyhow, bail, Result};
+use crate::json_path::PathElement;
+use crate::json_value::{object_from_entries, take_padding, JsonValue};
+
+/// How array indices appear in flattened keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayNotation {
+    /// `skills[0].name`
+    Brackets,
+    /// `skills.0.name`
+    Separator,
+}
+
+#[derive(Debug, Clone)]
+pub struct FlattenOptions {
+    /// Separates member names in keys
+    pub separator: String,
+    pub array_notation: ArrayNotation,
+}
+
+impl Default for FlattenOptions {
+    fn default() -> Self {
+        FlattenOptions { separator: ".".to_string(), array_notation: ArrayNotation::Brackets }
+    }
+}
+
+impl FlattenOptions {
+    fn check(&self) -> Result<()> {
+        if self.separator.is_empty() || self.separator.contains('\\') {
+            bail!("The separator must not be empty or contain '\\'");
+        }
+        if self.array_notation == ArrayNotation::Brackets && self.separator.contains(['[', ']']) {
+            bail!("The separator must not contain brackets when indices are written in brackets");
+        }
+        if self.array_notation == ArrayNotation::Separator && self.separator.contains(|c: char| c.is_ascii_digit()) {
+            bail!("The separator must not contain digits when indices are written with the separator");
+        }
+        Ok(())
+    }
+}
+
+/// Turns nested arrays and objects into an object that maps a key for the path of every leaf
+/// to the leaf, e.g. `{"address.city": "Anytown", "skills[0]": "Rust"}`. Empty arrays and
+/// objects are leaves. Backslashes escape separators, brackets and backslashes in member
+/// names and, with separator notation, names consisting of digits only. Values other than
+/// non-empty arrays and objects are returned unchanged. With indices in brackets, an array
+/// that is the member "" of the document cannot be told apart from the document being an
+/// array and fails.
+pub fn flatten(value: &JsonValue, options: &FlattenOptions) -> Result<JsonValue> {
+    options.check()?;
+    if value.is_empty() {
+        return Ok(value.clone());
+    }
+    let mut entries = Vec::new();
+    flatten_into(value, &mut String::new(), true, options, &mut entries)?;
+    Ok(object_from_entries(entries))
+}
+
+fn flatten_into(
+    value: &JsonValue,
+    key: &mut String,
+    at_root: bool,
+    options: &FlattenOptions,
+    entries: &mut Vec<(String, JsonValue)>,
+) -> Result<()> {
+    let len = key.len();
+    match value {
+        JsonValue::Array(elements) if !elements.is_empty() => {
+            for (index, element) in elements.iter().enumerate() {
+                match options.array_notation {
+                    ArrayNotation::Brackets => key.push_str(&format!("[{}]", index)),
+                    ArrayNotation::Separator => {
+                        if !at_root {
+                            key.push_str(&options.separator);
+                        }
+                        key.push_str(&index.to_string());
+                    }
+                }
+                flatten_into(element, key, false, options, entries)?;
+                key.truncate(len);
+            }
+        }
+        JsonValue::Object(names, members) if !names.is_empty() => {
+            for name in names {
+                let member = &members[name];
+                // The keys would start with an index and be read as those of an array
+                let array = matches!(member, JsonValue::Array(elements) if !elements.is_empty());
+                if at_root && name.is_empty() && array && options.array_notation == ArrayNotation::Brackets {
+                    bail!("An array in the top-level member \"\" cannot be flattened with indices in brackets");
+                }
+                if !at_root {
+                    key.push_str(&options.separator);
+                }
+                key.push_str(&escape_name(name, options));
+                flatten_into(member, key, false, options, entries)?;
+                key.truncate(len);
+            }
+        }
+        _ => entries.push((key.clone(), value.clone())),
+    }
+    Ok(())
+}
+
+fn escape_name(name: &str, options: &FlattenOptions) -> String {
+    let mut escaped = String::new();
+    if options.array_notation == ArrayNotation::Separator && is_index(name) {
+        escaped.push('\\');
+    }
+    let mut rest = name;
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with(&options.separator) {
+            escaped.push('\\');
+            escaped.push_str(&options.separator);
+            rest = &rest[options.separator.len()..];
+            continue;
+        }
+        if c == '\\' || (options.array_notation == ArrayNotation::Brackets && c == '[') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    escaped
+}
+
+fn is_index(segment: &str) -> bool {
+    !segment.is_empty() && segment.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Reverses [`flatten`]: builds nested arrays and objects from an object of flattened keys.
+/// Arrays are padded with nulls up to the highest index, but with no more nulls in total than
+/// there are keys.
+pub fn unflatten(value: &JsonValue, options: &FlattenOptions) -> Result<JsonValue> {
+    options.check()?;
+    let JsonValue::Object(names, members) = value else {
+        bail!("Only objects can be unflattened");
+    };
+    let mut document = JsonValue::Null;
+    let mut padding = names.len();
+    for name in names {
+        let path = parse_key(name, options).map_err(|e| anyhow!("Invalid key '{}': {}", name, e))?;
+        insert(&mut document, &path, members[name].clone(), &mut padding).map_err(|e| anyhow!("Key '{}' {}", name, e))?;
+    }
+    Ok(match document {
+        JsonValue::Null => JsonValue::Object(Vec::new(), Default::default()),
+        document => document,
+    })
+}
+
+fn parse_key(key: &str, options: &FlattenOptions) -> Result<Vec<PathElement>> {
+    let brackets = options.array_notation == ArrayNotation::Brackets;
+    let mut path = Vec::new();
+    let mut name = String::new();
+    // Whether the name was escaped, which keeps it from being taken for an index
+    let mut escaped = false;
+    // Whether the characters read belong to a member name, false after an index in brackets
+    let mut in_name = true;
+    let mut rest = key;
+
+    let end_name = |path: &mut Vec<PathElement>, name: &mut String, escaped: &mut bool| {
+        let segment = std::mem::take(name);
+        if !brackets && !*escaped && is_index(&segment) {
+            path.push(PathElement::Index(segment.parse()?));
+        } else {
+            path.push(PathElement::Name(segment));
+        }
+        *escaped = false;
+        anyhow::Ok(())
+    };
+
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with(&options.separator) {
+            if in_name {
+                end_name(&mut path, &mut name, &mut escaped)?;
+            }
+            in_name = true;
+            rest = &rest[options.separator.len()..];
+        } else if brackets && c == '[' {
+            // A key may start with an index when the document is an array
+            if in_name && !(path.is_empty() && rest.len() == key.len()) {
+                end_name(&mut path, &mut name, &mut escaped)?;
+            }
+            let (index, after) = rest[1..].split_once(']').ok_or_else(|| anyhow!("missing ']'"))?;
+            if !is_index(index) {
+                bail!("'{}' is not an array index", index);
+            }
+            path.push(PathElement::Index(index.parse()?));
+            in_name = false;
+            rest = after;
+        } else if !in_name {
+            bail!("expected '{}' or '[' after ']'", options.separator);
+        } else if c == '\\' {
+            rest = &rest[1..];
+            escaped = true;
+            if rest.starts_with(&options.separator) {
+                name.push_str(&options.separator);
+                rest = &rest[options.separator.len()..];
+            } else {
+                let escaped_char = rest.chars().next().ok_or_else(|| anyhow!("incomplete escape sequence"))?;
+                name.push(escaped_char);
+                rest = &rest[escaped_char.len_utf8()..];
+            }
+        } else {
+            name.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    if in_name {
+        end_name(&mut path, &mut name, &mut escaped)?;
+    }
+    Ok(path)
+}
+
+/// Sets the value at the path, creating arrays and objects on the way and padding arrays
+/// with at most `padding` nulls. Null stands for values not set yet.
+fn insert(document: &mut JsonValue, path: &[PathElement], value: JsonValue, padding: &mut usize) -> Result<()> {
+    let Some((first, rest)) = path.split_first() else {
+        if !document.is_null() {
+            bail!("conflicts with a previous key: the value is set twice");
+        }
+        *document = value;
+        return Ok(());
+    };
+    match (first, &mut *document) {
+        (PathElement::Name(_), JsonValue::Null) => *document = JsonValue::Object(Vec::new(), Default::default()),
+        (PathElement::Index(_), JsonValue::Null) => *document = JsonValue::Array(Vec::new()),
+        _ => (),
+    }
+    match (first, document) {
+        (PathElement::Name(name), document @ JsonValue::Object(_, _)) => {
+            insert(&mut document[name.as_str()], rest, value, padding)
+        }
+        (PathElement::Index(index), document @ JsonValue::Array(_)) => {
+            if !take_padding(document, *index, padding) {
+                bail!("has the index {}, which leaves too many missing elements", index);
+            }
+            insert(&mut document[*index], rest, value, padding)
+        }
+        (PathElement::Name(name), document) => bail!("conflicts with a previous key: member '{}' of {}", name, document.kind()),
+        (PathElement::Index(index), document) => bail!("conflicts with a previous key: element {} of {}", index, document.kind()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    fn options(separator: &str, array_notation: ArrayNotation) -> FlattenOptions {
+        FlattenOptions { separator: separator.to_string(), array_notation }
+    }
+
+    #[test]
+    fn test_flatten() {
+        let document = parse_json(r#"{
+            "name": "John",
+            "address": {"city": "Anytown", "zip": null},
+            "skills": ["Rust", {"level": 3}],
+            "empty": [],
+            "none": {}
+        }"#).unwrap();
+        let flat = flatten(&document, &FlattenOptions::default()).unwrap();
+        assert_eq!(flat, parse_json(r#"{
+            "name": "John",
+            "address.city": "Anytown",
+            "address.zip": null,
+            "skills[0]": "Rust",
+            "skills[1].level": 3,
+            "empty": [],
+            "none": {}
+        }"#).unwrap());
+        assert_eq!(unflatten(&flat, &FlattenOptions::default()).unwrap(), document);
+
+        let options = options("/", ArrayNotation::Separator);
+        let flat = flatten(&document, &options).unwrap();
+        assert_eq!(flat.as_object().unwrap().0, ["name", "address/city", "address/zip", "skills/0", "skills/1/level", "empty", "none"]);
+        assert_eq!(unflatten(&flat, &options).unwrap(), document);
+
+        assert_eq!(flatten(&JsonValue::from(1), &FlattenOptions::default()).unwrap(), JsonValue::from(1));
+        assert_eq!(flatten(&JsonValue::Array(Vec::new()), &FlattenOptions::default()).unwrap(), JsonValue::Array(Vec::new()));
+        let array = parse_json(r#"[[1], {"a": 2}]"#).unwrap();
+        let flat = flatten(&array, &FlattenOptions::default()).unwrap();
+        assert_eq!(flat, parse_json(r#"{"[0][0]": 1, "[1].a": 2}"#).unwrap());
+        assert_eq!(unflatten(&flat, &FlattenOptions::default()).unwrap(), array);
+    }
+
+    #[test]
+    fn test_escaping() {
+        let document = parse_json(r#"{"a.b": {"c\\d": 1, "[x]": 2, "": {"": 3}}, "0": [4], "::": 5}"#).unwrap();
+        let flat = flatten(&document, &FlattenOptions::default()).unwrap();
+        assert_eq!(flat.as_object().unwrap().0, [r"a\.b.c\\d", r"a\.b.\[x]", r"a\.b..", "0[0]", "::"]);
+        assert_eq!(unflatten(&flat, &FlattenOptions::default()).unwrap(), document);
+
+        for options in [options("::", ArrayNotation::Separator), options("[", ArrayNotation::Separator)] {
+            let flat = flatten(&document, &options).unwrap();
+            assert_eq!(unflatten(&flat, &options).unwrap(), document);
+        }
+        let flat = flatten(&document, &options("::", ArrayNotation::Separator)).unwrap();
+        assert_eq!(flat.as_object().unwrap().0, ["a.b::c\\\\d", "a.b::[x]", "a.b::::", r"\0::0", r"\::"]);
+
+        // Empty member names of the document itself
+        for (json, keys) in [
+            (r#"{"": {"a": 1}, "a": 2}"#, [".a", "a"]),
+            (r#"{"": 1, "b": {"": 2}}"#, ["", "b."]),
+            (r#"{"": {"": 1}, ".": 2}"#, [".", r"\."]),
+        ] {
+            let document = parse_json(json).unwrap();
+            let flat = flatten(&document, &FlattenOptions::default()).unwrap();
+            assert_eq!(flat.as_object().unwrap().0, keys);
+            assert_eq!(unflatten(&flat, &FlattenOptions::default()).unwrap(), document);
+        }
+        let document = parse_json(r#"{"": [1], "0": [2]}"#).unwrap();
+        let options = options(".", ArrayNotation::Separator);
+        let flat = flatten(&document, &options).unwrap();
+        assert_eq!(flat.as_object().unwrap().0, [".0", r"\0.0"]);
+        assert_eq!(unflatten(&flat, &options).unwrap(), document);
+        assert!(flatten(&document, &FlattenOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_unflatten() {
+        let options = FlattenOptions::default();
+        let unflat = unflatten(&parse_json(r#"{"a[2]": 1, "a[0].b": 2, "c": {"d": 3}, "c.e": 4}"#).unwrap(), &options);
+        assert_eq!(unflat.unwrap(), parse_json(r#"{"a": [{"b": 2}, null, 1], "c": {"d": 3, "e": 4}}"#).unwrap());
+        assert_eq!(unflatten(&parse_json("{}").unwrap(), &options).unwrap(), parse_json("{}").unwrap());
+
+        let error = unflatten(&parse_json(r#"{"a": 1, "a.b": 2}"#).unwrap(), &options).unwrap_err();
+        assert_eq!(error.to_string(), "Key 'a.b' conflicts with a previous key: member 'b' of a number");
+        let error = unflatten(&parse_json(r#"{"a[0]": 1, "a.b": 2}"#).unwrap(), &options).unwrap_err();
+        assert_eq!(error.to_string(), "Key 'a.b' conflicts with a previous key: member 'b' of an array");
+        let error = unflatten(&parse_json(r#"{"a.b\\": 1}"#).unwrap(), &options);
+        assert_eq!(error.unwrap_err().to_string(), r"Invalid key 'a.b\': incomplete escape sequence");
+        for key in ["a[x]", "a[0", "a[0]b"] {
+            let flat = JsonValue::from_iter([(key.to_string(), 1)]);
+            assert!(unflatten(&flat, &options).unwrap_err().to_string().starts_with(&format!("Invalid key '{}'", key)));
+        }
+        for key in ["a[18446744073709551615]", "a[4000000000]", "a[2]"] {
+            let flat = JsonValue::from_iter([(key.to_string(), 1)]);
+            let error = unflatten(&flat, &options).unwrap_err().to_string();
+            assert!(error.ends_with("which leaves too many missing elements"), "{}", error);
+        }
+        assert!(unflatten(&JsonValue::from(1), &options).is_err());
+        assert!(flatten(&JsonValue::Null, &FlattenOptions { separator: String::new(), ..options.clone() }).is_err());
+        assert!(flatten(&JsonValue::Null, &FlattenOptions { separator: "]".to_string(), ..options }).is_err());
+        let document = parse_json(r#"{"a": [1, 2]}"#).unwrap();
+        assert!(flatten(&document, &self::options("0", ArrayNotation::Separator)).is_err());
+        assert!(unflatten(&document, &self::options("-1-", ArrayNotation::Separator)).is_err());
+        let flat = flatten(&document, &self::options("0", ArrayNotation::Brackets)).unwrap();
+        assert_eq!(unflatten(&flat, &self::options("0", ArrayNotation::Brackets)).unwrap(), document);
+    }
+}
@@ -0,0 +1,74 @@
+/// Indentation used for nested arrays and objects
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Indent {
+    Spaces(usize),
+    Tab,
+}
+
+impl Indent {
+    /// Number of columns a single indentation level takes; a tab counts as eight columns.
+    pub fn width(&self) -> usize {
+        match self {
+            Indent::Spaces(n) => *n,
+            Indent::Tab => 8,
+        }
+    }
+
+    fn as_str(&self) -> String {
+        match self {
+            Indent::Spaces(n) => " ".repeat(*n),
+            Indent::Tab => "\t".to_string(),
+        }
+    }
+
+    pub(crate) fn repeat(&self, level: usize) -> String {
+        self.as_str().repeat(level)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineEnding {
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+/// Whitespace style of the pretty printer output
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatOptions {
+    pub indent: Indent,
+    /// Put a space between an object key and the colon: `"key" : value`
+    pub space_before_colon: bool,
+    /// Put spaces inside brackets and braces of arrays and objects printed on a single line
+    pub space_in_brackets: bool,
+    /// Put a space after commas in compact mode
+    pub space_after_comma: bool,
+    pub line_ending: LineEnding,
+    /// Terminate the output with a line ending
+    pub final_newline: bool,
+    /// Escape all non-ASCII characters in strings as `\uXXXX`. Otherwise they are printed as
+    /// literal UTF-8, including characters that were escaped in the input.
+    pub ascii_only: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            indent: Indent::Spaces(4),
+            space_before_colon: false,
+            space_in_brackets: false,
+            space_after_comma: false,
+            line_ending: LineEnding::Lf,
+            final_newline: true,
+            ascii_only: false,
+        }
+    }
+}
\ No newline at end of file
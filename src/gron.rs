@@ -0,0 +1,208 @@
+use anyhow::{anyhow, bail, Result};
+use crate::format_options::FormatOptions;
+use crate::json_path::{NormalizedPath, PathElement};
+use crate::json_value::{take_padding, JsonValue};
+use crate::parser::parse_json;
+use crate::pretty_printer::{escape_string, escape_string_ascii, format_number};
+
+impl JsonValue {
+    /// Prints one assignment per node in document order, e.g. `json.address.city = "Anytown";`,
+    /// so that the output can be searched with line-based tools. Arrays and objects are
+    /// assigned `[]` and `{}` before their children. Member names that are not identifiers
+    /// are written as quoted strings in brackets: `json["first name"]`. Only the line ending
+    /// and `ascii_only` are taken from the options.
+    pub fn to_gron(&self, options: &FormatOptions) -> String {
+        let quote = |s: &str| match options.ascii_only {
+            true => format!("\"{}\"", escape_string_ascii(s)),
+            false => format!("\"{}\"", escape_string(s)),
+        };
+        let mut output = String::new();
+        for (path, value) in self.walk() {
+            output.push_str(&gron_path(&path, &quote));
+            output.push_str(" = ");
+            match value {
+                JsonValue::Null => output.push_str("null"),
+                JsonValue::Bool(b) => output.push_str(&b.to_string()),
+                JsonValue::Number(n) => output.push_str(&format_number(*n)),
+                JsonValue::String(s) => output.push_str(&quote(s)),
+                JsonValue::Array(_) => output.push_str("[]"),
+                JsonValue::Object(_, _) => output.push_str("{}"),
+            }
+            output.push(';');
+            output.push_str(options.line_ending.as_str());
+        }
+        output
+    }
+}
+
+fn gron_path(path: &NormalizedPath, quote: &impl Fn(&str) -> String) -> String {
+    let mut text = "json".to_string();
+    for element in &path.0 {
+        match element {
+            PathElement::Name(name) if is_identifier(name) => {
+                text.push('.');
+                text.push_str(name);
+            }
+            PathElement::Name(name) => text.push_str(&format!("[{}]", quote(name))),
+            PathElement::Index(index) => text.push_str(&format!("[{}]", index)),
+        }
+    }
+    text
+}
+
+/// JavaScript identifiers restricted to ASCII
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Rebuilds a document from the output of [`JsonValue::to_gron`]. The lines may come in any
+/// order; missing array elements become null, up to one per line of input. Assigning `{}` or `[]` to an existing object or
+/// array keeps its contents, any other assignment replaces the previous value. Empty lines are
+/// ignored.
+pub fn parse_gron(input: &str) -> Result<JsonValue> {
+    let mut document = JsonValue::Null;
+    let mut padding = input.lines().count();
+    for (number, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (path, value) = parse_assignment(line.trim()).map_err(|e| anyhow!("Line {}: {}", number + 1, e))?;
+        assign(&mut document, &path, value, &mut padding).map_err(|e| anyhow!("Line {}: {}", number + 1, e))?;
+    }
+    Ok(document)
+}
+
+fn parse_assignment(line: &str) -> Result<(Vec<PathElement>, JsonValue)> {
+    let Some(mut rest) = line.strip_prefix("json") else {
+        bail!("expected an assignment to 'json'");
+    };
+    let mut path = Vec::new();
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix('.') {
+            let after = after.trim_start();
+            let end = after.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '$')).unwrap_or(after.len());
+            if !is_identifier(&after[..end]) {
+                bail!("expected a member name after '.'");
+            }
+            path.push(PathElement::Name(after[..end].to_string()));
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix("[\"") {
+            let end = string_end(after).ok_or_else(|| anyhow!("unterminated string"))?;
+            let JsonValue::String(name) = parse_json(&rest[1..end + 3])? else { unreachable!() };
+            path.push(PathElement::Name(name));
+            rest = after[end + 1..].strip_prefix(']').ok_or_else(|| anyhow!("expected ']'"))?;
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let (index, after) = after.split_once(']').ok_or_else(|| anyhow!("expected ']'"))?;
+            path.push(PathElement::Index(index.trim().parse().map_err(|_| anyhow!("invalid array index '{}'", index))?));
+            rest = after;
+        } else {
+            break;
+        }
+    }
+    let value = rest.strip_prefix('=').ok_or_else(|| anyhow!("expected '='"))?;
+    let value = value.strip_suffix(';').unwrap_or(value);
+    Ok((path, parse_json(value)?))
+}
+
+/// Byte position of the quotation mark closing a string whose opening quotation mark precedes
+/// `s`
+fn string_end(s: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (position, c) in s.char_indices() {
+        match c {
+            '"' if !escaped => return Some(position),
+            '\\' => escaped = !escaped,
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+/// Sets the value at the path, padding arrays with at most `padding` nulls
+fn assign(document: &mut JsonValue, path: &[PathElement], value: JsonValue, padding: &mut usize) -> Result<()> {
+    let mut target = document;
+    for element in path {
+        target = match (element, target) {
+            (PathElement::Name(name), target @ (JsonValue::Null | JsonValue::Object(_, _))) => &mut target[name.as_str()],
+            (PathElement::Index(index), target @ (JsonValue::Null | JsonValue::Array(_))) => {
+                if !take_padding(target, *index, padding) {
+                    bail!("array index {} leaves too many missing elements", index);
+                }
+                &mut target[*index]
+            }
+            (PathElement::Name(name), target) => bail!("cannot assign member '{}' of {}", name, target.kind()),
+            (PathElement::Index(index), target) => bail!("cannot assign element {} of {}", index, target.kind()),
+        };
+    }
+    let keep = match (&*target, &value) {
+        (JsonValue::Array(_), JsonValue::Array(elements)) => elements.is_empty(),
+        (JsonValue::Object(_, _), JsonValue::Object(names, _)) => names.is_empty(),
+        _ => false,
+    };
+    if !keep {
+        *target = value;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_options::LineEnding;
+
+    #[test]
+    fn test_to_gron() {
+        let document = parse_json(r#"{
+            "name": "John \"J\"",
+            "address": {"city": "Anytown", "zip code": null},
+            "skills": ["Rust", {"level": 3.5}],
+            "empty": [],
+            "$id_2": {},
+            "2x": true,
+            "ä": "ö"
+        }"#).unwrap();
+        assert_eq!(document.to_gron(&FormatOptions::default()), r#"json = {};
+json.name = "John \"J\"";
+json.address = {};
+json.address.city = "Anytown";
+json.address["zip code"] = null;
+json.skills = [];
+json.skills[0] = "Rust";
+json.skills[1] = {};
+json.skills[1].level = 3.5;
+json.empty = [];
+json.$id_2 = {};
+json["2x"] = true;
+json["ä"] = "ö";
+"#);
+        assert_eq!(parse_gron(&document.to_gron(&FormatOptions::default())).unwrap(), document);
+
+        let options = FormatOptions { ascii_only: true, line_ending: LineEnding::CrLf, ..FormatOptions::default() };
+        assert_eq!(parse_json(r#"{"ä": 1}"#).unwrap().to_gron(&options), "json = {};\r\njson[\"\\u00e4\"] = 1;\r\n");
+        assert_eq!(JsonValue::from(1).to_gron(&FormatOptions::default()), "json = 1;\n");
+    }
+
+    #[test]
+    fn test_parse_gron() {
+        let document = parse_gron(r#"
+            json.b[2] = "x";
+            json = {};
+            json["a\"]"].c = [1, {"d": true}];
+            json.b = [];
+            json . e = 1
+        "#).unwrap();
+        assert_eq!(document, parse_json(r#"{"b": [null, null, "x"], "a\"]": {"c": [1, {"d": true}]}, "e": 1}"#).unwrap());
+        assert_eq!(parse_gron("").unwrap(), JsonValue::Null);
+
+        for line in ["json[18446744073709551615] = 1;", "json[4000000000] = 1;", "json.a[2] = 1;"] {
+            assert!(parse_gron(line).unwrap_err().to_string().ends_with("leaves too many missing elements"), "{}", line);
+        }
+        assert_eq!(parse_gron("json = 1;\njson.a = 2;").unwrap_err().to_string(), "Line 2: cannot assign member 'a' of a number");
+        for line in ["data.a = 1;", "json.a = ;", "json.1 = 1;", "json[x] = 1;", "json[\"a] = 1;", "json.a 1;"] {
+            assert!(parse_gron(line).unwrap_err().to_string().starts_with("Line 1: "), "{}", line);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+/// Builds a `JsonValue` from JSON-like syntax, e.g. `json!({"a": 1, "b": [true, null], "c": some_var})`.
+/// Members keep the order in which they are written, any expression implementing
+/// `Into<JsonValue>` can be interpolated and keys are string literals or expressions in
+/// parentheses.
+#[macro_export]
+macro_rules! json {
+    (null) => {
+        $crate::JsonValue::Null
+    };
+    ([ $($elements:tt)* ]) => {
+        $crate::JsonValue::Array($crate::json!(@array [] $($elements)*))
+    };
+    ({ $($members:tt)* }) => {
+        $crate::__private::object($crate::json!(@object [] $($members)*))
+    };
+    ($other:expr) => {
+        $crate::JsonValue::from($other)
+    };
+
+    // Elements and members are munched one at a time. null, arrays and objects are matched
+    // before expressions since an expression fragment cannot be matched against tokens again.
+    (@array [$($done:expr,)*]) => {
+        ::std::vec![$($done),*]
+    };
+    (@array [$($done:expr,)*] null $(, $($rest:tt)*)?) => {
+        $crate::json!(@array [$($done,)* $crate::JsonValue::Null,] $($($rest)*)?)
+    };
+    (@array [$($done:expr,)*] [ $($element:tt)* ] $(, $($rest:tt)*)?) => {
+        $crate::json!(@array [$($done,)* $crate::json!([$($element)*]),] $($($rest)*)?)
+    };
+    (@array [$($done:expr,)*] { $($element:tt)* } $(, $($rest:tt)*)?) => {
+        $crate::json!(@array [$($done,)* $crate::json!({$($element)*}),] $($($rest)*)?)
+    };
+    (@array [$($done:expr,)*] $element:expr $(, $($rest:tt)*)?) => {
+        $crate::json!(@array [$($done,)* $crate::JsonValue::from($element),] $($($rest)*)?)
+    };
+
+    (@object [$($done:expr,)*]) => {
+        ::std::vec![$($done),*]
+    };
+    (@object [$($done:expr,)*] $key:tt : null $(, $($rest:tt)*)?) => {
+        $crate::json!(@object [$($done,)* ($crate::json!(@key $key), $crate::JsonValue::Null),] $($($rest)*)?)
+    };
+    (@object [$($done:expr,)*] $key:tt : [ $($value:tt)* ] $(, $($rest:tt)*)?) => {
+        $crate::json!(@object [$($done,)* ($crate::json!(@key $key), $crate::json!([$($value)*])),] $($($rest)*)?)
+    };
+    (@object [$($done:expr,)*] $key:tt : { $($value:tt)* } $(, $($rest:tt)*)?) => {
+        $crate::json!(@object [$($done,)* ($crate::json!(@key $key), $crate::json!({$($value)*})),] $($($rest)*)?)
+    };
+    (@object [$($done:expr,)*] $key:tt : $value:expr $(, $($rest:tt)*)?) => {
+        $crate::json!(@object [$($done,)* ($crate::json!(@key $key), $crate::JsonValue::from($value)),] $($($rest)*)?)
+    };
+
+    (@key $key:literal) => {
+        ::std::string::String::from($key)
+    };
+    (@key ($key:expr)) => {
+        ::std::string::ToString::to_string(&$key)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json_value::JsonValue;
+    use crate::parser::parse_json;
+
+    #[test]
+    fn test_json_macro() {
+        let name = "Ann".to_string();
+        let key = "dynamic";
+        let value = json!({
+            "name": name,
+            "age": 40 + 2,
+            "tags": [true, null, [], {}, "a", -1.5],
+            "address": { "city": "Berlin", "zip": null },
+            (key): Some(1),
+            "none": None::<bool>,
+        });
+        assert_eq!(value, parse_json(r#"{
+            "name": "Ann",
+            "age": 42,
+            "tags": [true, null, [], {}, "a", -1.5],
+            "address": {"city": "Berlin", "zip": null},
+            "dynamic": 1,
+            "none": null
+        }"#).unwrap());
+
+        assert_eq!(json!(null), JsonValue::Null);
+        assert_eq!(json!([]), JsonValue::Array(vec![]));
+        assert_eq!(json!({}), parse_json("{}").unwrap());
+        assert_eq!(json!([1, [2, [3]],]), parse_json("[1, [2, [3]]]").unwrap());
+        assert_eq!(json!(vec![1, 2]), parse_json("[1, 2]").unwrap());
+        assert_eq!(json!(f64::NAN), JsonValue::Null);
+        assert_eq!(json!({"a": 1, "a": 2}), parse_json(r#"{"a": 2}"#).unwrap());
+    }
+}
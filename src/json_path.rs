@@ -0,0 +1,655 @@
+mod parser;
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use anyhow::Result;
+use regex::Regex;
+use crate::json_value::{json_equal, JsonValue};
+
+/// A compiled JSONPath query (RFC 9535), e.g. `$.store.book[?@.price < 10].title`
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPath {
+    query: Query,
+}
+
+/// A node matched by a JSONPath query: a value together with its location in the document
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node<'a> {
+    pub path: NormalizedPath,
+    pub value: &'a JsonValue,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathElement {
+    Name(String),
+    Index(usize),
+}
+
+/// Location of a node in a document. It is displayed as a normalized path like
+/// `$['store']['book'][0]`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NormalizedPath(pub Vec<PathElement>);
+
+impl NormalizedPath {
+    pub(crate) fn child(&self, element: PathElement) -> Self {
+        let mut elements = self.0.clone();
+        elements.push(element);
+        NormalizedPath(elements)
+    }
+
+    /// Converts the path into a JSON pointer (RFC 6901).
+    pub fn to_pointer(&self) -> String {
+        let tokens: Vec<String> = self
+            .0
+            .iter()
+            .map(|element| match element {
+                PathElement::Name(name) => name.clone(),
+                PathElement::Index(index) => index.to_string(),
+            })
+            .collect();
+        crate::pointer::to_pointer(&tokens)
+    }
+}
+
+impl fmt::Display for NormalizedPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "$")?;
+        for element in &self.0 {
+            match element {
+                PathElement::Name(name) => write!(f, "['{}']", escape_name(name))?,
+                PathElement::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn escape_name(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for c in name.chars() {
+        match c {
+            '\'' => escaped.push_str("\\'"),
+            '\\' => escaped.push_str("\\\\"),
+            '\u{0008}' => escaped.push_str("\\b"),
+            '\u{000C}' => escaped.push_str("\\f"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c < '\u{0020}' => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl JsonPath {
+    pub fn parse(path: &str) -> Result<Self> {
+        Ok(JsonPath { query: parser::parse_query(path)? })
+    }
+
+    /// Returns all nodes of the document matched by the query.
+    pub fn query<'a>(&self, document: &'a JsonValue) -> Vec<Node<'a>> {
+        let evaluator = Evaluator { root: document, regex_cache: RefCell::new(HashMap::new()) };
+        evaluator.eval_query(&self.query, document)
+    }
+}
+
+impl FromStr for JsonPath {
+    type Err = anyhow::Error;
+
+    fn from_str(path: &str) -> Result<Self> {
+        JsonPath::parse(path)
+    }
+}
+
+impl JsonValue {
+    /// Evaluates a JSONPath query against the value.
+    pub fn query(&self, path: &str) -> Result<Vec<Node<'_>>> {
+        Ok(JsonPath::parse(path)?.query(self))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Query {
+    relative: bool,
+    segments: Vec<Segment>,
+}
+
+impl Query {
+    /// A singular query selects at most one node: it only consists of name and index selectors.
+    fn is_singular(&self) -> bool {
+        self.segments.iter().all(|segment| match segment {
+            Segment::Child(selectors) => {
+                selectors.len() == 1
+                    && matches!(selectors[0], Selector::Name(_) | Selector::Index(_))
+            }
+            Segment::Descendant(_) => false,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Child(Vec<Selector>),
+    Descendant(Vec<Selector>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+    Filter(LogicalExpr),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum LogicalExpr {
+    Or(Vec<LogicalExpr>),
+    And(Vec<LogicalExpr>),
+    Not(Box<LogicalExpr>),
+    Comparison(Operand, ComparisonOp, Operand),
+    Test(Operand),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ComparisonOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Literal(JsonValue),
+    Query(Query),
+    Function(FunctionExpr),
+    Logical(Box<LogicalExpr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Function {
+    Length,
+    Count,
+    Match,
+    Search,
+    Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ExprType {
+    Value,
+    Logical,
+    Nodes,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "length" => Some(Function::Length),
+            "count" => Some(Function::Count),
+            "match" => Some(Function::Match),
+            "search" => Some(Function::Search),
+            "value" => Some(Function::Value),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Function::Length => "length",
+            Function::Count => "count",
+            Function::Match => "match",
+            Function::Search => "search",
+            Function::Value => "value",
+        }
+    }
+
+    fn parameter_types(&self) -> &'static [ExprType] {
+        match self {
+            Function::Length => &[ExprType::Value],
+            Function::Count | Function::Value => &[ExprType::Nodes],
+            Function::Match | Function::Search => &[ExprType::Value, ExprType::Value],
+        }
+    }
+
+    fn result_type(&self) -> ExprType {
+        match self {
+            Function::Length | Function::Count | Function::Value => ExprType::Value,
+            Function::Match | Function::Search => ExprType::Logical,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct FunctionExpr {
+    function: Function,
+    arguments: Vec<Operand>,
+}
+
+/// Result of evaluating a function argument or comparable
+enum Evaluated<'a> {
+    /// A single value or `None` for the special result "Nothing"
+    Value(Option<Cow<'a, JsonValue>>),
+    Logical(bool),
+    Nodes(Vec<&'a JsonValue>),
+}
+
+struct Evaluator<'a> {
+    root: &'a JsonValue,
+    regex_cache: RefCell<HashMap<(String, bool), Option<Regex>>>,
+}
+
+impl<'a> Evaluator<'a> {
+    fn eval_query(&self, query: &Query, current: &'a JsonValue) -> Vec<Node<'a>> {
+        let start = if query.relative { current } else { self.root };
+        let mut nodes = vec![Node { path: NormalizedPath::default(), value: start }];
+        for segment in &query.segments {
+            let mut selected = Vec::new();
+            for node in &nodes {
+                match segment {
+                    Segment::Child(selectors) => self.select_all(selectors, node, &mut selected),
+                    Segment::Descendant(selectors) => self.select_descendants(selectors, node, &mut selected),
+                }
+            }
+            nodes = selected;
+        }
+        nodes
+    }
+
+    fn select_descendants(&self, selectors: &[Selector], node: &Node<'a>, selected: &mut Vec<Node<'a>>) {
+        self.select_all(selectors, node, selected);
+        for child in children(node) {
+            self.select_descendants(selectors, &child, selected);
+        }
+    }
+
+    fn select_all(&self, selectors: &[Selector], node: &Node<'a>, selected: &mut Vec<Node<'a>>) {
+        for selector in selectors {
+            self.select(selector, node, selected);
+        }
+    }
+
+    fn select(&self, selector: &Selector, node: &Node<'a>, selected: &mut Vec<Node<'a>>) {
+        match (selector, node.value) {
+            (Selector::Name(name), JsonValue::Object(_, members)) => {
+                if let Some(value) = members.get(name) {
+                    selected.push(Node { path: node.path.child(PathElement::Name(name.clone())), value });
+                }
+            }
+            (Selector::Wildcard, _) => selected.extend(children(node)),
+            (Selector::Index(index), JsonValue::Array(elements)) => {
+                let len = elements.len() as i64;
+                let index = if *index < 0 { len + index } else { *index };
+                if (0..len).contains(&index) {
+                    let index = index as usize;
+                    selected.push(Node {
+                        path: node.path.child(PathElement::Index(index)),
+                        value: &elements[index],
+                    });
+                }
+            }
+            (Selector::Slice(start, end, step), JsonValue::Array(elements)) => {
+                for index in slice_indices(elements.len() as i64, *start, *end, *step) {
+                    selected.push(Node {
+                        path: node.path.child(PathElement::Index(index)),
+                        value: &elements[index],
+                    });
+                }
+            }
+            (Selector::Filter(expr), _) => {
+                selected.extend(children(node).filter(|child| self.eval_logical(expr, child.value)));
+            }
+            _ => (),
+        }
+    }
+
+    fn eval_logical(&self, expr: &LogicalExpr, current: &'a JsonValue) -> bool {
+        match expr {
+            LogicalExpr::Or(exprs) => exprs.iter().any(|expr| self.eval_logical(expr, current)),
+            LogicalExpr::And(exprs) => exprs.iter().all(|expr| self.eval_logical(expr, current)),
+            LogicalExpr::Not(expr) => !self.eval_logical(expr, current),
+            LogicalExpr::Comparison(left, op, right) => {
+                let left = self.eval_value(left, current);
+                let right = self.eval_value(right, current);
+                compare(left.as_deref(), *op, right.as_deref())
+            }
+            LogicalExpr::Test(operand) => match self.eval_operand(operand, current) {
+                Evaluated::Logical(result) => result,
+                Evaluated::Nodes(nodes) => !nodes.is_empty(),
+                Evaluated::Value(value) => value.is_some(),
+            },
+        }
+    }
+
+    fn eval_value(&self, operand: &Operand, current: &'a JsonValue) -> Option<Cow<'a, JsonValue>> {
+        match self.eval_operand(operand, current) {
+            Evaluated::Value(value) => value,
+            Evaluated::Nodes(nodes) if nodes.len() == 1 => Some(Cow::Borrowed(nodes[0])),
+            _ => None,
+        }
+    }
+
+    fn eval_operand(&self, operand: &Operand, current: &'a JsonValue) -> Evaluated<'a> {
+        match operand {
+            Operand::Literal(value) => Evaluated::Value(Some(Cow::Owned(value.clone()))),
+            Operand::Query(query) => {
+                Evaluated::Nodes(self.eval_query(query, current).into_iter().map(|node| node.value).collect())
+            }
+            Operand::Logical(expr) => Evaluated::Logical(self.eval_logical(expr, current)),
+            Operand::Function(function_expr) => self.eval_function(function_expr, current),
+        }
+    }
+
+    fn eval_function(&self, function_expr: &FunctionExpr, current: &'a JsonValue) -> Evaluated<'a> {
+        let arguments = &function_expr.arguments;
+        match function_expr.function {
+            Function::Length => {
+                let length = match self.eval_value(&arguments[0], current).as_deref() {
+                    Some(JsonValue::String(s)) => Some(s.chars().count()),
+                    Some(JsonValue::Array(elements)) => Some(elements.len()),
+                    Some(JsonValue::Object(names, _)) => Some(names.len()),
+                    _ => None,
+                };
+                Evaluated::Value(length.map(|length| Cow::Owned(JsonValue::Number(length as f64))))
+            }
+            Function::Count => match self.eval_operand(&arguments[0], current) {
+                Evaluated::Nodes(nodes) => Evaluated::Value(Some(Cow::Owned(JsonValue::Number(nodes.len() as f64)))),
+                _ => Evaluated::Value(None),
+            },
+            Function::Value => match self.eval_operand(&arguments[0], current) {
+                Evaluated::Nodes(nodes) if nodes.len() == 1 => Evaluated::Value(Some(Cow::Borrowed(nodes[0]))),
+                _ => Evaluated::Value(None),
+            },
+            Function::Match | Function::Search => {
+                let text = self.eval_value(&arguments[0], current);
+                let pattern = self.eval_value(&arguments[1], current);
+                let result = match (text.as_deref(), pattern.as_deref()) {
+                    (Some(JsonValue::String(text)), Some(JsonValue::String(pattern))) => {
+                        let full_match = function_expr.function == Function::Match;
+                        self.regex_matches(pattern, full_match, text)
+                    }
+                    _ => false,
+                };
+                Evaluated::Logical(result)
+            }
+        }
+    }
+
+    fn regex_matches(&self, pattern: &str, full_match: bool, text: &str) -> bool {
+        let mut cache = self.regex_cache.borrow_mut();
+        let regex = cache
+            .entry((pattern.to_string(), full_match))
+            .or_insert_with(|| compile_i_regexp(pattern, full_match));
+        regex.as_ref().is_some_and(|regex| regex.is_match(text))
+    }
+}
+
+fn children<'a>(node: &Node<'a>) -> impl Iterator<Item = Node<'a>> {
+    let path = node.path.clone();
+    let (names, members, elements) = match node.value {
+        JsonValue::Object(names, members) => (names.as_slice(), Some(members), [].as_slice()),
+        JsonValue::Array(elements) => ([].as_slice(), None, elements.as_slice()),
+        _ => ([].as_slice(), None, [].as_slice()),
+    };
+    let object_children = names.iter().filter_map(move |name| {
+        let value = members?.get(name)?;
+        Some((PathElement::Name(name.clone()), value))
+    });
+    let array_children = elements.iter().enumerate().map(|(index, value)| (PathElement::Index(index), value));
+    object_children
+        .chain(array_children)
+        .map(move |(element, value)| Node { path: path.child(element), value })
+}
+
+fn slice_indices(len: i64, start: Option<i64>, end: Option<i64>, step: Option<i64>) -> Vec<usize> {
+    let step = step.unwrap_or(1);
+    if step == 0 {
+        return Vec::new();
+    }
+    let normalize = |i: i64| if i >= 0 { i } else { len + i };
+
+    let mut indices = Vec::new();
+    if step > 0 {
+        let lower = normalize(start.unwrap_or(0)).clamp(0, len);
+        let upper = normalize(end.unwrap_or(len)).clamp(0, len);
+        let mut i = lower;
+        while i < upper {
+            indices.push(i as usize);
+            i += step;
+        }
+    } else {
+        let upper = normalize(start.unwrap_or(len - 1)).clamp(-1, len - 1);
+        let lower = end.map_or(-1, |end| normalize(end).clamp(-1, len - 1));
+        let mut i = upper;
+        while lower < i {
+            indices.push(i as usize);
+            i += step;
+        }
+    }
+    indices
+}
+
+fn compare(left: Option<&JsonValue>, op: ComparisonOp, right: Option<&JsonValue>) -> bool {
+    match op {
+        ComparisonOp::Eq => values_equal(left, right),
+        ComparisonOp::Ne => !values_equal(left, right),
+        ComparisonOp::Lt => less_than(left, right),
+        ComparisonOp::Le => less_than(left, right) || values_equal(left, right),
+        ComparisonOp::Gt => less_than(right, left),
+        ComparisonOp::Ge => less_than(right, left) || values_equal(left, right),
+    }
+}
+
+fn values_equal(left: Option<&JsonValue>, right: Option<&JsonValue>) -> bool {
+    match (left, right) {
+        (None, None) => true,
+        (Some(left), Some(right)) => json_equal(left, right),
+        _ => false,
+    }
+}
+
+fn less_than(left: Option<&JsonValue>, right: Option<&JsonValue>) -> bool {
+    match (left, right) {
+        (Some(JsonValue::Number(l)), Some(JsonValue::Number(r))) => l < r,
+        (Some(JsonValue::String(l)), Some(JsonValue::String(r))) => l < r,
+        _ => false,
+    }
+}
+
+/// Translates an I-Regexp (RFC 9485) into the syntax of the regex crate. The only difference
+/// that matters is that `.` does not match carriage returns and line feeds.
+fn compile_i_regexp(pattern: &str, full_match: bool) -> Option<Regex> {
+    let mut translated = String::new();
+    let mut in_class = false;
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                translated.push(c);
+                translated.push(chars.next()?);
+            }
+            '[' if !in_class => {
+                in_class = true;
+                translated.push(c);
+            }
+            ']' if in_class => {
+                in_class = false;
+                translated.push(c);
+            }
+            '.' if !in_class => translated.push_str("[^\\n\\r]"),
+            c => translated.push(c),
+        }
+    }
+    let translated = if full_match { format!("\\A(?:{})\\z", translated) } else { translated };
+    Regex::new(&translated).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    const STORE: &str = r#"{ "store": {
+        "book": [
+          { "category": "reference",
+            "author": "Nigel Rees",
+            "title": "Sayings of the Century",
+            "price": 8.95
+          },
+          { "category": "fiction",
+            "author": "Evelyn Waugh",
+            "title": "Sword of Honour",
+            "price": 12.99
+          },
+          { "category": "fiction",
+            "author": "Herman Melville",
+            "title": "Moby Dick",
+            "isbn": "0-553-21311-3",
+            "price": 8.99
+          },
+          { "category": "fiction",
+            "author": "J. R. R. Tolkien",
+            "title": "The Lord of the Rings",
+            "isbn": "0-395-19395-8",
+            "price": 22.99
+          }
+        ],
+        "bicycle": {
+          "color": "red",
+          "price": 399
+        }
+      }
+    }"#;
+
+    fn query(json: &str, path: &str) -> Vec<(String, JsonValue)> {
+        let document = parse_json(json).unwrap();
+        let path = JsonPath::parse(path).unwrap();
+        path.query(&document)
+            .into_iter()
+            .map(|node| (node.path.to_string(), node.value.clone()))
+            .collect()
+    }
+
+    fn paths(json: &str, path: &str) -> Vec<String> {
+        query(json, path).into_iter().map(|(path, _)| path).collect()
+    }
+
+    fn values(json: &str, path: &str) -> Vec<JsonValue> {
+        query(json, path).into_iter().map(|(_, value)| value).collect()
+    }
+
+    fn strings(values: &[&str]) -> Vec<JsonValue> {
+        values.iter().map(|s| JsonValue::String(s.to_string())).collect()
+    }
+
+    #[test]
+    fn test_store_examples() {
+        // RFC 9535, table 2
+        assert_eq!(
+            values(STORE, "$.store.book[*].author"),
+            strings(&["Nigel Rees", "Evelyn Waugh", "Herman Melville", "J. R. R. Tolkien"])
+        );
+        assert_eq!(values(STORE, "$..author").len(), 4);
+        assert_eq!(values(STORE, "$.store.*").len(), 2);
+        assert_eq!(values(STORE, "$.store..price").len(), 5);
+        assert_eq!(paths(STORE, "$..book[2]"), vec!["$['store']['book'][2]"]);
+        assert_eq!(values(STORE, "$..book[2].author"), strings(&["Herman Melville"]));
+        assert!(values(STORE, "$..book[2].publisher").is_empty());
+        assert_eq!(values(STORE, "$..book[-1].title"), strings(&["The Lord of the Rings"]));
+        assert_eq!(paths(STORE, "$..book[0,1]"), vec!["$['store']['book'][0]", "$['store']['book'][1]"]);
+        assert_eq!(paths(STORE, "$..book[:2]"), vec!["$['store']['book'][0]", "$['store']['book'][1]"]);
+        assert_eq!(values(STORE, "$..book[?@.isbn].title"), strings(&["Moby Dick", "The Lord of the Rings"]));
+        assert_eq!(values(STORE, "$..book[?@.price<10].title"), strings(&["Sayings of the Century", "Moby Dick"]));
+        assert_eq!(values(STORE, "$.store.book[?@.price < 10].title"), strings(&["Sayings of the Century", "Moby Dick"]));
+        assert_eq!(values(STORE, "$..*").len(), 27);
+    }
+
+    #[test]
+    fn test_slices() {
+        let json = r#"["a", "b", "c", "d", "e", "f", "g"]"#;
+        assert_eq!(values(json, "$[1:3]"), strings(&["b", "c"]));
+        assert_eq!(values(json, "$[5:]"), strings(&["f", "g"]));
+        assert_eq!(values(json, "$[1:5:2]"), strings(&["b", "d"]));
+        assert_eq!(values(json, "$[5:1:-2]"), strings(&["f", "d"]));
+        assert_eq!(values(json, "$[::-1]"), strings(&["g", "f", "e", "d", "c", "b", "a"]));
+        assert!(values(json, "$[::0]").is_empty());
+        assert_eq!(values(json, "$[-2:]"), strings(&["f", "g"]));
+    }
+
+    #[test]
+    fn test_filters() {
+        let json = r#"{
+            "a": [3, 5, 1, 2, 4, 6, {"b": "j"}, {"b": "k"}, {"b": {}}, {"b": "kilo"}],
+            "o": {"p": 1, "q": 2, "r": 3, "s": 5, "t": {"u": 6}},
+            "e": "f"
+        }"#;
+        assert_eq!(paths(json, "$.a[?@.b == 'kilo']"), vec!["$['a'][9]"]);
+        assert_eq!(paths(json, "$.a[?(@.b == 'kilo')]"), vec!["$['a'][9]"]);
+        assert_eq!(values(json, "$.a[?@>3.5]"), values("[5, 4, 6]", "$[*]"));
+        assert_eq!(paths(json, "$.a[?@.b]").len(), 4);
+        assert_eq!(values(json, "$[?@.*]").len(), 2);
+        assert_eq!(paths(json, "$[?@[?@.b]]"), vec!["$['a']"]);
+        assert_eq!(values(json, "$.o[?@<3, ?@<3]").len(), 4);
+        assert_eq!(values(json, "$.a[?@<2 || @.b == \"k\"]"), values(r#"[1, {"b": "k"}]"#, "$[*]"));
+        assert_eq!(values(json, "$.a[?match(@.b, '[jk]')]"), values(r#"[{"b": "j"}, {"b": "k"}]"#, "$[*]"));
+        assert_eq!(values(json, "$.a[?search(@.b, '[jk]')]").len(), 3);
+        assert_eq!(paths(json, "$.o[?@>1 && @<4]"), vec!["$['o']['q']", "$['o']['r']"]);
+        assert_eq!(paths(json, "$.o[?@.u || @.x]"), vec!["$['o']['t']"]);
+        assert_eq!(values(json, "$.a[?@.b == $.x]").len(), 6);
+        assert_eq!(values(json, "$.a[?@ == @]").len(), 10);
+        assert_eq!(values(json, "$.a[?!@.b]").len(), 6);
+        assert_eq!(values(json, "$.a[?!(@.b == 'j')]").len(), 9);
+        assert_eq!(values(json, "$.o[?@.u == 6]").len(), 1);
+    }
+
+    #[test]
+    fn test_functions() {
+        let json = r#"[
+            {"name": "ab", "tags": [1, 2, 3]},
+            {"name": "abc", "tags": []},
+            {"name": "a\nb", "tags": [1]}
+        ]"#;
+        assert_eq!(paths(json, "$[?length(@.name) == 3]"), vec!["$[1]", "$[2]"]);
+        assert_eq!(paths(json, "$[?length(@.tags) > 1]"), vec!["$[0]"]);
+        assert_eq!(paths(json, "$[?count(@.tags[*]) == 1]"), vec!["$[2]"]);
+        assert_eq!(paths(json, "$[?value(@.tags[0]) == 1]"), vec!["$[0]", "$[2]"]);
+        assert_eq!(paths(json, "$[?match(@.name, 'a.c')]"), vec!["$[1]"]);
+        assert_eq!(paths(json, "$[?match(@.name, 'a.b')]"), Vec::<String>::new());
+        assert_eq!(paths(json, "$[?search(@.name, 'b')]"), vec!["$[0]", "$[1]", "$[2]"]);
+        assert_eq!(paths(json, "$[?match(@.name, '[')]"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_normalized_paths() {
+        let json = r#"{"a'b": {"c\\d": [0, {"\n": 1}]}}"#;
+        assert_eq!(paths(json, "$..*"), vec![
+            "$['a\\'b']",
+            "$['a\\'b']['c\\\\d']",
+            "$['a\\'b']['c\\\\d'][0]",
+            "$['a\\'b']['c\\\\d'][1]",
+            "$['a\\'b']['c\\\\d'][1]['\\n']",
+        ]);
+        let document = parse_json(json).unwrap();
+        let nodes = document.query("$..[?@ == 1]").unwrap();
+        assert_eq!(nodes[0].path.to_pointer(), "/a'b/c\\d/1/\n");
+    }
+
+    #[test]
+    fn test_invalid_queries() {
+        for path in [
+            "", "store", "$.", "$..", "$[", "$[1", "$['a'", "$[01]", "$[-0]", "$[1.0]",
+            "$[9007199254740992]", "$.a b", "$ ", "$[?@.a == ]", "$[?@.* == 1]",
+            "$[?length(@.*) == 1]", "$[?length(@.a)]", "$[?count(1) == 1]", "$[?match(@.a)]",
+            "$[?foo(@.a)]", "$[?@.a == 1 ||]", "$[?(@.a]", "$[?true]", "$[?@ == {}]", "$[?@ === 1]", "$['\\x']",
+        ] {
+            assert!(JsonPath::parse(path).is_err(), "{} should not be valid", path);
+        }
+    }
+}
@@ -0,0 +1,547 @@
+use anyhow::{Result, anyhow};
+use crate::json_value::JsonValue;
+use super::{ComparisonOp, ExprType, Function, FunctionExpr, LogicalExpr, Operand, Query, Segment, Selector};
+
+/// Largest integer that can be represented exactly in I-JSON
+const MAX_INT: i64 = (1 << 53) - 1;
+
+pub(super) fn parse_query(input: &str) -> Result<Query> {
+    let mut parser = Parser::new(input);
+    let query = parser.parse_root()?;
+    Ok(query)
+}
+
+struct Parser {
+    input: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn new(input: &str) -> Self {
+        Parser {
+            input: input.chars().collect(),
+            position: 0,
+        }
+    }
+
+    fn parse_root(&mut self) -> Result<Query> {
+        if !self.consume('$') {
+            return Err(self.error("JSONPath query must start with '$'"));
+        }
+        let segments = self.parse_segments()?;
+        if self.peek().is_some() {
+            return Err(self.error("Unexpected character"));
+        }
+        Ok(Query { relative: false, segments })
+    }
+
+    fn parse_segments(&mut self) -> Result<Vec<Segment>> {
+        let mut segments = Vec::new();
+        loop {
+            let position = self.position;
+            self.skip_blank();
+            match self.peek() {
+                Some('.') | Some('[') => segments.push(self.parse_segment()?),
+                _ => {
+                    self.position = position;
+                    return Ok(segments);
+                }
+            }
+        }
+    }
+
+    fn parse_segment(&mut self) -> Result<Segment> {
+        if self.consume_str("..") {
+            return match self.peek() {
+                Some('[') => Ok(Segment::Descendant(self.parse_bracketed_selection()?)),
+                Some('*') => {
+                    self.advance();
+                    Ok(Segment::Descendant(vec![Selector::Wildcard]))
+                }
+                Some(c) if is_name_first(c) => Ok(Segment::Descendant(vec![Selector::Name(self.parse_member_name())])),
+                _ => Err(self.error("Expected '[', '*' or member name after '..'")),
+            };
+        }
+        if self.consume('.') {
+            return match self.peek() {
+                Some('*') => {
+                    self.advance();
+                    Ok(Segment::Child(vec![Selector::Wildcard]))
+                }
+                Some(c) if is_name_first(c) => Ok(Segment::Child(vec![Selector::Name(self.parse_member_name())])),
+                _ => Err(self.error("Expected '*' or member name after '.'")),
+            };
+        }
+        Ok(Segment::Child(self.parse_bracketed_selection()?))
+    }
+
+    fn parse_member_name(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(c) = self.peek() {
+            if !is_name_first(c) && !c.is_ascii_digit() {
+                break;
+            }
+            name.push(c);
+            self.advance();
+        }
+        name
+    }
+
+    fn parse_bracketed_selection(&mut self) -> Result<Vec<Selector>> {
+        self.expect('[')?;
+        let mut selectors = Vec::new();
+        loop {
+            self.skip_blank();
+            selectors.push(self.parse_selector()?);
+            self.skip_blank();
+            if !self.consume(',') {
+                break;
+            }
+        }
+        self.expect(']')?;
+        Ok(selectors)
+    }
+
+    fn parse_selector(&mut self) -> Result<Selector> {
+        match self.peek() {
+            Some('\'') | Some('"') => Ok(Selector::Name(self.parse_string_literal()?)),
+            Some('*') => {
+                self.advance();
+                Ok(Selector::Wildcard)
+            }
+            Some('?') => {
+                self.advance();
+                self.skip_blank();
+                Ok(Selector::Filter(self.parse_logical_or()?))
+            }
+            Some(c) if c == '-' || c == ':' || c.is_ascii_digit() => self.parse_index_or_slice(),
+            _ => Err(self.error("Expected a selector")),
+        }
+    }
+
+    fn parse_index_or_slice(&mut self) -> Result<Selector> {
+        let start = self.parse_optional_int()?;
+        self.skip_blank();
+        if !self.consume(':') {
+            return start.map(Selector::Index).ok_or_else(|| self.error("Expected an index"));
+        }
+        self.skip_blank();
+        let end = self.parse_optional_int()?;
+        self.skip_blank();
+        let step = if self.consume(':') {
+            self.skip_blank();
+            self.parse_optional_int()?
+        } else {
+            None
+        };
+        Ok(Selector::Slice(start, end, step))
+    }
+
+    fn parse_optional_int(&mut self) -> Result<Option<i64>> {
+        match self.peek() {
+            Some(c) if c == '-' || c.is_ascii_digit() => Ok(Some(self.parse_int()?)),
+            _ => Ok(None),
+        }
+    }
+
+    fn parse_int(&mut self) -> Result<i64> {
+        let start = self.position;
+        let negative = self.consume('-');
+        let digits = self.take_digits();
+        if digits.is_empty() {
+            return Err(self.error("Expected digits"));
+        }
+        if (digits.len() > 1 && digits.starts_with('0')) || (negative && digits == "0") {
+            self.position = start;
+            return Err(self.error("Invalid integer"));
+        }
+        match digits.parse::<i64>() {
+            Ok(value) if value <= MAX_INT => Ok(if negative { -value } else { value }),
+            _ => {
+                self.position = start;
+                Err(self.error("Integer out of range"))
+            }
+        }
+    }
+
+    fn take_digits(&mut self) -> String {
+        let mut digits = String::new();
+        while let Some(c) = self.peek().filter(|c| c.is_ascii_digit()) {
+            digits.push(c);
+            self.advance();
+        }
+        digits
+    }
+
+    fn parse_string_literal(&mut self) -> Result<String> {
+        let quote = self.advance().ok_or_else(|| self.error("Expected a string"))?;
+        let mut literal = String::new();
+        loop {
+            match self.advance() {
+                None => return Err(self.error("Unterminated string literal")),
+                Some(c) if c == quote => return Ok(literal),
+                Some('\\') => literal.push(self.parse_escape(quote)?),
+                Some(c) if c < '\u{0020}' => {
+                    return Err(self.error("Control characters must be escaped in string literals"));
+                }
+                Some(c) => literal.push(c),
+            }
+        }
+    }
+
+    fn parse_escape(&mut self, quote: char) -> Result<char> {
+        match self.advance() {
+            Some(c) if c == quote => Ok(c),
+            Some('b') => Ok('\u{0008}'),
+            Some('f') => Ok('\u{000C}'),
+            Some('n') => Ok('\n'),
+            Some('r') => Ok('\r'),
+            Some('t') => Ok('\t'),
+            Some('/') => Ok('/'),
+            Some('\\') => Ok('\\'),
+            Some('u') => {
+                let high = self.parse_hex4()?;
+                if (0xDC00..0xE000).contains(&high) {
+                    return Err(self.error("Unexpected low surrogate"));
+                }
+                if !(0xD800..0xDC00).contains(&high) {
+                    return char::from_u32(high).ok_or_else(|| self.error("Invalid unicode escape"));
+                }
+                if !self.consume_str("\\u") {
+                    return Err(self.error("Expected low surrogate"));
+                }
+                let low = self.parse_hex4()?;
+                if !(0xDC00..0xE000).contains(&low) {
+                    return Err(self.error("Expected low surrogate"));
+                }
+                char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+                    .ok_or_else(|| self.error("Invalid unicode escape"))
+            }
+            _ => Err(self.error("Invalid escape sequence")),
+        }
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self.advance().and_then(|c| c.to_digit(16));
+            code = code * 16 + digit.ok_or_else(|| self.error("Expected four hex digits"))?;
+        }
+        Ok(code)
+    }
+
+    fn parse_logical_or(&mut self) -> Result<LogicalExpr> {
+        let mut exprs = vec![self.parse_logical_and()?];
+        loop {
+            self.skip_blank();
+            if !self.consume_str("||") {
+                break;
+            }
+            self.skip_blank();
+            exprs.push(self.parse_logical_and()?);
+        }
+        Ok(if exprs.len() == 1 { exprs.pop().unwrap() } else { LogicalExpr::Or(exprs) })
+    }
+
+    fn parse_logical_and(&mut self) -> Result<LogicalExpr> {
+        let mut exprs = vec![self.parse_basic_expr()?];
+        loop {
+            self.skip_blank();
+            if !self.consume_str("&&") {
+                break;
+            }
+            self.skip_blank();
+            exprs.push(self.parse_basic_expr()?);
+        }
+        Ok(if exprs.len() == 1 { exprs.pop().unwrap() } else { LogicalExpr::And(exprs) })
+    }
+
+    fn parse_basic_expr(&mut self) -> Result<LogicalExpr> {
+        if self.consume('!') {
+            self.skip_blank();
+            let expr = if self.peek() == Some('(') {
+                self.parse_paren_expr()?
+            } else {
+                let start = self.position;
+                let operand = self.parse_operand()?;
+                self.check_test_expr(&operand, start)?;
+                LogicalExpr::Test(operand)
+            };
+            return Ok(LogicalExpr::Not(Box::new(expr)));
+        }
+        if self.peek() == Some('(') {
+            return self.parse_paren_expr();
+        }
+
+        let start = self.position;
+        let left = self.parse_operand()?;
+        let position = self.position;
+        self.skip_blank();
+        let Some(op) = self.parse_comparison_op() else {
+            self.position = position;
+            self.check_test_expr(&left, start)?;
+            return Ok(LogicalExpr::Test(left));
+        };
+        self.check_comparable(&left, start)?;
+        self.skip_blank();
+        let right_start = self.position;
+        let right = self.parse_operand()?;
+        self.check_comparable(&right, right_start)?;
+        Ok(LogicalExpr::Comparison(left, op, right))
+    }
+
+    fn parse_paren_expr(&mut self) -> Result<LogicalExpr> {
+        self.expect('(')?;
+        self.skip_blank();
+        let expr = self.parse_logical_or()?;
+        self.skip_blank();
+        self.expect(')')?;
+        Ok(expr)
+    }
+
+    fn parse_comparison_op(&mut self) -> Option<ComparisonOp> {
+        let ops = [
+            ("==", ComparisonOp::Eq),
+            ("!=", ComparisonOp::Ne),
+            ("<=", ComparisonOp::Le),
+            (">=", ComparisonOp::Ge),
+            ("<", ComparisonOp::Lt),
+            (">", ComparisonOp::Gt),
+        ];
+        ops.into_iter().find(|(text, _)| self.consume_str(text)).map(|(_, op)| op)
+    }
+
+    /// Parses a literal, a filter query or a function expression.
+    fn parse_operand(&mut self) -> Result<Operand> {
+        match self.peek() {
+            Some('@') | Some('$') => {
+                let relative = self.advance() == Some('@');
+                let segments = self.parse_segments()?;
+                Ok(Operand::Query(Query { relative, segments }))
+            }
+            Some('\'') | Some('"') => Ok(Operand::Literal(JsonValue::String(self.parse_string_literal()?))),
+            Some(c) if c == '-' || c.is_ascii_digit() => Ok(Operand::Literal(self.parse_number()?)),
+            Some(c) if c.is_ascii_lowercase() => self.parse_name_operand(),
+            _ => Err(self.error("Expected a literal, query or function")),
+        }
+    }
+
+    fn parse_name_operand(&mut self) -> Result<Operand> {
+        let start = self.position;
+        let mut name = String::new();
+        while let Some(c) = self.peek() {
+            if !(c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+                break;
+            }
+            name.push(c);
+            self.advance();
+        }
+        match name.as_str() {
+            "true" => return Ok(Operand::Literal(JsonValue::Bool(true))),
+            "false" => return Ok(Operand::Literal(JsonValue::Bool(false))),
+            "null" => return Ok(Operand::Literal(JsonValue::Null)),
+            _ => (),
+        }
+        let Some(function) = Function::from_name(&name) else {
+            self.position = start;
+            return Err(self.error(&format!("Unknown function '{}'", name)));
+        };
+        self.expect('(')?;
+        let mut arguments = Vec::new();
+        self.skip_blank();
+        if self.peek() != Some(')') {
+            loop {
+                self.skip_blank();
+                let argument_start = self.position;
+                let argument = self.parse_function_argument()?;
+                arguments.push((argument, argument_start));
+                self.skip_blank();
+                if !self.consume(',') {
+                    break;
+                }
+            }
+        }
+        let end = self.position;
+        self.expect(')')?;
+
+        let parameter_types = function.parameter_types();
+        if arguments.len() != parameter_types.len() {
+            self.position = end;
+            return Err(self.error(&format!(
+                "Function {}() expects {} argument(s), found {}",
+                function.name(),
+                parameter_types.len(),
+                arguments.len()
+            )));
+        }
+        for ((argument, argument_start), parameter_type) in arguments.iter().zip(parameter_types) {
+            self.check_argument(function, argument, *parameter_type, *argument_start)?;
+        }
+
+        let arguments = arguments.into_iter().map(|(argument, _)| argument).collect();
+        Ok(Operand::Function(FunctionExpr { function, arguments }))
+    }
+
+    fn parse_function_argument(&mut self) -> Result<Operand> {
+        let start = self.position;
+        if let Ok(operand) = self.parse_operand() {
+            let position = self.position;
+            self.skip_blank();
+            if matches!(self.peek(), Some(',') | Some(')')) {
+                self.position = position;
+                return Ok(operand);
+            }
+        }
+        self.position = start;
+        Ok(Operand::Logical(Box::new(self.parse_logical_or()?)))
+    }
+
+    fn parse_number(&mut self) -> Result<JsonValue> {
+        let start = self.position;
+        let mut literal = String::new();
+        if self.consume('-') {
+            literal.push('-');
+        }
+        let int = self.take_digits();
+        if int.is_empty() || (int.len() > 1 && int.starts_with('0')) {
+            self.position = start;
+            return Err(self.error("Invalid number"));
+        }
+        literal.push_str(&int);
+        if self.consume('.') {
+            let frac = self.take_digits();
+            if frac.is_empty() {
+                return Err(self.error("Expected digits after decimal point"));
+            }
+            literal.push('.');
+            literal.push_str(&frac);
+        }
+        if let Some(e) = self.peek().filter(|c| *c == 'e' || *c == 'E') {
+            self.advance();
+            literal.push(e);
+            if let Some(sign) = self.peek().filter(|c| *c == '+' || *c == '-') {
+                self.advance();
+                literal.push(sign);
+            }
+            let exp = self.take_digits();
+            if exp.is_empty() {
+                return Err(self.error("Expected digits in exponent"));
+            }
+            literal.push_str(&exp);
+        }
+        literal
+            .parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite())
+            .map(JsonValue::Number)
+            .ok_or_else(|| {
+                self.position = start;
+                self.error("Invalid number")
+            })
+    }
+
+    /// Operands of comparisons must produce a single value.
+    fn check_comparable(&mut self, operand: &Operand, start: usize) -> Result<()> {
+        let valid = match operand {
+            Operand::Literal(_) => true,
+            Operand::Query(query) => query.is_singular(),
+            Operand::Function(function_expr) => function_expr.function.result_type() == ExprType::Value,
+            Operand::Logical(_) => false,
+        };
+        if valid {
+            return Ok(());
+        }
+        self.position = start;
+        Err(self.error("Only literals, singular queries and value functions can be compared"))
+    }
+
+    /// Test expressions must be queries or functions not producing a plain value.
+    fn check_test_expr(&mut self, operand: &Operand, start: usize) -> Result<()> {
+        let valid = match operand {
+            Operand::Query(_) => true,
+            Operand::Function(function_expr) => function_expr.function.result_type() != ExprType::Value,
+            _ => false,
+        };
+        if valid {
+            return Ok(());
+        }
+        self.position = start;
+        Err(self.error("Expected a query, a logical function or a comparison"))
+    }
+
+    fn check_argument(&mut self, function: Function, argument: &Operand, parameter_type: ExprType, start: usize) -> Result<()> {
+        let valid = match (parameter_type, argument) {
+            (ExprType::Value, Operand::Literal(_)) => true,
+            (ExprType::Value, Operand::Query(query)) => query.is_singular(),
+            (ExprType::Value, Operand::Function(f)) => f.function.result_type() == ExprType::Value,
+            (ExprType::Nodes, Operand::Query(_)) => true,
+            (ExprType::Logical, Operand::Logical(_) | Operand::Query(_)) => true,
+            (ExprType::Logical, Operand::Function(f)) => f.function.result_type() != ExprType::Value,
+            _ => false,
+        };
+        if valid {
+            return Ok(());
+        }
+        let expected = match parameter_type {
+            ExprType::Value => "a value",
+            ExprType::Logical => "a logical expression",
+            ExprType::Nodes => "a query",
+        };
+        self.position = start;
+        Err(self.error(&format!("Argument of function {}() must be {}", function.name(), expected)))
+    }
+
+    fn error(&self, message: &str) -> anyhow::Error {
+        match self.peek() {
+            Some(c) => anyhow!("Invalid JSONPath at position {} ('{}'): {}", self.position, c, message),
+            None => anyhow!("Invalid JSONPath at end of input: {}", message),
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        if self.consume(expected) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{}'", expected)))
+        }
+    }
+
+    fn skip_blank(&mut self) {
+        while matches!(self.peek(), Some(' ') | Some('\t') | Some('\n') | Some('\r')) {
+            self.advance();
+        }
+    }
+
+    fn consume(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn consume_str(&mut self, expected: &str) -> bool {
+        let len = expected.chars().count();
+        let matches = self.input.len() >= self.position + len
+            && self.input[self.position..self.position + len].iter().copied().eq(expected.chars());
+        if matches {
+            self.position += len;
+        }
+        matches
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input.get(self.position).copied()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+        Some(c)
+    }
+}
+
+fn is_name_first(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || !c.is_ascii()
+}
@@ -1,6 +1,7 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::{Index, IndexMut};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum JsonValue {
     Null,
     Bool(bool),
@@ -8,4 +9,347 @@ pub enum JsonValue {
     String(String),
     Array(Vec<JsonValue>),
     Object(Vec<String>, HashMap<String, JsonValue>),
-}
\ No newline at end of file
+}
+
+/// Result of indexing a missing member or element
+static NULL: JsonValue = JsonValue::Null;
+
+impl JsonValue {
+    pub fn is_null(&self) -> bool {
+        matches!(self, JsonValue::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonValue::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<JsonValue>> {
+        match self {
+            JsonValue::Array(elements) => Some(elements),
+            _ => None,
+        }
+    }
+
+    /// The member names in order and the members of an object
+    pub fn as_object(&self) -> Option<(&[String], &HashMap<String, JsonValue>)> {
+        match self {
+            JsonValue::Object(names, members) => Some((names, members)),
+            _ => None,
+        }
+    }
+
+    /// The member of an object with the given name
+    pub fn get(&self, name: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(_, members) => members.get(name),
+            _ => None,
+        }
+    }
+
+    /// The element of an array at the given index
+    pub fn get_index(&self, index: usize) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Array(elements) => elements.get(index),
+            _ => None,
+        }
+    }
+
+    /// Replaces the value with null and returns the previous value
+    pub fn take(&mut self) -> JsonValue {
+        std::mem::replace(self, JsonValue::Null)
+    }
+
+    /// Number of elements of an array or members of an object; other values have none
+    pub fn len(&self) -> usize {
+        match self {
+            JsonValue::Array(elements) => elements.len(),
+            JsonValue::Object(names, _) => names.len(),
+            _ => 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Kind of the value with an article, e.g. `an array`
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            JsonValue::Null => "null",
+            JsonValue::Bool(_) => "a boolean",
+            JsonValue::Number(_) => "a number",
+            JsonValue::String(_) => "a string",
+            JsonValue::Array(_) => "an array",
+            JsonValue::Object(_, _) => "an object",
+        }
+    }
+}
+
+/// Missing members and values other than objects index to null
+impl Index<&str> for JsonValue {
+    type Output = JsonValue;
+
+    fn index(&self, name: &str) -> &JsonValue {
+        self.get(name).unwrap_or(&NULL)
+    }
+}
+
+/// Missing elements and values other than arrays index to null
+impl Index<usize> for JsonValue {
+    type Output = JsonValue;
+
+    fn index(&self, index: usize) -> &JsonValue {
+        self.get_index(index).unwrap_or(&NULL)
+    }
+}
+
+/// Inserts a null member if the object has none with the name; null turns into an empty
+/// object first. Panics for other values.
+impl IndexMut<&str> for JsonValue {
+    fn index_mut(&mut self, name: &str) -> &mut JsonValue {
+        if self.is_null() {
+            *self = JsonValue::Object(Vec::new(), HashMap::new());
+        }
+        match self {
+            JsonValue::Object(names, members) => members.entry(name.to_string()).or_insert_with(|| {
+                names.push(name.to_string());
+                JsonValue::Null
+            }),
+            _ => panic!("cannot index into {} with the name {:?}", self.kind(), name),
+        }
+    }
+}
+
+/// Pads the array with nulls up to the index; null turns into an empty array first. Panics
+/// for other values.
+impl IndexMut<usize> for JsonValue {
+    fn index_mut(&mut self, index: usize) -> &mut JsonValue {
+        if self.is_null() {
+            *self = JsonValue::Array(Vec::new());
+        }
+        match self {
+            JsonValue::Array(elements) => {
+                if index >= elements.len() {
+                    elements.resize(index + 1, JsonValue::Null);
+                }
+                &mut elements[index]
+            }
+            _ => panic!("cannot index into {} with the index {}", self.kind(), index),
+        }
+    }
+}
+
+/// Takes the nulls that `IndexMut<usize>` would pad the value with from the budget, which
+/// bounds the size of arrays built from untrusted indices. Returns false if the budget does
+/// not cover them.
+pub(crate) fn take_padding(value: &JsonValue, index: usize, budget: &mut usize) -> bool {
+    let padding = index.saturating_sub(value.as_array().map_or(0, Vec::len));
+    if padding > *budget {
+        return false;
+    }
+    *budget -= padding;
+    true
+}
+
+/// Deep equality that ignores the order of object members
+pub(crate) fn json_equal(left: &JsonValue, right: &JsonValue) -> bool {
+    match (left, right) {
+        (JsonValue::Array(left), JsonValue::Array(right)) => {
+            left.len() == right.len() && left.iter().zip(right).all(|(l, r)| json_equal(l, r))
+        }
+        (JsonValue::Object(_, left), JsonValue::Object(_, right)) => {
+            left.len() == right.len()
+                && left.iter().all(|(name, l)| right.get(name).is_some_and(|r| json_equal(l, r)))
+        }
+        _ => left == right,
+    }
+}
+
+/// Builds an object from its members; a repeated name replaces the earlier value.
+pub(crate) fn object_from_entries(entries: Vec<(String, JsonValue)>) -> JsonValue {
+    let mut names = Vec::new();
+    let mut members = HashMap::new();
+    for (name, value) in entries {
+        if members.insert(name.clone(), value).is_none() {
+            names.push(name);
+        }
+    }
+    JsonValue::Object(names, members)
+}
+
+impl From<bool> for JsonValue {
+    fn from(b: bool) -> Self {
+        JsonValue::Bool(b)
+    }
+}
+
+macro_rules! from_numbers {
+    ($($type:ty),*) => {
+        $(
+            /// Non-finite numbers have no JSON representation and become null
+            impl From<$type> for JsonValue {
+                #[allow(clippy::unnecessary_cast)]
+                fn from(n: $type) -> Self {
+                    let n = n as f64;
+                    if n.is_finite() { JsonValue::Number(n) } else { JsonValue::Null }
+                }
+            }
+        )*
+    };
+}
+
+from_numbers!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+impl From<String> for JsonValue {
+    fn from(s: String) -> Self {
+        JsonValue::String(s)
+    }
+}
+
+impl From<&String> for JsonValue {
+    fn from(s: &String) -> Self {
+        JsonValue::String(s.clone())
+    }
+}
+
+impl From<char> for JsonValue {
+    fn from(c: char) -> Self {
+        JsonValue::String(c.to_string())
+    }
+}
+
+impl From<&str> for JsonValue {
+    fn from(s: &str) -> Self {
+        JsonValue::String(s.to_string())
+    }
+}
+
+impl From<()> for JsonValue {
+    fn from(_: ()) -> Self {
+        JsonValue::Null
+    }
+}
+
+impl<T: Into<JsonValue>> From<Option<T>> for JsonValue {
+    fn from(option: Option<T>) -> Self {
+        option.map_or(JsonValue::Null, Into::into)
+    }
+}
+
+impl<T: Into<JsonValue>> From<Vec<T>> for JsonValue {
+    fn from(elements: Vec<T>) -> Self {
+        JsonValue::Array(elements.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Clone + Into<JsonValue>> From<&[T]> for JsonValue {
+    fn from(elements: &[T]) -> Self {
+        elements.iter().cloned().collect()
+    }
+}
+
+/// Members are sorted by name since hash maps have no order
+impl<T: Into<JsonValue>, S> From<HashMap<String, T, S>> for JsonValue {
+    fn from(map: HashMap<String, T, S>) -> Self {
+        let mut entries: Vec<(String, T)> = map.into_iter().collect();
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        entries.into_iter().collect()
+    }
+}
+
+impl<T: Into<JsonValue>> From<BTreeMap<String, T>> for JsonValue {
+    fn from(map: BTreeMap<String, T>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl<T: Into<JsonValue>> FromIterator<T> for JsonValue {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        JsonValue::Array(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Builds an object; a repeated name replaces the earlier value
+impl<T: Into<JsonValue>> FromIterator<(String, T)> for JsonValue {
+    fn from_iter<I: IntoIterator<Item = (String, T)>>(iter: I) -> Self {
+        object_from_entries(iter.into_iter().map(|(name, value)| (name, value.into())).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    #[test]
+    fn test_accessors() {
+        let value = parse_json(r#"{"name": "Ann", "age": 42, "admin": false, "tags": ["a", null], "address": {}}"#).unwrap();
+        assert_eq!(value["name"].as_str(), Some("Ann"));
+        assert_eq!(value["age"].as_f64(), Some(42.0));
+        assert_eq!(value["admin"].as_bool(), Some(false));
+        assert_eq!(value["tags"].as_array().map(Vec::len), Some(2));
+        assert_eq!(value["address"].as_object().map(|(names, _)| names.len()), Some(0));
+        assert_eq!(value.as_object().unwrap().0[1], "age");
+        assert_eq!(value.get("tags").and_then(|tags| tags.get_index(0)), Some(&JsonValue::from("a")));
+        assert!(value["tags"][1].is_null());
+        assert!(value["missing"]["deeper"][3].is_null());
+        assert!(value["name"][0].is_null());
+        assert_eq!(value["name"].as_f64(), None);
+        assert_eq!(value.get("missing"), None);
+        assert_eq!(value.len(), 5);
+        assert!(value["address"].is_empty());
+        assert_eq!(value["name"].len(), 0);
+    }
+
+    #[test]
+    fn test_index_mut() {
+        let mut value = JsonValue::Null;
+        value["address"]["city"] = JsonValue::from("Berlin");
+        value["tags"][2] = JsonValue::from(true);
+        value["address"]["zip"] = JsonValue::from(10115);
+        value["address"]["city"] = JsonValue::from("Bonn");
+        assert_eq!(value, parse_json(r#"{"address": {"city": "Bonn", "zip": 10115}, "tags": [null, null, true]}"#).unwrap());
+
+        let city = value["address"]["city"].take();
+        assert_eq!(city, JsonValue::from("Bonn"));
+        assert!(value["address"]["city"].is_null());
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot index into a string with the name \"a\"")]
+    fn test_index_mut_panics() {
+        let mut value = JsonValue::from("text");
+        value["a"] = JsonValue::Null;
+    }
+
+    #[test]
+    fn test_from() {
+        assert_eq!(JsonValue::from(vec![Some(1), None]), parse_json("[1, null]").unwrap());
+        assert_eq!(JsonValue::from(['a', 'b'].as_slice()), parse_json(r#"["a", "b"]"#).unwrap());
+        assert_eq!(JsonValue::from(&"s".to_string()), JsonValue::String("s".to_string()));
+        let map = HashMap::from([("b".to_string(), 2u8), ("a".to_string(), 1)]);
+        assert_eq!(JsonValue::from(map), parse_json(r#"{"a": 1, "b": 2}"#).unwrap());
+        let map = BTreeMap::from([("y".to_string(), vec![1.5]), ("x".to_string(), vec![])]);
+        assert_eq!(JsonValue::from(map), parse_json(r#"{"x": [], "y": [1.5]}"#).unwrap());
+        assert_eq!((1..4).collect::<JsonValue>(), parse_json("[1, 2, 3]").unwrap());
+        let object: JsonValue = [("k".to_string(), "v")].into_iter().collect();
+        assert_eq!(object, parse_json(r#"{"k": "v"}"#).unwrap());
+    }
+}
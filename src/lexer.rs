@@ -30,6 +30,7 @@ pub struct Lexer {
     position: usize,
     line: usize,
     column: usize,
+    error: Option<String>,
 }
 
 impl Lexer {
@@ -39,9 +40,15 @@ impl Lexer {
             position: 0,
             line: 1,
             column: 1,
+            error: None,
         }
     }
 
+    /// Why `next_token` returned `None` when it was not for lack of input or a malformed token
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
     pub fn next_token(&mut self) -> Option<Token> {
         self.skip_whitespace();
 
@@ -91,31 +98,62 @@ impl Lexer {
 
     fn scan_string(&mut self, line: usize, column: usize) -> Option<Token> {
         let mut lexeme = String::new();
-        let mut prev_char = '\0';
 
         while let Some(char) = self.advance() {
-            if char == '"' {
-                if prev_char == '\\' {
-                    lexeme.pop(); // Remove the escape character
-                    lexeme.push(char); // Add the quote
-                    prev_char = char;
-                    continue;
-                }
-                return Some(Token::new(TokenValue::StringLiteral(lexeme), line, column));
-            } else {
-                lexeme.push(char);
+            match char {
+                '"' => return Some(Token::new(TokenValue::StringLiteral(lexeme), line, column)),
+                '\\' => lexeme.push(self.scan_escape()?),
+                _ => lexeme.push(char),
             }
-            prev_char = char;
         }
         // If we reach here, the string was not closed
         None
     }
 
+    fn scan_escape(&mut self) -> Option<char> {
+        match self.advance()? {
+            '"' => Some('"'),
+            '\\' => Some('\\'),
+            '/' => Some('/'),
+            'b' => Some('\u{0008}'),
+            'f' => Some('\u{000C}'),
+            'n' => Some('\n'),
+            'r' => Some('\r'),
+            't' => Some('\t'),
+            'u' => {
+                let high = self.scan_hex4()?;
+                if (0xD800..0xDC00).contains(&high) {
+                    // High surrogate: must be followed by an escaped low surrogate
+                    if self.advance()? != '\\' || self.advance()? != 'u' {
+                        return None;
+                    }
+                    let low = self.scan_hex4()?;
+                    if !(0xDC00..0xE000).contains(&low) {
+                        return None;
+                    }
+                    char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+                } else {
+                    char::from_u32(high)
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn scan_hex4(&mut self) -> Option<u32> {
+        let mut code = 0;
+        for _ in 0..4 {
+            code = code * 16 + self.advance()?.to_digit(16)?;
+        }
+        Some(code)
+    }
+
     fn scan_chars(&mut self, first: char, line: usize, column: usize) -> Option<Token> {
         let mut lexeme = String::new();
         lexeme.push(first);
         while let Some(char) = self.peek_char() {
-            if char.is_alphanumeric() || char == '.' || char == '-' {
+            // A plus sign can only start an exponent, as in 1e+21
+            if char.is_alphanumeric() || char == '.' || char == '-' || char == '+' && lexeme.ends_with(['e', 'E']) {
                 lexeme.push(char);
                 self.advance();
             } else {
@@ -131,11 +169,18 @@ impl Lexer {
             return Some(Token::new(TokenValue::Null, line, column));
         }
 
-        if let Ok(number) = lexeme.parse::<f64>() {
-            return Some(Token::new(TokenValue::Number(number), line, column));
+        // Rust also parses words like "NaN" and "inf", which are not JSON
+        if !lexeme.chars().all(|c| c.is_ascii_digit() || "+-.eE".contains(c)) {
+            return None;
+        }
+        match lexeme.parse::<f64>() {
+            Ok(number) if number.is_finite() => Some(Token::new(TokenValue::Number(number), line, column)),
+            Ok(_) => {
+                self.error = Some(format!("Number out of range at line {}, column {}: {}", line, column, lexeme));
+                None
+            }
+            Err(_) => None,
         }
-
-        None
     }
 }
 
@@ -208,4 +253,30 @@ mod tests {
         assert_eq!(tokens.len(), 29); // Just check that we got some tokens
     }
 
+    #[test]
+    fn test_lexer_json_string_escapes() {
+        let mut lexer = Lexer::new(r#""a\"b\\c\/\n\t\u20ac\ud83d\ude00""#);
+        let token = lexer.next_token().unwrap();
+        match token.value {
+            TokenValue::StringLiteral(s) => assert_eq!(s, "a\"b\\c/\n\t\u{20ac}\u{1f600}"),
+            _ => panic!("Expected StringLiteral token"),
+        }
+    }
+
+    #[test]
+    fn test_lexer_json_invalid_escapes() {
+        assert!(Lexer::new(r#""\x""#).next_token().is_none());
+        assert!(Lexer::new(r#""\u12""#).next_token().is_none());
+        assert!(Lexer::new(r#""\ud83d""#).next_token().is_none());
+    }
+
+    #[test]
+    fn test_lexer_json_non_finite_number() {
+        assert!(Lexer::new("NaN").next_token().is_none());
+        assert!(Lexer::new("-Infinity").next_token().is_none());
+        let mut lexer = Lexer::new("[\n  -1e999]");
+        lexer.next_token();
+        assert!(lexer.next_token().is_none());
+        assert_eq!(lexer.error(), Some("Number out of range at line 2, column 3: -1e999"));
+    }
 }
\ No newline at end of file
@@ -1,8 +1,53 @@
+// Lets the implementations derived in tests refer to the crate as `::medea`
+extern crate self as medea;
+
+mod canonical;
+mod codegen;
+mod convert;
+mod diff;
+mod filter;
+mod flatten;
+mod format_options;
+mod gron;
 mod lexer;
+mod json_macro;
+mod json_path;
 mod json_value;
+mod merge_patch;
 mod parser;
+mod patch;
+mod pointer;
 mod pretty_printer;
+pub mod schema;
+#[cfg(feature = "serde")]
+mod serialization;
+mod shape;
+mod theme;
+mod visit;
+mod yaml;
 
+pub use codegen::{generate_rust, generate_typescript, CodegenOptions};
+pub use convert::{ConversionError, FromJson, ToJson};
+#[doc(hidden)]
+pub use convert::__private;
+#[cfg(feature = "derive")]
+pub use medea_derive::{FromJson, ToJson};
+pub use diff::{diff, DiffEntry, DiffKind, DiffOptions};
+pub use filter::Filter;
+pub use flatten::{flatten, unflatten, ArrayNotation, FlattenOptions};
+pub use format_options::{FormatOptions, Indent, LineEnding};
+pub use gron::parse_gron;
+pub use json_path::{JsonPath, Node, NormalizedPath, PathElement};
 pub use json_value::JsonValue;
+pub use merge_patch::merge_patch_diff;
 pub use parser::parse_json;
-pub use pretty_printer::pretty_print_json;
\ No newline at end of file
+pub use patch::{JsonPatch, PatchOperation};
+pub use pointer::{escape_token, parse_pointer, to_pointer};
+#[cfg(feature = "serde")]
+pub use serialization::{from_str, from_value, to_string, to_string_pretty, to_value, SerdeError};
+pub use theme::{default_theme_file, ColorCategory, ColorChoice, Style, Theme, THEME_ENV_VAR};
+pub use visit::{Control, Visitor, VisitorMut, Walk};
+pub use yaml::{parse_yaml, YamlOptions};
+pub use pretty_printer::{
+    pretty_print_json, escape_string, escape_string_ascii, format_number, natural_cmp, KeyComparator, KeyOrder, PrettyPrinter,
+};
\ No newline at end of file
@@ -1,6 +1,11 @@
-use std::io::Read;
-use clap::Parser;
-use medea;
+use std::io::{IsTerminal, Read};
+use anyhow::Context;
+use std::process::ExitCode;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use colored::Colorize;
+use std::path::PathBuf;
+use medea::schema::{InferOptions, Schema};
+use medea::{ArrayNotation, CodegenOptions, ColorChoice, DiffKind, DiffOptions, Filter, FlattenOptions, FormatOptions, Indent, JsonPatch, JsonPath, JsonValue, KeyOrder, LineEnding, PrettyPrinter, Theme, YamlOptions};
 
 #[derive(Debug, Parser)]
 #[command(name = "medea")]
@@ -8,40 +13,743 @@ use medea;
 #[command(about = "a simple pretty-printer for JSON data", long_about = None)]
 #[command(help_template = "{name} - {about} [version: {version}, author: {author}]\n\n{usage-heading} {usage}\n\n{all-args}")]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Input JSON file (optional, defaults to stdin)
     #[arg(default_value = "")]
     json_file: String,
 
+    /// Only print the sub-document the JSON pointer (RFC 6901) refers to, e.g. /address/city
+    #[arg(short, long, value_name = "POINTER")]
+    pointer: Option<String>,
+
+    /// Output the canonical form according to RFC 8785 (JCS)
+    #[arg(long, default_value_t = false, conflicts_with_all = [
+        "indent", "tabs", "compact", "space_before_colon", "space_in_brackets", "crlf", "no_final_newline", "ascii",
+        "color", "use_colors", "theme", "theme_file", "sort_keys", "max_width",
+    ])]
+    canonical: bool,
+
+    /// Print one greppable assignment per value, e.g. json.address.city = "Anytown";
+    #[arg(long, default_value_t = false, conflicts_with = "canonical")]
+    gron: bool,
+
+    /// Read the assignments printed by --gron instead of JSON
+    #[arg(long, default_value_t = false)]
+    ungron: bool,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Select nodes with a JSONPath query (RFC 9535)
+    Query {
+        /// JSONPath query, e.g. '$.store.book[?@.price < 10].title'
+        path: String,
+
+        /// Input JSON file (optional, defaults to stdin)
+        #[arg(default_value = "")]
+        json_file: String,
+
+        /// Print an object mapping the normalized path of each match to its value
+        #[arg(long, default_value_t = false)]
+        with_paths: bool,
+    },
+    /// Transform JSON with a jq-style filter expression
+    Filter {
+        /// Filter expression, e.g. '.items | map(select(.price < 10)) | length'
+        expr: String,
+
+        /// Input JSON file (optional, defaults to stdin)
+        #[arg(default_value = "")]
+        json_file: String,
+    },
+    /// Apply a JSON Patch (RFC 6902) to a document
+    Patch {
+        /// JSON document to patch
+        json_file: String,
+
+        /// JSON Patch document, an array of operations
+        patch_file: String,
+    },
+    /// Apply JSON merge patches (RFC 7386) to a document, in the given order
+    Merge {
+        /// JSON document to start from
+        base_file: String,
+
+        /// JSON merge patches, e.g. configuration overrides
+        #[arg(required = true)]
+        patch_files: Vec<String>,
+    },
+    /// Validate a JSON document against a JSON Schema (draft 2020-12)
+    Validate {
+        /// JSON Schema file
+        #[arg(long)]
+        schema: String,
+
+        /// Input JSON file (optional, defaults to stdin)
+        #[arg(default_value = "")]
+        json_file: String,
+    },
+    /// Infer a JSON Schema (draft 2020-12) from sample documents
+    InferSchema {
+        /// Sample JSON files
+        #[arg(required = true)]
+        sample_files: Vec<String>,
+
+        /// Turn repeating strings with at most this many distinct values into enums (0 disables enums)
+        #[arg(long, value_name = "N", default_value_t = 5)]
+        max_enum: usize,
+    },
+    /// Generate type definitions from sample documents
+    Codegen {
+        /// Target language
+        #[arg(long, value_enum)]
+        lang: Language,
+
+        /// Name of the type of the whole document
+        #[arg(long, default_value = "Root")]
+        name: String,
+
+        /// Sample JSON files
+        #[arg(required = true)]
+        sample_files: Vec<String>,
+    },
+    /// Compare two JSON documents structurally
+    Diff {
+        /// Output format of the differences
+        #[arg(long, value_enum, default_value_t = DiffFormat::Text)]
+        format: DiffFormat,
+
+        /// Do not report objects whose members only differ in order (text format only)
+        #[arg(long, default_value_t = false)]
+        ignore_key_order: bool,
+
+        /// Compare arrays regardless of the order of their elements (text format only)
+        #[arg(long, default_value_t = false)]
+        ignore_array_order: bool,
+
+        /// Consider numbers differing by at most this amount equal (text format only)
+        #[arg(long, value_name = "TOLERANCE", default_value_t = 0.0)]
+        float_tolerance: f64,
+
+        /// Original JSON document
+        from_file: String,
+
+        /// Changed JSON document
+        to_file: String,
+    },
+    /// Turn nested arrays and objects into an object mapping paths to leaf values
+    Flatten {
+        #[command(flatten)]
+        key_args: KeyArgs,
+
+        /// Input JSON file (optional, defaults to stdin)
+        #[arg(default_value = "")]
+        json_file: String,
+    },
+    /// Rebuild nested arrays and objects from the output of flatten
+    Unflatten {
+        #[command(flatten)]
+        key_args: KeyArgs,
+
+        /// Input JSON file (optional, defaults to stdin)
+        #[arg(default_value = "")]
+        json_file: String,
+    },
+    /// Convert between JSON and YAML
+    Convert {
+        /// Format of the input; YAML input is limited to the subset that maps to JSON, without
+        /// anchors, aliases, tags and multiple documents
+        #[arg(long, value_enum, default_value_t = DataFormat::Json)]
+        from: DataFormat,
+
+        /// Format of the output; .inf and .nan become null in JSON
+        #[arg(long, value_enum, default_value_t = DataFormat::Json)]
+        to: DataFormat,
+
+        /// Input file (optional, defaults to stdin)
+        #[arg(default_value = "")]
+        input_file: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum DataFormat {
+    Json,
+    /// YAML 1.2
+    Yaml,
+}
+
+/// Options controlling the keys of flattened documents
+#[derive(Debug, Args)]
+struct KeyArgs {
+    /// Separates member names in keys
+    #[arg(long, default_value = ".")]
+    separator: String,
+
+    /// How array indices appear in keys
+    #[arg(long, value_enum, default_value_t = KeyArrayNotation::Brackets)]
+    array_notation: KeyArrayNotation,
+}
+
+impl KeyArgs {
+    fn flatten_options(&self) -> FlattenOptions {
+        FlattenOptions {
+            separator: self.separator.clone(),
+            array_notation: match self.array_notation {
+                KeyArrayNotation::Brackets => ArrayNotation::Brackets,
+                KeyArrayNotation::Separator => ArrayNotation::Separator,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum KeyArrayNotation {
+    /// skills[0].name
+    Brackets,
+    /// skills.0.name, using the separator
+    Separator,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Language {
+    /// Structs with serde derives
+    Rust,
+    /// Interfaces and type aliases
+    #[value(name = "ts", alias = "typescript")]
+    TypeScript,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum DiffFormat {
+    /// Added (+), removed (-) and changed (~) paths, one per line
+    Text,
+    /// JSON Patch (RFC 6902) turning the first document into the second
+    Patch,
+    /// JSON merge patch (RFC 7386) turning the first document into the second
+    MergePatch,
+}
+
+/// Options controlling how JSON is printed
+#[derive(Debug, Args)]
+struct OutputArgs {
     /// Indent size for pretty-printing
-    #[arg(short, long, default_value_t = 4)]
+    #[arg(short, long, global = true, default_value_t = 4)]
     indent: usize,
 
-    /// Use colors in output
-    #[arg(short='c', long, default_value_t = false)]
+    /// Indent with tabs instead of spaces
+    #[arg(long, global = true, default_value_t = false, conflicts_with = "indent")]
+    tabs: bool,
+
+    /// Print the whole document on a single line without whitespace
+    #[arg(long, global = true, default_value_t = false)]
+    compact: bool,
+
+    /// Put a space between object keys and colons
+    #[arg(long, global = true, default_value_t = false)]
+    space_before_colon: bool,
+
+    /// Put spaces inside brackets and braces of single line arrays and objects
+    #[arg(long, global = true, default_value_t = false)]
+    space_in_brackets: bool,
+
+    /// Put a space after commas in compact mode
+    #[arg(long, global = true, default_value_t = false, requires = "compact")]
+    space_after_comma: bool,
+
+    /// Use CRLF line endings instead of LF
+    #[arg(long, global = true, default_value_t = false)]
+    crlf: bool,
+
+    /// Do not terminate the output with a line ending
+    #[arg(long, global = true, default_value_t = false)]
+    no_final_newline: bool,
+
+    /// Escape all non-ASCII characters as \uXXXX
+    #[arg(long, global = true, default_value_t = false)]
+    ascii: bool,
+
+    /// When to use colors in output and error messages
+    #[arg(long, global = true, value_enum, value_name = "WHEN", default_value_t = ColorWhen::Auto)]
+    color: ColorWhen,
+
+    /// Use colors in output (same as --color=always)
+    #[arg(short='c', long, global = true, default_value_t = false, conflicts_with = "color")]
     use_colors: bool,
+
+    /// Built-in color theme
+    #[arg(long, global = true, value_enum, default_value_t = ThemeName::Default)]
+    theme: ThemeName,
+
+    /// Theme file with `category=style` lines (defaults to ~/.config/medea/theme if it exists)
+    #[arg(long, global = true)]
+    theme_file: Option<PathBuf>,
+
+    /// Sort object keys, in lexicographic order unless another mode is given as --sort-keys=MODE
+    #[arg(short, long, global = true, value_enum, num_args = 0..=1, require_equals = true, default_missing_value = "lexicographic")]
+    sort_keys: Option<SortKeys>,
+
+    /// Print arrays and objects that fit into this line width on a single line
+    #[arg(short = 'w', long, global = true, conflicts_with = "compact")]
+    max_width: Option<usize>,
+
+    /// Pack as many scalar array elements per line as the maximum width allows
+    #[arg(long, global = true, default_value_t = false, requires = "max_width")]
+    fill: bool,
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum SortKeys {
+    /// Compare keys by Unicode code points
+    Lexicographic,
+    /// Compare numbers embedded in keys by value
+    Natural,
+}
 
-fn main() -> anyhow::Result<()> {
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ColorWhen {
+    /// Always use colors
+    Always,
+    /// Never use colors
+    Never,
+    /// Use colors if writing to a terminal and NO_COLOR is not set
+    Auto,
+}
 
-    let cli = Cli::parse();
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ThemeName {
+    /// Basic ANSI colors
+    Default,
+    /// 256-color theme for dark backgrounds
+    Dark,
+    /// 256-color theme for light backgrounds
+    Light,
+}
+
+impl From<SortKeys> for KeyOrder {
+    fn from(sort_keys: SortKeys) -> Self {
+        match sort_keys {
+            SortKeys::Lexicographic => KeyOrder::Lexicographic,
+            SortKeys::Natural => KeyOrder::Natural,
+        }
+    }
+}
+
+impl OutputArgs {
+    fn color_choice(&self) -> ColorChoice {
+        if self.use_colors {
+            return ColorChoice::Always;
+        }
+        match self.color {
+            ColorWhen::Always => ColorChoice::Always,
+            ColorWhen::Never => ColorChoice::Never,
+            ColorWhen::Auto => ColorChoice::Auto,
+        }
+    }
+
+    /// Loads the built-in theme and applies the overrides from the theme file and from the
+    /// `MEDEA_COLORS` environment variable, in that order.
+    fn load_theme(&self) -> anyhow::Result<Theme> {
+        let mut theme = match self.theme {
+            ThemeName::Default => Theme::default(),
+            ThemeName::Dark => Theme::dark(),
+            ThemeName::Light => Theme::light(),
+        };
+
+        match &self.theme_file {
+            Some(path) => theme.apply_file(path)?,
+            None => {
+                if let Some(path) = medea::default_theme_file().filter(|path| path.is_file()) {
+                    theme.apply_file(&path)?;
+                }
+            }
+        }
 
-    let input_json = if !cli.json_file.is_empty() {
-        std::fs::read_to_string(&cli.json_file)
-            .map_err(|e| anyhow::anyhow!("Failed to read input file {}: {}", cli.json_file, e))?
+        theme.apply_env()?;
+
+        Ok(theme)
+    }
+
+    /// Decides whether to use colors on stdout and configures the colored crate accordingly.
+    fn enable_colors(&self) -> bool {
+        let use_colors = self.color_choice().should_colorize(std::io::stdout().is_terminal());
+        colored::control::set_override(use_colors);
+        use_colors
+    }
+
+    fn format_options(&self) -> FormatOptions {
+        FormatOptions {
+            indent: if self.tabs { Indent::Tab } else { Indent::Spaces(self.indent) },
+            space_before_colon: self.space_before_colon,
+            space_in_brackets: self.space_in_brackets,
+            space_after_comma: self.space_after_comma,
+            line_ending: if self.crlf { LineEnding::CrLf } else { LineEnding::Lf },
+            final_newline: !self.no_final_newline,
+            ascii_only: self.ascii,
+        }
+    }
+
+    fn printer(&self) -> anyhow::Result<PrettyPrinter> {
+        let key_order = self.sort_keys.map_or(KeyOrder::Source, KeyOrder::from);
+        let options = self.format_options();
+        let use_colors = self.enable_colors();
+        let theme = if use_colors { self.load_theme()? } else { Theme::default() };
+
+        Ok(PrettyPrinter::new(self.indent, use_colors)
+            .with_format_options(options)
+            .with_theme(theme)
+            .with_compact(self.compact)
+            .with_key_order(key_order)
+            .with_max_width(self.max_width)
+            .with_fill(self.fill))
+    }
+
+    fn print(&self, json_value: &JsonValue) -> anyhow::Result<()> {
+        let pretty_json = self.printer()?.pretty_print(json_value);
+
+        for colored_str in pretty_json {
+            print!("{}", colored_str);
+        }
+
+        Ok(())
+    }
+}
+
+fn read_input(json_file: &str) -> anyhow::Result<String> {
+    if !json_file.is_empty() {
+        std::fs::read_to_string(json_file)
+            .map_err(|e| anyhow::anyhow!("Failed to read input file {}: {}", json_file, e))
     } else {
         let mut buffer = String::new();
         std::io::stdin()
             .read_to_string(&mut buffer)
             .map_err(|e| anyhow::anyhow!("Failed to read from stdin: {}", e))?;
-        buffer
+        Ok(buffer)
+    }
+}
+
+fn main() -> ExitCode {
+
+    let cli = Cli::parse();
+
+    if let Err(error) = run(&cli) {
+        let use_colors = cli.output.color_choice().should_colorize(std::io::stderr().is_terminal());
+        colored::control::set_override(use_colors);
+        eprintln!("{} {:#}", "error:".red().bold(), error);
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn run(cli: &Cli) -> anyhow::Result<()> {
+    let formatting_args = !cli.json_file.is_empty() || cli.pointer.is_some() || cli.canonical || cli.gron || cli.ungron;
+    if cli.command.is_some() && formatting_args {
+        anyhow::bail!("An input file, --pointer, --canonical, --gron and --ungron cannot be used with a subcommand");
+    }
+    match &cli.command {
+        None => format(cli),
+        Some(Command::Query { path, json_file, with_paths }) => query(cli, path, json_file, *with_paths),
+        Some(Command::Filter { expr, json_file }) => filter(cli, expr, json_file),
+        Some(Command::Patch { json_file, patch_file }) => patch(cli, json_file, patch_file),
+        Some(Command::Merge { base_file, patch_files }) => merge(cli, base_file, patch_files),
+        Some(Command::Validate { schema, json_file }) => validate(schema, json_file),
+        Some(Command::InferSchema { sample_files, max_enum }) => infer_schema(cli, sample_files, *max_enum),
+        Some(Command::Codegen { lang, name, sample_files }) => codegen(*lang, name, sample_files),
+        Some(Command::Diff { format, ignore_key_order, ignore_array_order, float_tolerance, from_file, to_file }) => {
+            let options = DiffOptions {
+                ignore_key_order: *ignore_key_order,
+                ignore_array_order: *ignore_array_order,
+                float_tolerance: *float_tolerance,
+            };
+            diff(cli, *format, &options, from_file, to_file)
+        }
+        Some(Command::Flatten { key_args, json_file }) => flatten(cli, &key_args.flatten_options(), json_file),
+        Some(Command::Unflatten { key_args, json_file }) => unflatten(cli, &key_args.flatten_options(), json_file),
+        Some(Command::Convert { from, to, input_file }) => convert(cli, *from, *to, input_file),
+    }
+}
+
+fn format(cli: &Cli) -> anyhow::Result<()> {
+
+    let input_json = read_input(&cli.json_file)?;
+    let document = if cli.ungron { medea::parse_gron(&input_json)? } else { medea::parse_json(&input_json)? };
+
+    let json_value = match &cli.pointer {
+        Some(pointer) => {
+            medea::parse_pointer(pointer)?;
+            document
+                .pointer(pointer)
+                .ok_or_else(|| anyhow::anyhow!("No value found at JSON pointer {}", pointer))?
+        }
+        None => &document,
+    };
+
+    if cli.canonical {
+        print!("{}", json_value.to_canonical_string()?);
+        return Ok(());
+    }
+
+    if cli.gron {
+        print!("{}", json_value.to_gron(&cli.output.format_options()));
+        return Ok(());
+    }
+
+    cli.output.print(json_value)
+}
+
+fn query(cli: &Cli, path: &str, json_file: &str, with_paths: bool) -> anyhow::Result<()> {
+    let path = JsonPath::parse(path)?;
+    let document = medea::parse_json(&read_input(json_file)?)?;
+
+    let nodes = path.query(&document);
+
+    let result = if with_paths {
+        let names = nodes.iter().map(|node| node.path.to_string()).collect();
+        let members = nodes.into_iter().map(|node| (node.path.to_string(), node.value.clone())).collect();
+        JsonValue::Object(names, members)
+    } else {
+        JsonValue::Array(nodes.into_iter().map(|node| node.value.clone()).collect())
     };
 
-    let pretty_json = medea::pretty_print_json(&input_json, cli.indent, cli.use_colors)?;
+    cli.output.print(&result)
+}
+
+/// Prints every output of the filter, one document after the other.
+fn filter(cli: &Cli, expr: &str, json_file: &str) -> anyhow::Result<()> {
+    let filter = Filter::parse(expr)?;
+    let document = medea::parse_json(&read_input(json_file)?)?;
 
-    for colored_str in pretty_json {
-        print!("{}", colored_str);
+    for output in filter.apply(&document)? {
+        cli.output.print(&output)?;
     }
 
     Ok(())
 }
+
+fn patch(cli: &Cli, json_file: &str, patch_file: &str) -> anyhow::Result<()> {
+    let mut document = medea::parse_json(&read_input(json_file)?)?;
+    let patch = JsonPatch::from_json(&medea::parse_json(&read_input(patch_file)?)?)?;
+
+    document.apply_patch(&patch)?;
+
+    cli.output.print(&document)
+}
+
+fn merge(cli: &Cli, base_file: &str, patch_files: &[String]) -> anyhow::Result<()> {
+    let mut document = medea::parse_json(&read_input(base_file)?)?;
+
+    for patch_file in patch_files {
+        let patch = medea::parse_json(&read_input(patch_file)?)?;
+        document.merge_patch(&patch);
+    }
+
+    cli.output.print(&document)
+}
+
+/// Prints every schema violation and fails if there are any.
+fn validate(schema_file: &str, json_file: &str) -> anyhow::Result<()> {
+    let schema = Schema::compile(&medea::parse_json(&read_input(schema_file)?)?)?;
+    let document = medea::parse_json(&read_input(json_file)?)?;
+
+    let errors = schema.validate(&document);
+    for error in &errors {
+        println!("{}", error);
+    }
+
+    match errors.len() {
+        0 => Ok(()),
+        1 => anyhow::bail!("Document is invalid: 1 schema violation"),
+        n => anyhow::bail!("Document is invalid: {} schema violations", n),
+    }
+}
+
+fn read_samples(sample_files: &[String]) -> anyhow::Result<Vec<JsonValue>> {
+    sample_files
+        .iter()
+        .map(|sample_file| {
+            medea::parse_json(&read_input(sample_file)?).with_context(|| format!("Invalid sample {}", sample_file))
+        })
+        .collect()
+}
+
+fn infer_schema(cli: &Cli, sample_files: &[String], max_enum: usize) -> anyhow::Result<()> {
+    let samples = read_samples(sample_files)?;
+
+    let options = InferOptions { max_enum_values: max_enum };
+    let schema = medea::schema::infer_schema(&samples, &options)?;
+
+    cli.output.print(&schema)
+}
+
+fn codegen(lang: Language, name: &str, sample_files: &[String]) -> anyhow::Result<()> {
+    let samples = read_samples(sample_files)?;
+    let options = CodegenOptions {
+        root_name: name.to_string(),
+        ..CodegenOptions::default()
+    };
+
+    let code = match lang {
+        Language::Rust => medea::generate_rust(&samples, &options)?,
+        Language::TypeScript => medea::generate_typescript(&samples, &options)?,
+    };
+    print!("{}", code);
+
+    Ok(())
+}
+
+fn diff(cli: &Cli, format: DiffFormat, options: &DiffOptions, from_file: &str, to_file: &str) -> anyhow::Result<()> {
+    let uses_options = options.ignore_key_order || options.ignore_array_order || options.float_tolerance != 0.0;
+    if uses_options && !matches!(format, DiffFormat::Text) {
+        anyhow::bail!("--ignore-key-order, --ignore-array-order and --float-tolerance require --format=text");
+    }
+
+    let from = medea::parse_json(&read_input(from_file)?)?;
+    let to = medea::parse_json(&read_input(to_file)?)?;
+
+    let result = match format {
+        DiffFormat::Text => {
+            cli.output.enable_colors();
+            for entry in medea::diff(&from, &to, options) {
+                let line = entry.to_string();
+                let line = match entry.kind {
+                    DiffKind::Added => line.green(),
+                    DiffKind::Removed => line.red(),
+                    DiffKind::Changed | DiffKind::Reordered => line.yellow(),
+                };
+                println!("{}", line);
+            }
+            return Ok(());
+        }
+        DiffFormat::Patch => JsonPatch::diff(&from, &to).to_json(),
+        DiffFormat::MergePatch => medea::merge_patch_diff(&from, &to),
+    };
+
+    cli.output.print(&result)
+}
+
+fn flatten(cli: &Cli, options: &FlattenOptions, json_file: &str) -> anyhow::Result<()> {
+    let document = medea::parse_json(&read_input(json_file)?)?;
+    cli.output.print(&medea::flatten(&document, options)?)
+}
+
+fn unflatten(cli: &Cli, options: &FlattenOptions, json_file: &str) -> anyhow::Result<()> {
+    let document = medea::parse_json(&read_input(json_file)?)?;
+    cli.output.print(&medea::unflatten(&document, options)?)
+}
+
+fn convert(cli: &Cli, from: DataFormat, to: DataFormat, input_file: &str) -> anyhow::Result<()> {
+    let input = read_input(input_file)?;
+    let document = match from {
+        DataFormat::Json => medea::parse_json(&input)?,
+        DataFormat::Yaml => medea::parse_yaml(&input)?,
+    };
+
+    match to {
+        DataFormat::Json => cli.output.print(&finite_numbers(document)),
+        DataFormat::Yaml => {
+            let output = &cli.output;
+            if output.tabs || output.compact || output.space_before_colon || output.space_in_brackets || output.no_final_newline {
+                anyhow::bail!("--tabs, --compact, --space-before-colon, --space-in-brackets and --no-final-newline cannot be used with --to yaml");
+            }
+            let mut document = document;
+            if let Some(order) = output.sort_keys {
+                sort_keys(&mut document, order);
+            }
+            let options = YamlOptions {
+                indent: output.indent,
+                max_width: output.max_width.unwrap_or(YamlOptions::default().max_width),
+                line_ending: if output.crlf { LineEnding::CrLf } else { LineEnding::Lf },
+                ascii_only: output.ascii,
+            };
+            print!("{}", document.to_yaml(&options));
+            Ok(())
+        }
+    }
+}
+
+/// Reorders the members of all objects, for outputs that have no key order of their own
+fn sort_keys(value: &mut JsonValue, order: SortKeys) {
+    match value {
+        JsonValue::Array(elements) => elements.iter_mut().for_each(|element| sort_keys(element, order)),
+        JsonValue::Object(names, members) => {
+            match order {
+                SortKeys::Lexicographic => names.sort(),
+                SortKeys::Natural => names.sort_by(|a, b| medea::natural_cmp(a, b)),
+            }
+            members.values_mut().for_each(|member| sort_keys(member, order));
+        }
+        _ => (),
+    }
+}
+
+/// Replaces the non-finite numbers YAML can express (`.inf`, `.nan`) with null, which is what
+/// converting them to JSON yields elsewhere
+fn finite_numbers(value: JsonValue) -> JsonValue {
+    match value {
+        JsonValue::Number(n) if !n.is_finite() => JsonValue::Null,
+        JsonValue::Array(elements) => JsonValue::Array(elements.into_iter().map(finite_numbers).collect()),
+        JsonValue::Object(names, members) => {
+            JsonValue::Object(names, members.into_iter().map(|(name, value)| (name, finite_numbers(value))).collect())
+        }
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_keys_argument() {
+        let cli = Cli::try_parse_from(["medea", "--sort-keys", "file.json"]).unwrap();
+        assert_eq!(cli.json_file, "file.json");
+        assert!(matches!(cli.output.sort_keys, Some(SortKeys::Lexicographic)));
+
+        let cli = Cli::try_parse_from(["medea", "-s", "file.json"]).unwrap();
+        assert_eq!(cli.json_file, "file.json");
+        assert!(cli.output.sort_keys.is_some());
+
+        let cli = Cli::try_parse_from(["medea", "--sort-keys=natural", "file.json"]).unwrap();
+        assert!(matches!(cli.output.sort_keys, Some(SortKeys::Natural)));
+
+        let cli = Cli::try_parse_from(["medea", "file.json"]).unwrap();
+        assert!(cli.output.sort_keys.is_none());
+    }
+
+    #[test]
+    fn test_global_arguments_before_subcommand() {
+        let cli = Cli::try_parse_from(["medea", "--compact", "-s", "query", "$.a", "file.json"]).unwrap();
+        assert!(cli.output.compact && cli.output.sort_keys.is_some());
+        let Some(Command::Query { path, json_file, .. }) = &cli.command else { panic!("expected the query subcommand") };
+        assert_eq!((path.as_str(), json_file.as_str()), ("$.a", "file.json"));
+        assert!(cli.json_file.is_empty());
+
+        let cli = Cli::try_parse_from(["medea", "query", "$.a", "--indent", "2"]).unwrap();
+        assert_eq!(cli.output.indent, 2);
+
+        let cli = Cli::try_parse_from(["medea", "--pointer", "/a", "query", "$.a"]).unwrap();
+        assert!(run(&cli).is_err());
+    }
+
+    #[test]
+    fn test_sort_keys() {
+        let mut document = medea::parse_json(r#"{"b10": [{"y": 1, "x": 2}], "b2": 1, "a": 0}"#).unwrap();
+        sort_keys(&mut document, SortKeys::Natural);
+        assert_eq!(document.to_yaml(&YamlOptions::default()), "a: 0\nb2: 1\nb10:\n  - {x: 2, \"y\": 1}\n");
+        sort_keys(&mut document, SortKeys::Lexicographic);
+        let JsonValue::Object(names, _) = document else { unreachable!() };
+        assert_eq!(names, ["a", "b10", "b2"]);
+    }
+
+    #[test]
+    fn test_finite_numbers() {
+        let document = medea::parse_yaml("a: .inf\nb: [-.inf, .nan, 1.5]\n").unwrap();
+        assert_eq!(finite_numbers(document), medea::parse_json(r#"{"a": null, "b": [null, null, 1.5]}"#).unwrap());
+    }
+}
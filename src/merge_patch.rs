@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use crate::json_value::{json_equal, JsonValue};
+
+impl JsonValue {
+    /// Applies a JSON merge patch (RFC 7386). Members set to null in the patch are removed,
+    /// other members are merged recursively; new members are appended after the existing ones.
+    pub fn merge_patch(&mut self, patch: &JsonValue) {
+        let JsonValue::Object(patch_names, patch_members) = patch else {
+            *self = patch.clone();
+            return;
+        };
+        if !matches!(self, JsonValue::Object(_, _)) {
+            *self = JsonValue::Object(Vec::new(), HashMap::new());
+        }
+        let JsonValue::Object(names, members) = self else {
+            unreachable!("target was replaced by an object");
+        };
+
+        for name in patch_names {
+            let Some(value) = patch_members.get(name) else {
+                continue;
+            };
+            if *value == JsonValue::Null {
+                if members.remove(name).is_some() {
+                    names.retain(|n| n != name);
+                }
+                continue;
+            }
+            match members.get_mut(name) {
+                Some(member) => member.merge_patch(value),
+                None => {
+                    let mut member = JsonValue::Null;
+                    member.merge_patch(value);
+                    names.push(name.clone());
+                    members.insert(name.clone(), member);
+                }
+            }
+        }
+    }
+}
+
+/// Computes a merge patch that turns `from` into `to`.
+///
+/// Merge patches cannot express null values inside objects, so members of `to` that are null
+/// are removed when the patch is applied.
+pub fn merge_patch_diff(from: &JsonValue, to: &JsonValue) -> JsonValue {
+    let (JsonValue::Object(from_names, from_members), JsonValue::Object(to_names, to_members)) = (from, to) else {
+        return to.clone();
+    };
+
+    let mut names = Vec::new();
+    let mut members = HashMap::new();
+    for name in from_names.iter().filter(|name| !to_members.contains_key(*name)) {
+        names.push(name.clone());
+        members.insert(name.clone(), JsonValue::Null);
+    }
+    for name in to_names {
+        let to_value = &to_members[name];
+        let patch = match from_members.get(name) {
+            Some(from_value) if json_equal(from_value, to_value) => continue,
+            Some(from_value) => merge_patch_diff(from_value, to_value),
+            None => to_value.clone(),
+        };
+        names.push(name.clone());
+        members.insert(name.clone(), patch);
+    }
+    JsonValue::Object(names, members)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    fn merged(target: &str, patch: &str) -> JsonValue {
+        let mut target = parse_json(target).unwrap();
+        target.merge_patch(&parse_json(patch).unwrap());
+        target
+    }
+
+    #[test]
+    fn test_rfc_examples() {
+        let examples = [
+            (r#"{"a":"b"}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"b":"c"}"#, r#"{"a":"b","b":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"a":null}"#, r#"{}"#),
+            (r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#, r#"{"b":"c"}"#),
+            (r#"{"a":["b"]}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"c"}"#, r#"{"a":["b"]}"#, r#"{"a":["b"]}"#),
+            (r#"{"a":{"b":"c"}}"#, r#"{"a":{"b":"d","c":null}}"#, r#"{"a":{"b":"d"}}"#),
+            (r#"{"a":[{"b":"c"}]}"#, r#"{"a":[1]}"#, r#"{"a":[1]}"#),
+            (r#"["a","b"]"#, r#"["c","d"]"#, r#"["c","d"]"#),
+            (r#"{"a":"b"}"#, r#"["c"]"#, r#"["c"]"#),
+            (r#"{"a":"foo"}"#, "null", "null"),
+            (r#"{"a":"foo"}"#, r#""bar""#, r#""bar""#),
+            (r#"{"e":null}"#, r#"{"a":1}"#, r#"{"e":null,"a":1}"#),
+            (r#"[1,2]"#, r#"{"a":"b","c":null}"#, r#"{"a":"b"}"#),
+            (r#"{}"#, r#"{"a":{"bb":{"ccc":null}}}"#, r#"{"a":{"bb":{}}}"#),
+        ];
+        for (target, patch, expected) in examples {
+            assert_eq!(merged(target, patch), parse_json(expected).unwrap(), "{} + {}", target, patch);
+        }
+    }
+
+    #[test]
+    fn test_key_order() {
+        let result = merged(r#"{"z": 1, "a": 2, "m": 3}"#, r#"{"b": 4, "a": 5, "z": null}"#);
+        let JsonValue::Object(names, _) = result else { panic!("expected an object") };
+        assert_eq!(names, vec!["a", "m", "b"]);
+    }
+
+    #[test]
+    fn test_diff() {
+        let from = parse_json(r#"{"title": "Goodbye!", "author": {"givenName": "John", "familyName": "Doe"},
+            "tags": ["example", "sample"], "content": "This will be unchanged"}"#).unwrap();
+        let to = parse_json(r#"{"title": "Hello!", "author": {"givenName": "John"},
+            "tags": ["example"], "content": "This will be unchanged", "phoneNumber": "+01-123-456-7890"}"#).unwrap();
+        let patch = merge_patch_diff(&from, &to);
+        assert_eq!(patch, parse_json(r#"{"title": "Hello!", "author": {"familyName": null},
+            "tags": ["example"], "phoneNumber": "+01-123-456-7890"}"#).unwrap());
+
+        let mut patched = from.clone();
+        patched.merge_patch(&patch);
+        assert!(json_equal(&patched, &to));
+
+        assert_eq!(merge_patch_diff(&from, &from), parse_json("{}").unwrap());
+        assert_eq!(merge_patch_diff(&from, &parse_json("[1]").unwrap()), parse_json("[1]").unwrap());
+    }
+}
@@ -121,7 +121,7 @@ impl Parser {
         if let Some(token) = self.tokens.pop_front() {
             Ok(token)
         } else {
-            self.lexer.next_token().ok_or_else(|| anyhow!("Unexpected end of input"))
+            self.lexer.next_token().ok_or_else(|| self.lexer_error())
         }
     }
 
@@ -130,16 +130,21 @@ impl Parser {
             if let Some(token) = self.lexer.next_token() {
                 self.tokens.push_back(token);
             } else {
-                return Err(anyhow!("Unexpected end of input"));
+                return Err(self.lexer_error());
             }
         }
         Ok(self.tokens.front().unwrap())
     }
+
+    fn lexer_error(&self) -> anyhow::Error {
+        anyhow!(self.lexer.error().unwrap_or("Unexpected end of input").to_string())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pretty_printer::format_number;
 
 
     #[test]
@@ -164,6 +169,19 @@ mod tests {
         let json = "123.45";
         let result = parse_json(json).unwrap();
         assert_eq!(result, JsonValue::Number(123.45));
+        assert_eq!(parse_json("[1e400]").unwrap_err().to_string(), "Number out of range at line 1, column 2: 1e400");
+        assert_eq!(parse_json("[NaN]").unwrap_err().to_string(), "Unexpected end of input");
+        assert_eq!(parse_json("[1E+2, 1e-2]").unwrap(), JsonValue::Array(vec![JsonValue::Number(100.0), JsonValue::Number(0.01)]));
+        assert!(parse_json("[1+2]").is_err());
+    }
+
+    #[test]
+    fn test_parse_printed_numbers() {
+        for n in [1e21, 1e-7, 1e300, -2.5e-300] {
+            let printed = format_number(n);
+            assert!(printed.contains('e'), "{}", printed);
+            assert_eq!(parse_json(&printed).unwrap(), JsonValue::Number(n), "{}", printed);
+        }
     }
 
     #[test]
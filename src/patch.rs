@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+use anyhow::{Context, Result, anyhow};
+use crate::json_value::{json_equal, JsonValue};
+use crate::pointer::{escape_token, parse_index, parse_pointer, to_pointer};
+
+/// A single operation of a JSON Patch document (RFC 6902)
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchOperation {
+    Add { path: String, value: JsonValue },
+    Remove { path: String },
+    Replace { path: String, value: JsonValue },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: JsonValue },
+}
+
+impl PatchOperation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PatchOperation::Add { .. } => "add",
+            PatchOperation::Remove { .. } => "remove",
+            PatchOperation::Replace { .. } => "replace",
+            PatchOperation::Move { .. } => "move",
+            PatchOperation::Copy { .. } => "copy",
+            PatchOperation::Test { .. } => "test",
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        match self {
+            PatchOperation::Add { path, .. }
+            | PatchOperation::Remove { path }
+            | PatchOperation::Replace { path, .. }
+            | PatchOperation::Move { path, .. }
+            | PatchOperation::Copy { path, .. }
+            | PatchOperation::Test { path, .. } => path,
+        }
+    }
+
+    fn from_json(operation: &JsonValue) -> Result<Self> {
+        let JsonValue::Object(_, members) = operation else {
+            return Err(anyhow!("Patch operation must be an object"));
+        };
+        let string_member = |name: &str| match members.get(name) {
+            Some(JsonValue::String(s)) => Ok(s.clone()),
+            Some(_) => Err(anyhow!("Member '{}' must be a string", name)),
+            None => Err(anyhow!("Missing member '{}'", name)),
+        };
+        let value_member = || members.get("value").cloned().ok_or_else(|| anyhow!("Missing member 'value'"));
+
+        let op = string_member("op")?;
+        let path = string_member("path")?;
+        parse_pointer(&path)?;
+        let operation = match op.as_str() {
+            "add" => PatchOperation::Add { path, value: value_member()? },
+            "remove" => PatchOperation::Remove { path },
+            "replace" => PatchOperation::Replace { path, value: value_member()? },
+            "move" | "copy" => {
+                let from = string_member("from")?;
+                parse_pointer(&from)?;
+                if op == "move" {
+                    PatchOperation::Move { from, path }
+                } else {
+                    PatchOperation::Copy { from, path }
+                }
+            }
+            "test" => PatchOperation::Test { path, value: value_member()? },
+            _ => return Err(anyhow!("Unknown operation '{}'", op)),
+        };
+        Ok(operation)
+    }
+
+    fn to_json(&self) -> JsonValue {
+        let mut members = vec![
+            ("op", JsonValue::String(self.name().to_string())),
+        ];
+        if let PatchOperation::Move { from, .. } | PatchOperation::Copy { from, .. } = self {
+            members.push(("from", JsonValue::String(from.clone())));
+        }
+        members.push(("path", JsonValue::String(self.path().to_string())));
+        if let PatchOperation::Add { value, .. }
+        | PatchOperation::Replace { value, .. }
+        | PatchOperation::Test { value, .. } = self
+        {
+            members.push(("value", value.clone()));
+        }
+
+        let names = members.iter().map(|(name, _)| name.to_string()).collect();
+        let members: HashMap<String, JsonValue> =
+            members.into_iter().map(|(name, value)| (name.to_string(), value)).collect();
+        JsonValue::Object(names, members)
+    }
+}
+
+/// A JSON Patch document (RFC 6902), i.e. a sequence of operations applied in order
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct JsonPatch(pub Vec<PatchOperation>);
+
+impl JsonPatch {
+    /// Reads a patch from its JSON representation, an array of operation objects.
+    pub fn from_json(patch: &JsonValue) -> Result<Self> {
+        let JsonValue::Array(operations) = patch else {
+            return Err(anyhow!("JSON patch must be an array of operations"));
+        };
+        operations
+            .iter()
+            .enumerate()
+            .map(|(index, operation)| {
+                PatchOperation::from_json(operation)
+                    .with_context(|| format!("Invalid patch operation at index {}", index))
+            })
+            .collect::<Result<_>>()
+            .map(JsonPatch)
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        JsonValue::Array(self.0.iter().map(PatchOperation::to_json).collect())
+    }
+
+    /// Computes a patch that turns `from` into `to`.
+    ///
+    /// Objects are compared member by member, arrays element by element; elements missing at
+    /// the end are removed back to front and additional elements are appended.
+    pub fn diff(from: &JsonValue, to: &JsonValue) -> Self {
+        let mut operations = Vec::new();
+        diff_values(&mut Vec::new(), from, to, &mut operations);
+        JsonPatch(operations)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+fn diff_values(path: &mut Vec<String>, from: &JsonValue, to: &JsonValue, operations: &mut Vec<PatchOperation>) {
+    if json_equal(from, to) {
+        return;
+    }
+    match (from, to) {
+        (JsonValue::Object(from_names, from_members), JsonValue::Object(to_names, to_members)) => {
+            for name in from_names.iter().filter(|name| !to_members.contains_key(*name)) {
+                operations.push(PatchOperation::Remove { path: child_pointer(path, name) });
+            }
+            for name in from_names {
+                if let (Some(from_value), Some(to_value)) = (from_members.get(name), to_members.get(name)) {
+                    path.push(name.clone());
+                    diff_values(path, from_value, to_value, operations);
+                    path.pop();
+                }
+            }
+            for name in to_names.iter().filter(|name| !from_members.contains_key(*name)) {
+                operations.push(PatchOperation::Add {
+                    path: child_pointer(path, name),
+                    value: to_members[name].clone(),
+                });
+            }
+        }
+        (JsonValue::Array(from_elements), JsonValue::Array(to_elements)) => {
+            let common = from_elements.len().min(to_elements.len());
+            for index in 0..common {
+                path.push(index.to_string());
+                diff_values(path, &from_elements[index], &to_elements[index], operations);
+                path.pop();
+            }
+            for index in (common..from_elements.len()).rev() {
+                operations.push(PatchOperation::Remove { path: child_pointer(path, &index.to_string()) });
+            }
+            for value in &to_elements[common..] {
+                operations.push(PatchOperation::Add { path: child_pointer(path, "-"), value: value.clone() });
+            }
+        }
+        _ => operations.push(PatchOperation::Replace { path: to_pointer(path), value: to.clone() }),
+    }
+}
+
+fn child_pointer(path: &[String], token: &str) -> String {
+    format!("{}/{}", to_pointer(path), escape_token(token))
+}
+
+impl JsonValue {
+    /// Applies a JSON patch. The patch is applied atomically: if any operation fails, the
+    /// value is left unchanged.
+    pub fn apply_patch(&mut self, patch: &JsonPatch) -> Result<()> {
+        let mut patched = self.clone();
+        for (index, operation) in patch.0.iter().enumerate() {
+            patched
+                .apply_operation(operation)
+                .with_context(|| format!("Patch operation {} ({} {}) failed", index, operation.name(), operation.path()))?;
+        }
+        *self = patched;
+        Ok(())
+    }
+
+    fn apply_operation(&mut self, operation: &PatchOperation) -> Result<()> {
+        match operation {
+            PatchOperation::Add { path, value } => self.add_pointer(path, value.clone()),
+            PatchOperation::Remove { path } => self.remove_pointer(path).map(|_| ()),
+            PatchOperation::Replace { path, value } => {
+                self.existing(path)?;
+                self.set_pointer(path, value.clone()).map(|_| ())
+            }
+            PatchOperation::Move { from, path } => {
+                if from == path {
+                    return self.existing(from).map(|_| ());
+                }
+                if path.starts_with(&format!("{}/", from)) {
+                    return Err(anyhow!("Cannot move a value into one of its children"));
+                }
+                let value = self.remove_pointer(from)?;
+                self.add_pointer(path, value)
+            }
+            PatchOperation::Copy { from, path } => {
+                let value = self.existing(from)?.clone();
+                self.add_pointer(path, value)
+            }
+            PatchOperation::Test { path, value } => {
+                if json_equal(self.existing(path)?, value) {
+                    Ok(())
+                } else {
+                    Err(anyhow!("Test failed, the value at {} differs", path))
+                }
+            }
+        }
+    }
+
+    fn existing(&self, pointer: &str) -> Result<&JsonValue> {
+        parse_pointer(pointer)?;
+        self.pointer(pointer).ok_or_else(|| anyhow!("No value found at JSON pointer {}", pointer))
+    }
+
+    /// Adds a value like the patch operation `add`: array elements are inserted rather
+    /// than replaced.
+    fn add_pointer(&mut self, pointer: &str, value: JsonValue) -> Result<()> {
+        let mut tokens = parse_pointer(pointer)?;
+        let Some(last) = tokens.pop() else {
+            *self = value;
+            return Ok(());
+        };
+        match self.pointer_mut(&to_pointer(&tokens)) {
+            Some(JsonValue::Array(elements)) => {
+                let index = if last == "-" { Some(elements.len()) } else { parse_index(&last) };
+                match index {
+                    Some(index) if index <= elements.len() => {
+                        elements.insert(index, value);
+                        Ok(())
+                    }
+                    _ => Err(anyhow!("Invalid array index '{}' in JSON pointer {}", last, pointer)),
+                }
+            }
+            Some(_) => self.set_pointer(pointer, value).map(|_| ()),
+            None => Err(anyhow!("Parent of JSON pointer {} does not exist", pointer)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    fn apply(doc: &str, patch: &str) -> Result<JsonValue> {
+        let mut doc = parse_json(doc).unwrap();
+        let patch = JsonPatch::from_json(&parse_json(patch).unwrap())?;
+        doc.apply_patch(&patch)?;
+        Ok(doc)
+    }
+
+    fn assert_patched(doc: &str, patch: &str, expected: &str) {
+        assert_eq!(apply(doc, patch).unwrap(), parse_json(expected).unwrap());
+    }
+
+    #[test]
+    fn test_rfc_examples() {
+        assert_patched(r#"{"foo": "bar"}"#, r#"[{"op": "add", "path": "/baz", "value": "qux"}]"#,
+            r#"{"foo": "bar", "baz": "qux"}"#);
+        assert_patched(r#"{"foo": ["bar", "baz"]}"#, r#"[{"op": "add", "path": "/foo/1", "value": "qux"}]"#,
+            r#"{"foo": ["bar", "qux", "baz"]}"#);
+        assert_patched(r#"{"baz": "qux", "foo": "bar"}"#, r#"[{"op": "remove", "path": "/baz"}]"#,
+            r#"{"foo": "bar"}"#);
+        assert_patched(r#"{"foo": ["bar", "qux", "baz"]}"#, r#"[{"op": "remove", "path": "/foo/1"}]"#,
+            r#"{"foo": ["bar", "baz"]}"#);
+        assert_patched(r#"{"baz": "qux", "foo": "bar"}"#, r#"[{"op": "replace", "path": "/baz", "value": "boo"}]"#,
+            r#"{"baz": "boo", "foo": "bar"}"#);
+        assert_patched(
+            r#"{"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}"#,
+            r#"[{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]"#,
+            r#"{"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}}"#,
+        );
+        assert_patched(r#"{"foo": ["all", "grass", "cows", "eat"]}"#, r#"[{"op": "move", "from": "/foo/1", "path": "/foo/3"}]"#,
+            r#"{"foo": ["all", "cows", "eat", "grass"]}"#);
+        assert_patched(r#"{"baz": "qux", "foo": ["a", 2, "c"]}"#,
+            r#"[{"op": "test", "path": "/baz", "value": "qux"}, {"op": "test", "path": "/foo/1", "value": 2}]"#,
+            r#"{"baz": "qux", "foo": ["a", 2, "c"]}"#);
+        assert_patched(r#"{"foo": "bar"}"#, r#"[{"op": "add", "path": "/child", "value": {"grandchild": {}}}]"#,
+            r#"{"foo": "bar", "child": {"grandchild": {}}}"#);
+        assert_patched(r#"{"foo": ["bar"]}"#, r#"[{"op": "add", "path": "/foo/-", "value": ["abc", "def"]}]"#,
+            r#"{"foo": ["bar", ["abc", "def"]]}"#);
+        assert_patched(r#"{"/": 9, "~1": 10}"#, r#"[{"op": "test", "path": "/~01", "value": 10}]"#,
+            r#"{"/": 9, "~1": 10}"#);
+        assert_patched(r#"{"a": 1}"#, r#"[{"op": "copy", "from": "/a", "path": "/b"}, {"op": "add", "path": "", "value": [1]}]"#,
+            "[1]");
+    }
+
+    #[test]
+    fn test_errors_leave_document_unchanged() {
+        let mut doc = parse_json(r#"{"baz": "qux", "foo": "bar"}"#).unwrap();
+        let original = doc.clone();
+        let patch = JsonPatch::from_json(&parse_json(
+            r#"[{"op": "add", "path": "/new", "value": 1}, {"op": "test", "path": "/baz", "value": "bar"}]"#,
+        ).unwrap()).unwrap();
+        let error = doc.apply_patch(&patch).unwrap_err();
+        assert_eq!(format!("{:#}", error), "Patch operation 1 (test /baz) failed: Test failed, the value at /baz differs");
+        assert_eq!(doc, original);
+
+        let error = |doc: &str, patch: &str| format!("{:#}", apply(doc, patch).unwrap_err());
+        assert_eq!(error(r#"{"foo": "bar"}"#, r#"[{"op": "add", "path": "/baz/bat", "value": "qux"}]"#),
+            "Patch operation 0 (add /baz/bat) failed: Parent of JSON pointer /baz/bat does not exist");
+        assert_eq!(error(r#"{"foo": "bar"}"#, r#"[{"op": "replace", "path": "/baz", "value": 1}]"#),
+            "Patch operation 0 (replace /baz) failed: No value found at JSON pointer /baz");
+        assert_eq!(error("[1, 2]", r#"[{"op": "add", "path": "/3", "value": 1}]"#),
+            "Patch operation 0 (add /3) failed: Invalid array index '3' in JSON pointer /3");
+        assert_eq!(error(r#"{"a": {"b": 1}}"#, r#"[{"op": "move", "from": "/a", "path": "/a/c"}]"#),
+            "Patch operation 0 (move /a/c) failed: Cannot move a value into one of its children");
+        assert_eq!(error("{}", r#"[{"op": "add", "path": "/a"}]"#),
+            "Invalid patch operation at index 0: Missing member 'value'");
+        assert_eq!(error("{}", r#"[{"op": "bogus", "path": "/a"}]"#),
+            "Invalid patch operation at index 0: Unknown operation 'bogus'");
+        assert_eq!(error("{}", r#"{"op": "remove", "path": "/a"}"#), "JSON patch must be an array of operations");
+    }
+
+    #[test]
+    fn test_diff() {
+        let from = parse_json(r#"{"a": 1, "b": [1, 2, 3], "c": {"d": "x"}, "e/f": true}"#).unwrap();
+        let to = parse_json(r#"{"c": {"d": "y"}, "b": [1, 5], "a": 1, "g": null}"#).unwrap();
+        let patch = JsonPatch::diff(&from, &to);
+        assert_eq!(patch.to_json(), parse_json(r#"[
+            {"op": "remove", "path": "/e~1f"},
+            {"op": "replace", "path": "/b/1", "value": 5},
+            {"op": "remove", "path": "/b/2"},
+            {"op": "replace", "path": "/c/d", "value": "y"},
+            {"op": "add", "path": "/g", "value": null}
+        ]"#).unwrap());
+
+        let mut patched = from.clone();
+        patched.apply_patch(&patch).unwrap();
+        assert!(json_equal(&patched, &to));
+
+        assert!(JsonPatch::diff(&from, &from).is_empty());
+        assert_eq!(JsonPatch::diff(&from, &JsonValue::Null).to_json(),
+            parse_json(r#"[{"op": "replace", "path": "", "value": null}]"#).unwrap());
+        assert_eq!(JsonPatch::diff(&parse_json("[1]").unwrap(), &parse_json("[1, 2, 3]").unwrap()).to_json(),
+            parse_json(r#"[{"op": "add", "path": "/-", "value": 2}, {"op": "add", "path": "/-", "value": 3}]"#).unwrap());
+    }
+}
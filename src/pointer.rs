@@ -0,0 +1,235 @@
+use anyhow::{Result, anyhow};
+use crate::json_value::JsonValue;
+
+/// Splits a JSON pointer (RFC 6901) into its unescaped reference tokens.
+pub fn parse_pointer(pointer: &str) -> Result<Vec<String>> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(rest) = pointer.strip_prefix('/') else {
+        return Err(anyhow!("JSON pointer must start with '/': {}", pointer));
+    };
+    rest.split('/').map(unescape_token).collect()
+}
+
+/// Builds a JSON pointer from unescaped reference tokens.
+pub fn to_pointer<S: AsRef<str>>(tokens: &[S]) -> String {
+    tokens.iter().map(|token| format!("/{}", escape_token(token.as_ref()))).collect()
+}
+
+pub fn escape_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape_token(token: &str) -> Result<String> {
+    let mut unescaped = String::with_capacity(token.len());
+    let mut chars = token.chars();
+    while let Some(c) = chars.next() {
+        if c != '~' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('0') => unescaped.push('~'),
+            Some('1') => unescaped.push('/'),
+            _ => return Err(anyhow!("Invalid escape sequence in JSON pointer token: {}", token)),
+        }
+    }
+    Ok(unescaped)
+}
+
+/// Parses an array index token; leading zeros are not allowed.
+pub(crate) fn parse_index(token: &str) -> Option<usize> {
+    if token.is_empty() || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    if !token.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+impl JsonValue {
+    /// Looks up a value by a JSON pointer like `/address/city`.
+    pub fn pointer(&self, pointer: &str) -> Option<&JsonValue> {
+        let tokens = parse_pointer(pointer).ok()?;
+        tokens.iter().try_fold(self, |value, token| value.child(token))
+    }
+
+    /// Looks up a value by a JSON pointer and returns a mutable reference to it.
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut JsonValue> {
+        let tokens = parse_pointer(pointer).ok()?;
+        tokens.iter().try_fold(self, |value, token| value.child_mut(token))
+    }
+
+    /// Sets the value a JSON pointer refers to and returns the value it replaced.
+    ///
+    /// The parent of the target must exist. A missing object member is added, the token `-`
+    /// or an index equal to the length of an array appends to the array.
+    pub fn set_pointer(&mut self, pointer: &str, value: JsonValue) -> Result<Option<JsonValue>> {
+        let mut tokens = parse_pointer(pointer)?;
+        let Some(last) = tokens.pop() else {
+            return Ok(Some(std::mem::replace(self, value)));
+        };
+        let parent = self.parent_mut(&tokens, pointer)?;
+
+        match parent {
+            JsonValue::Object(names, members) => {
+                if !members.contains_key(&last) {
+                    names.push(last.clone());
+                }
+                Ok(members.insert(last, value))
+            }
+            JsonValue::Array(elements) => {
+                let index = if last == "-" { Some(elements.len()) } else { parse_index(&last) };
+                match index {
+                    Some(index) if index < elements.len() => {
+                        Ok(Some(std::mem::replace(&mut elements[index], value)))
+                    }
+                    Some(index) if index == elements.len() => {
+                        elements.push(value);
+                        Ok(None)
+                    }
+                    _ => Err(anyhow!("Invalid array index '{}' in JSON pointer {}", last, pointer)),
+                }
+            }
+            _ => Err(anyhow!("JSON pointer {} does not refer into an array or object", pointer)),
+        }
+    }
+
+    /// Removes the value a JSON pointer refers to and returns it.
+    pub fn remove_pointer(&mut self, pointer: &str) -> Result<JsonValue> {
+        let mut tokens = parse_pointer(pointer)?;
+        let Some(last) = tokens.pop() else {
+            return Err(anyhow!("Cannot remove the root of a document"));
+        };
+        let parent = self.parent_mut(&tokens, pointer)?;
+
+        let removed = match parent {
+            JsonValue::Object(names, members) => {
+                let removed = members.remove(&last);
+                if removed.is_some() {
+                    names.retain(|name| *name != last);
+                }
+                removed
+            }
+            JsonValue::Array(elements) => match parse_index(&last) {
+                Some(index) if index < elements.len() => Some(elements.remove(index)),
+                _ => None,
+            },
+            _ => None,
+        };
+        removed.ok_or_else(|| anyhow!("No value found at JSON pointer {}", pointer))
+    }
+
+    fn parent_mut(&mut self, tokens: &[String], pointer: &str) -> Result<&mut JsonValue> {
+        tokens
+            .iter()
+            .try_fold(self, |value, token| value.child_mut(token))
+            .ok_or_else(|| anyhow!("Parent of JSON pointer {} does not exist", pointer))
+    }
+
+    fn child(&self, token: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(_, members) => members.get(token),
+            JsonValue::Array(elements) => elements.get(parse_index(token)?),
+            _ => None,
+        }
+    }
+
+    fn child_mut(&mut self, token: &str) -> Option<&mut JsonValue> {
+        match self {
+            JsonValue::Object(_, members) => members.get_mut(token),
+            JsonValue::Array(elements) => elements.get_mut(parse_index(token)?),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    const RFC_EXAMPLE: &str = r#"{
+        "foo": ["bar", "baz"],
+        "": 0,
+        "a/b": 1,
+        "c%d": 2,
+        "e^f": 3,
+        "g|h": 4,
+        "i\\j": 5,
+        "k\"l": 6,
+        " ": 7,
+        "m~n": 8
+    }"#;
+
+    #[test]
+    fn test_pointer() {
+        // RFC 6901, section 5
+        let doc = parse_json(RFC_EXAMPLE).unwrap();
+        assert_eq!(doc.pointer(""), Some(&doc));
+        assert_eq!(doc.pointer("/foo"), Some(&parse_json(r#"["bar", "baz"]"#).unwrap()));
+        assert_eq!(doc.pointer("/foo/0"), Some(&JsonValue::String("bar".to_string())));
+        assert_eq!(doc.pointer("/"), Some(&JsonValue::Number(0.0)));
+        assert_eq!(doc.pointer("/a~1b"), Some(&JsonValue::Number(1.0)));
+        assert_eq!(doc.pointer("/c%d"), Some(&JsonValue::Number(2.0)));
+        assert_eq!(doc.pointer("/e^f"), Some(&JsonValue::Number(3.0)));
+        assert_eq!(doc.pointer("/g|h"), Some(&JsonValue::Number(4.0)));
+        assert_eq!(doc.pointer("/i\\j"), Some(&JsonValue::Number(5.0)));
+        assert_eq!(doc.pointer("/k\"l"), Some(&JsonValue::Number(6.0)));
+        assert_eq!(doc.pointer("/ "), Some(&JsonValue::Number(7.0)));
+        assert_eq!(doc.pointer("/m~0n"), Some(&JsonValue::Number(8.0)));
+
+        assert_eq!(doc.pointer("foo"), None);
+        assert_eq!(doc.pointer("/foo/2"), None);
+        assert_eq!(doc.pointer("/foo/01"), None);
+        assert_eq!(doc.pointer("/foo/-"), None);
+        assert_eq!(doc.pointer("/m~2n"), None);
+    }
+
+    #[test]
+    fn test_pointer_mut() {
+        let mut doc = parse_json(RFC_EXAMPLE).unwrap();
+        *doc.pointer_mut("/foo/1").unwrap() = JsonValue::Null;
+        assert_eq!(doc.pointer("/foo"), Some(&parse_json(r#"["bar", null]"#).unwrap()));
+    }
+
+    #[test]
+    fn test_set_pointer() {
+        let mut doc = parse_json(r#"{"a": {"b": [1, 2]}}"#).unwrap();
+
+        let old = doc.set_pointer("/a/b/0", JsonValue::Bool(true)).unwrap();
+        assert_eq!(old, Some(JsonValue::Number(1.0)));
+        assert_eq!(doc.set_pointer("/a/b/-", JsonValue::Null).unwrap(), None);
+        assert_eq!(doc.set_pointer("/a/c~1d", JsonValue::Number(3.0)).unwrap(), None);
+        assert_eq!(doc, parse_json(r#"{"a": {"b": [true, 2, null], "c/d": 3}}"#).unwrap());
+
+        assert!(doc.set_pointer("/a/b/5", JsonValue::Null).is_err());
+        assert!(doc.set_pointer("/x/y", JsonValue::Null).is_err());
+        assert!(doc.set_pointer("/a/c~1d/e", JsonValue::Null).is_err());
+
+        doc.set_pointer("", JsonValue::Null).unwrap();
+        assert_eq!(doc, JsonValue::Null);
+    }
+
+    #[test]
+    fn test_remove_pointer() {
+        let mut doc = parse_json(r#"{"a": {"b": [1, 2], "m~n": 3}, "c": 4}"#).unwrap();
+
+        assert_eq!(doc.remove_pointer("/a/b/0").unwrap(), JsonValue::Number(1.0));
+        assert_eq!(doc.remove_pointer("/a/m~0n").unwrap(), JsonValue::Number(3.0));
+        assert_eq!(doc, parse_json(r#"{"a": {"b": [2]}, "c": 4}"#).unwrap());
+
+        assert!(doc.remove_pointer("/a/b/1").is_err());
+        assert!(doc.remove_pointer("/a/x").is_err());
+        assert!(doc.remove_pointer("").is_err());
+    }
+
+    #[test]
+    fn test_to_pointer() {
+        assert_eq!(to_pointer(&["a/b", "m~n", "0"]), "/a~1b/m~0n/0");
+        assert_eq!(to_pointer::<&str>(&[]), "");
+        assert_eq!(parse_pointer("/a~1b/m~0n/0").unwrap(), vec!["a/b", "m~n", "0"]);
+    }
+}
\ No newline at end of file
@@ -1,4 +1,9 @@
-use colored::{ColoredString, Colorize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use colored::ColoredString;
+use crate::format_options::{FormatOptions, Indent};
+use crate::json_value::JsonValue;
+use crate::theme::{ColorCategory, Theme};
 
 pub fn pretty_print_json(
     json_str: &str,
@@ -12,17 +17,198 @@ pub fn pretty_print_json(
     Ok(printer.pretty_print(&json_value))
 }
 
-enum ColorCategory {
-    Key,
-    Delimiter,
-    String,
-    Normal
+/// Prints a value on a single line without colors, e.g. for messages.
+pub(crate) fn to_compact_string(value: &JsonValue) -> String {
+    let options = FormatOptions {
+        final_newline: false,
+        ..FormatOptions::default()
+    };
+    let mut printer = PrettyPrinter::new(0, false).with_format_options(options).with_compact(true);
+    printer.pretty_print(value).iter().map(|cs| cs.to_string()).collect()
+}
+
+/// Comparator used to sort object member names
+pub type KeyComparator = Box<dyn Fn(&str, &str) -> Ordering>;
+
+/// Order in which the members of an object are printed.
+///
+/// The order is applied by the printer only, the printed `JsonValue` is left untouched.
+pub enum KeyOrder {
+    /// Keep the order in which the members appear in the source document
+    Source,
+    /// Sort member names lexicographically (by Unicode code points)
+    Lexicographic,
+    /// Sort member names with embedded numbers compared by value ("item2" < "item10")
+    Natural,
+    /// Sort member names by UTF-16 code units as required by RFC 8785
+    Utf16,
+    /// Sort member names with a custom comparator
+    Custom(KeyComparator),
+}
+
+impl KeyOrder {
+    fn sort<'a>(&self, names: &'a [String]) -> Vec<&'a String> {
+        let mut sorted: Vec<&String> = names.iter().collect();
+        match self {
+            KeyOrder::Source => (),
+            KeyOrder::Lexicographic => sorted.sort(),
+            KeyOrder::Natural => sorted.sort_by(|a, b| natural_cmp(a, b)),
+            KeyOrder::Utf16 => sorted.sort_by(|a, b| a.encode_utf16().cmp(b.encode_utf16())),
+            KeyOrder::Custom(cmp) => sorted.sort_by(|a, b| cmp(a, b)),
+        }
+        sorted
+    }
+}
+
+/// Compares two strings so that runs of ASCII digits are compared by their numeric value.
+/// Ties are broken by plain lexicographic comparison.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+
+    loop {
+        match (a_chars.peek(), b_chars.peek()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(ca), Some(cb)) if ca.is_ascii_digit() && cb.is_ascii_digit() => {
+                let num_a = take_digits(&mut a_chars);
+                let num_b = take_digits(&mut b_chars);
+                let num_a = num_a.trim_start_matches('0');
+                let num_b = num_b.trim_start_matches('0');
+                let ordering = num_a.len().cmp(&num_b.len()).then_with(|| num_a.cmp(num_b));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(ca), Some(cb)) => {
+                let ordering = ca.cmp(cb);
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
+fn take_digits(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(c) = chars.peek() {
+        if !c.is_ascii_digit() {
+            break;
+        }
+        digits.push(*c);
+        chars.next();
+    }
+    digits
+}
+
+/// Escapes a string for output as a JSON string literal (without the surrounding quotes).
+///
+/// Only the characters that must be escaped are: quotation mark, reverse solidus and the
+/// control characters, which use the short forms where JSON defines one.
+pub fn escape_string(s: &str) -> String {
+    escape(s, false)
+}
+
+/// Escapes a string like [`escape_string`] and additionally escapes every non-ASCII character
+/// as `\uXXXX`, using a surrogate pair for characters outside of the Basic Multilingual Plane.
+pub fn escape_string_ascii(s: &str) -> String {
+    escape(s, true)
+}
+
+fn escape(s: &str, ascii_only: bool) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\u{0008}' => escaped.push_str("\\b"),
+            '\u{000C}' => escaped.push_str("\\f"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c < '\u{0020}' => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c if ascii_only && !c.is_ascii() => {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    escaped.push_str(&format!("\\u{:04x}", unit));
+                }
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Formats a number the way ECMAScript's `Number.prototype.toString` does, i.e. with the
+/// shortest digit sequence that round-trips and exponential notation outside of 1e-7..1e21.
+pub fn format_number(n: f64) -> String {
+    if n == 0.0 {
+        return "0".to_string();
+    }
+    if !n.is_finite() {
+        return n.to_string();
+    }
+
+    // The shortest round-trip representation determines the number of digits. Formatting
+    // again with that precision rounds exact ties to even as ECMAScript requires.
+    let shortest = format!("{:e}", n.abs());
+    let precision = shortest.split_once('e').unwrap().0.len().saturating_sub(2);
+    let scientific = format!("{:.*e}", precision, n.abs());
+    let (mantissa, exponent) = scientific.split_once('e').unwrap();
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let k = digits.len() as i32;
+    let n_exp = exponent.parse::<i32>().unwrap() + 1;
+
+    let mut result = String::new();
+    if n < 0.0 {
+        result.push('-');
+    }
+    if k <= n_exp && n_exp <= 21 {
+        result.push_str(&digits);
+        result.push_str(&"0".repeat((n_exp - k) as usize));
+    } else if 0 < n_exp && n_exp <= 21 {
+        result.push_str(&digits[..n_exp as usize]);
+        result.push('.');
+        result.push_str(&digits[n_exp as usize..]);
+    } else if -6 < n_exp && n_exp <= 0 {
+        result.push_str("0.");
+        result.push_str(&"0".repeat((-n_exp) as usize));
+        result.push_str(&digits);
+    } else {
+        result.push_str(&digits[..1]);
+        if k > 1 {
+            result.push('.');
+            result.push_str(&digits[1..]);
+        }
+        result.push('e');
+        result.push(if n_exp > 0 { '+' } else { '-' });
+        result.push_str(&(n_exp - 1).abs().to_string());
+    }
+    result
+}
+
+fn is_scalar(value: &JsonValue) -> bool {
+    match value {
+        JsonValue::Array(elements) => elements.is_empty(),
+        JsonValue::Object(names, _) => names.is_empty(),
+        _ => true,
+    }
 }
 
 pub struct PrettyPrinter {
-    indent_size: usize,
+    options: FormatOptions,
     use_colors: bool,
+    theme: Theme,
+    key_order: KeyOrder,
+    compact: bool,
+    max_width: Option<usize>,
+    fill: bool,
     level: usize,
+    column: usize,
     start_line: bool,
     output: Vec<ColoredString>,
 }
@@ -30,111 +216,310 @@ pub struct PrettyPrinter {
 impl PrettyPrinter {
     pub fn new(indent_size: usize, use_colors: bool) -> Self {
         PrettyPrinter {
-            indent_size,
+            options: FormatOptions {
+                indent: Indent::Spaces(indent_size),
+                ..FormatOptions::default()
+            },
             use_colors,
+            theme: Theme::default(),
+            key_order: KeyOrder::Source,
+            compact: false,
+            max_width: None,
+            fill: false,
             level: 0,
+            column: 0,
             start_line: true,
             output: Vec::new(),
         }
     }
 
-    pub fn pretty_print(&mut self, value: &crate::json_value::JsonValue) -> Vec<ColoredString> {
+    pub fn with_format_options(mut self, options: FormatOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub fn with_key_order(mut self, key_order: KeyOrder) -> Self {
+        self.key_order = key_order;
+        self
+    }
+
+    /// In compact mode no whitespace at all is emitted between tokens.
+    pub fn with_compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
+    /// With a maximum line width, arrays and objects that fit into the remaining width
+    /// of the current line are printed on a single line.
+    pub fn with_max_width(mut self, max_width: Option<usize>) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    /// In fill mode, arrays of scalars that do not fit on one line are packed with as many
+    /// elements per line as the maximum width allows.
+    pub fn with_fill(mut self, fill: bool) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    pub fn pretty_print(&mut self, value: &JsonValue) -> Vec<ColoredString> {
         self.output = Vec::new();
         self.level = 0;
+        self.column = 0;
         self.start_line = true;
 
-        self.print_value(value);
-        self.println("", ColorCategory::Normal); // Final newline
+        self.print_value(value, 0);
+        if self.options.final_newline {
+            self.newline();
+        }
 
         self.output.clone()
     }
 
-    fn print_value(&mut self, value: &crate::json_value::JsonValue) {
+    /// Prints a value; `suffix_width` is the width of the text that follows it on the same line.
+    fn print_value(&mut self, value: &JsonValue, suffix_width: usize) {
         match value {
-            crate::json_value::JsonValue::Null => self.print("null", ColorCategory::Normal),
-            crate::json_value::JsonValue::Bool(b) => self.print(&b.to_string(), ColorCategory::Normal),
-            crate::json_value::JsonValue::Number(n) => self.print(&n.to_string(), ColorCategory::Normal),
-            crate::json_value::JsonValue::String(s) => self.print(&format!("\"{}\"", s), ColorCategory::String),
-            crate::json_value::JsonValue::Array(arr) => self.print_array(arr),
-            crate::json_value::JsonValue::Object(names, members) => {
-                self.print_object(names, members)
+            JsonValue::Null => self.print("null", ColorCategory::Null),
+            JsonValue::Bool(b) => self.print(&b.to_string(), ColorCategory::Boolean),
+            JsonValue::Number(n) => self.print(&format_number(*n), ColorCategory::Number),
+            JsonValue::String(s) => self.print(&self.quote(s), ColorCategory::String),
+            JsonValue::Array(arr) => self.print_array(value, arr, suffix_width),
+            JsonValue::Object(names, members) => {
+                self.print_object(value, names, members, suffix_width)
             }
         }
     }
 
-    fn print_array(&mut self, arr: &Vec<crate::json_value::JsonValue>) {
-        if arr.len() == 0 {
-            self.print("[]", ColorCategory::Delimiter);
+    fn print_array(&mut self, value: &JsonValue, arr: &[JsonValue], suffix_width: usize) {
+        if arr.is_empty() {
+            self.print("[]", ColorCategory::Bracket);
+            return;
+        }
+
+        if self.fits_inline(value, suffix_width) {
+            self.print(self.open_bracket("["), ColorCategory::Bracket);
+            for (i, item) in arr.iter().enumerate() {
+                if i > 0 {
+                    self.print(self.inline_comma(), ColorCategory::Punctuation);
+                }
+                self.print_value(item, 0);
+            }
+            self.print(self.close_bracket("]"), ColorCategory::Bracket);
+            return;
+        }
+
+        if self.fill && self.max_width.is_some() && arr.iter().all(is_scalar) {
+            self.print_array_filled(arr);
             return;
         }
 
-        self.println("[", ColorCategory::Delimiter);
+        self.println("[", ColorCategory::Bracket);
         self.indent();
         for (i, item) in arr.iter().enumerate() {
-            self.print_value(&item);
             if i < arr.len() - 1 {
-                self.println(",", ColorCategory::Delimiter);
+                self.print_value(item, 1);
+                self.println(",", ColorCategory::Punctuation);
             } else {
-                self.println("", ColorCategory::Normal);
+                self.print_value(item, 0);
+                self.newline();
             }
         }
         self.dedent();
-        self.print("]", ColorCategory::Delimiter);
+        self.print("]", ColorCategory::Bracket);
+    }
+
+    fn print_array_filled(&mut self, arr: &[JsonValue]) {
+        let max_width = self.max_width.unwrap_or(usize::MAX);
+
+        self.println("[", ColorCategory::Bracket);
+        self.indent();
+        for (i, item) in arr.iter().enumerate() {
+            let is_last = i == arr.len() - 1;
+            if i > 0 {
+                let item_width = self.inline_width(item, usize::MAX).unwrap_or(0) + usize::from(!is_last);
+                if self.column + 1 + item_width <= max_width {
+                    self.print_plain(" ");
+                } else {
+                    self.newline();
+                }
+            }
+            self.print_value(item, 0);
+            if !is_last {
+                self.print(",", ColorCategory::Punctuation);
+            }
+        }
+        self.newline();
+        self.dedent();
+        self.print("]", ColorCategory::Bracket);
     }
 
     fn print_object(
         &mut self,
-        names: &Vec<String>,
-        members: &std::collections::HashMap<String, crate::json_value::JsonValue>,
+        value: &JsonValue,
+        names: &[String],
+        members: &HashMap<String, JsonValue>,
+        suffix_width: usize,
     ) {
-        if names.len() == 0 {
-            self.print("{}", ColorCategory::Delimiter);
+        if names.is_empty() {
+            self.print("{}", ColorCategory::Bracket);
             return;
         }
 
-        self.println("{", ColorCategory::Delimiter);
-        self.indent();
-        for (i, name) in names.iter().enumerate() {
-            self.print(&format!("\"{}\"", name), ColorCategory::Key);
-            self.print(": ", ColorCategory::Delimiter);
+        let inline = self.fits_inline(value, suffix_width);
+        if inline {
+            self.print(self.open_bracket("{"), ColorCategory::Bracket);
+        } else {
+            self.println("{", ColorCategory::Bracket);
+            self.indent();
+        }
+        let sorted_names = self.key_order.sort(names);
+        for (i, name) in sorted_names.into_iter().enumerate() {
+            let is_last = i == names.len() - 1;
+            if inline && i > 0 {
+                self.print(self.inline_comma(), ColorCategory::Punctuation);
+            }
+            self.print(&self.quote(name), ColorCategory::Key);
+            self.print(self.colon(), ColorCategory::Punctuation);
             if let Some(value) = members.get(name) {
-                self.print_value(value);
+                self.print_value(value, usize::from(!is_last));
             }
-            if i < names.len() - 1 {
-                self.println(",", ColorCategory::Delimiter);
+            if inline {
+                continue;
+            }
+            if !is_last {
+                self.println(",", ColorCategory::Punctuation);
             } else {
-                self.println("", ColorCategory::Delimiter);
+                self.newline();
             }
         }
-        self.dedent();
-        self.print("}", ColorCategory::Delimiter);
+        if inline {
+            self.print(self.close_bracket("}"), ColorCategory::Bracket);
+        } else {
+            self.dedent();
+            self.print("}", ColorCategory::Bracket);
+        }
+    }
+
+    /// Compact output is always printed inline, otherwise the value has to fit into the
+    /// remaining width of the current line.
+    fn fits_inline(&self, value: &JsonValue, suffix_width: usize) -> bool {
+        if self.compact {
+            return true;
+        }
+        let Some(max_width) = self.max_width else {
+            return false;
+        };
+        let column = if self.start_line { self.options.indent.width() * self.level } else { self.column };
+        let available = max_width.saturating_sub(column + suffix_width);
+        self.inline_width(value, available).is_some()
+    }
+
+    /// Returns the width of the value printed on a single line, or `None` if it exceeds `limit`.
+    fn inline_width(&self, value: &JsonValue, limit: usize) -> Option<usize> {
+        let brackets_width = 2 * self.open_bracket("[").len();
+        let comma_width = self.inline_comma().len();
+        let width = match value {
+            JsonValue::Null => 4,
+            JsonValue::Bool(b) => if *b { 4 } else { 5 },
+            JsonValue::Number(n) => format_number(*n).len(),
+            JsonValue::String(s) => self.quote(s).chars().count(),
+            JsonValue::Array(elements) if elements.is_empty() => 2,
+            JsonValue::Array(elements) => {
+                let mut width = brackets_width + comma_width * (elements.len() - 1);
+                for element in elements {
+                    width += self.inline_width(element, limit.checked_sub(width)?)?;
+                }
+                width
+            }
+            JsonValue::Object(names, _) if names.is_empty() => 2,
+            JsonValue::Object(names, members) => {
+                let mut width = brackets_width + comma_width * (names.len() - 1);
+                for name in names {
+                    width += self.quote(name).chars().count() + self.colon().len();
+                    if let Some(member) = members.get(name) {
+                        width += self.inline_width(member, limit.checked_sub(width)?)?;
+                    }
+                }
+                width
+            }
+        };
+        (width <= limit).then_some(width)
+    }
+
+    fn quote(&self, s: &str) -> String {
+        format!("\"{}\"", escape(s, self.options.ascii_only))
+    }
+
+    fn open_bracket(&self, bracket: &'static str) -> &'static str {
+        match (bracket, self.options.space_in_brackets) {
+            ("[", true) => "[ ",
+            ("{", true) => "{ ",
+            _ => bracket,
+        }
+    }
+
+    fn close_bracket(&self, bracket: &'static str) -> &'static str {
+        match (bracket, self.options.space_in_brackets) {
+            ("]", true) => " ]",
+            ("}", true) => " }",
+            _ => bracket,
+        }
+    }
+
+    fn inline_comma(&self) -> &'static str {
+        if !self.compact || self.options.space_after_comma { ", " } else { "," }
+    }
+
+    fn colon(&self) -> &'static str {
+        match (self.options.space_before_colon, self.compact) {
+            (false, false) => ": ",
+            (false, true) => ":",
+            (true, false) => " : ",
+            (true, true) => " :",
+        }
     }
 
     fn print(&mut self, text: &str, category: ColorCategory) {
         let mut text = ColoredString::from(text);
         if self.use_colors {
-            text = match category {
-                ColorCategory::Key => text.blue(),
-                ColorCategory::Delimiter => text.yellow(),
-                ColorCategory::String => text.green(),
-                ColorCategory::Normal => text.normal(),
-            };
+            text = self.theme.style(category).apply(text);
         }
+        self.emit(text);
+    }
+
+    fn print_plain(&mut self, text: &str) {
+        self.emit(ColoredString::from(text));
+    }
 
+    fn emit(&mut self, text: ColoredString) {
         if self.start_line {
             self.start_line = false;
-            let indent_str = " ".repeat(self.indent_size * self.level);
+            let indent_str = self.options.indent.repeat(self.level);
+            self.column = self.options.indent.width() * self.level;
             self.output.push(ColoredString::from(indent_str));
-            self.output.push(text);
+        }
+
+        if text.ends_with('\n') {
+            self.column = 0;
         } else {
-            self.output.push(text);
+            self.column += text.chars().count();
         }
+        self.output.push(text);
     }
 
     fn println(&mut self, text: &str, category: ColorCategory) {
-        let mut line = String::from(text);
-        line.push('\n');
-        self.print(&line, category);
+        self.print(text, category);
+        self.newline();
+    }
+
+    fn newline(&mut self) {
+        self.print_plain(self.options.line_ending.as_str());
         self.start_line = true;
     }
 
@@ -169,14 +554,174 @@ mod tests {
         "city": "Hannover",
         "zip": "31000"
     }
-}"#;
+}
+"#;
 
         let pretty_json = pretty_print_json(json, 4, false).unwrap();
         let pretty_json: String = pretty_json.iter().map(|cs| cs.to_string()).collect();
 
         assert_eq!(pretty_json, expected_json);
     }
+
+    #[test]
+    fn test_pretty_print_sorted_keys() {
+        let json = r#"{"b":1,"a":{"d":true,"c":null},"c":[{"z":1,"y":2}]}"#;
+        let expected_json = r#"{
+  "a": {
+    "c": null,
+    "d": true
+  },
+  "b": 1,
+  "c": [
+    {
+      "y": 2,
+      "z": 1
+    }
+  ]
 }
+"#;
 
+        let json_value = crate::parser::parse_json(json).unwrap();
+        let mut printer = PrettyPrinter::new(2, false).with_key_order(KeyOrder::Lexicographic);
+        let pretty_json: String = printer.pretty_print(&json_value).iter().map(|cs| cs.to_string()).collect();
 
+        assert_eq!(pretty_json, expected_json);
+    }
+
+    #[test]
+    fn test_pretty_print_custom_key_order() {
+        let json = r#"{"a":1,"bb":2,"ccc":3}"#;
+        let json_value = crate::parser::parse_json(json).unwrap();
+        let mut printer = PrettyPrinter::new(0, false)
+            .with_key_order(KeyOrder::Custom(Box::new(|a, b| b.len().cmp(&a.len()))));
+        let pretty_json: String = printer.pretty_print(&json_value).iter().map(|cs| cs.to_string()).collect();
+
+        assert_eq!(pretty_json, "{\n\"ccc\": 3,\n\"bb\": 2,\n\"a\": 1\n}\n");
+    }
 
+    #[test]
+    fn test_natural_cmp() {
+        let mut names = vec!["item10", "item2", "item1", "Item3", "item02b", "item2a"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(names, vec!["Item3", "item1", "item2", "item2a", "item02b", "item10"]);
+    }
+
+    #[test]
+    fn test_pretty_print_max_width() {
+        let json = r#"{"name":"Thomas","courses":["Math","Physics","Computer Science"],"point":{"x":1,"y":2}}"#;
+        let expected_json = r#"{
+  "name": "Thomas",
+  "courses": [
+    "Math",
+    "Physics",
+    "Computer Science"
+  ],
+  "point": {"x": 1, "y": 2}
+}
+"#;
+
+        let json_value = crate::parser::parse_json(json).unwrap();
+        let mut printer = PrettyPrinter::new(2, false).with_max_width(Some(30));
+        let pretty_json: String = printer.pretty_print(&json_value).iter().map(|cs| cs.to_string()).collect();
+
+        assert_eq!(pretty_json, expected_json);
+
+        let expected_json = r#"{"name": "Thomas", "courses": ["Math", "Physics", "Computer Science"], "point": {"x": 1, "y": 2}}
+"#;
+        let mut printer = PrettyPrinter::new(2, false).with_max_width(Some(100));
+        let pretty_json: String = printer.pretty_print(&json_value).iter().map(|cs| cs.to_string()).collect();
+
+        assert_eq!(pretty_json, expected_json);
+    }
+
+    #[test]
+    fn test_pretty_print_fill() {
+        let json = "[1,2,3,4,5,6,7,8,9,10,11,12,[]]";
+        let expected_json = r#"[
+  1, 2, 3, 4, 5, 6, 7,
+  8, 9, 10, 11, 12, []
+]
+"#;
+
+        let json_value = crate::parser::parse_json(json).unwrap();
+        let mut printer = PrettyPrinter::new(2, false).with_max_width(Some(22)).with_fill(true);
+        let pretty_json: String = printer.pretty_print(&json_value).iter().map(|cs| cs.to_string()).collect();
+
+        assert_eq!(pretty_json, expected_json);
+    }
+
+    #[test]
+    fn test_pretty_print_format_options() {
+        let json = r#"{"a":[1,{"b":null}]}"#;
+        let options = FormatOptions {
+            indent: Indent::Tab,
+            space_before_colon: true,
+            line_ending: crate::format_options::LineEnding::CrLf,
+            final_newline: false,
+            ..FormatOptions::default()
+        };
+
+        let json_value = crate::parser::parse_json(json).unwrap();
+        let mut printer = PrettyPrinter::new(4, false).with_format_options(options);
+        let pretty_json: String = printer.pretty_print(&json_value).iter().map(|cs| cs.to_string()).collect();
+
+        assert_eq!(pretty_json, "{\r\n\t\"a\" : [\r\n\t\t1,\r\n\t\t{\r\n\t\t\t\"b\" : null\r\n\t\t}\r\n\t]\r\n}");
+    }
+
+    #[test]
+    fn test_pretty_print_compact() {
+        let json = r#"{"a": [1, {"b": null}], "c": []}"#;
+        let json_value = crate::parser::parse_json(json).unwrap();
+
+        let mut printer = PrettyPrinter::new(4, false).with_compact(true);
+        let pretty_json: String = printer.pretty_print(&json_value).iter().map(|cs| cs.to_string()).collect();
+        assert_eq!(pretty_json, "{\"a\":[1,{\"b\":null}],\"c\":[]}\n");
+
+        let options = FormatOptions {
+            space_in_brackets: true,
+            space_after_comma: true,
+            ..FormatOptions::default()
+        };
+        let mut printer = PrettyPrinter::new(4, false).with_format_options(options).with_compact(true);
+        let pretty_json: String = printer.pretty_print(&json_value).iter().map(|cs| cs.to_string()).collect();
+        assert_eq!(pretty_json, "{ \"a\":[ 1, { \"b\":null } ], \"c\":[] }\n");
+    }
+
+    #[test]
+    fn test_pretty_print_theme() {
+        colored::control::set_override(true);
+
+        let mut theme = Theme::default();
+        theme.apply_spec("null=red:bool=green:number=blue:string=yellow:key=cyan:bracket=magenta:punctuation=white").unwrap();
+        let json_value = crate::parser::parse_json(r#"{"a": [1, true, null, "s"]}"#).unwrap();
+        let mut printer = PrettyPrinter::new(4, true).with_theme(theme).with_compact(true);
+        let pretty_json: String = printer.pretty_print(&json_value).iter().map(|cs| cs.to_string()).collect();
+
+        assert_eq!(
+            pretty_json,
+            "\x1b[35m{\x1b[0m\x1b[36m\"a\"\x1b[0m\x1b[37m:\x1b[0m\x1b[35m[\x1b[0m\x1b[34m1\x1b[0m\x1b[37m,\x1b[0m\
+             \x1b[32mtrue\x1b[0m\x1b[37m,\x1b[0m\x1b[31mnull\x1b[0m\x1b[37m,\x1b[0m\x1b[33m\"s\"\x1b[0m\x1b[35m]\x1b[0m\x1b[35m}\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn test_escape_string_ascii() {
+        assert_eq!(escape_string_ascii("a\"é€😀\n"), "a\\\"\\u00e9\\u20ac\\ud83d\\ude00\\n");
+        assert_eq!(escape_string("a\"é€😀\n"), "a\\\"é€😀\\n");
+    }
+
+    #[test]
+    fn test_pretty_print_ascii_only() {
+        // Escape sequences of the input are decoded, so without ascii_only they are printed as UTF-8
+        let json_value = crate::parser::parse_json(r#"{"Gr\u00fc\u00dfe": ["caf\u00e9", "😀"]}"#).unwrap();
+
+        let mut printer = PrettyPrinter::new(4, false).with_compact(true);
+        let pretty_json: String = printer.pretty_print(&json_value).iter().map(|cs| cs.to_string()).collect();
+        assert_eq!(pretty_json, "{\"Grüße\":[\"café\",\"😀\"]}\n");
+
+        let options = FormatOptions { ascii_only: true, ..FormatOptions::default() };
+        let mut printer = PrettyPrinter::new(4, false).with_format_options(options).with_compact(true);
+        let pretty_json: String = printer.pretty_print(&json_value).iter().map(|cs| cs.to_string()).collect();
+        assert_eq!(pretty_json, r#"{"Gr\u00fc\u00dfe":["caf\u00e9","\ud83d\ude00"]}"#.to_string() + "\n");
+    }
+}
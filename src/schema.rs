@@ -0,0 +1,832 @@
+//! Validation of JSON documents against JSON Schema (draft 2020-12)
+//!
+//! Supported are the type, enum and const keywords, the numeric, string, array and object
+//! assertions, properties, patternProperties, additionalProperties, prefixItems, items,
+//! contains, the logical applicators allOf, anyOf, oneOf and not, format assertions for
+//! common formats, and `$ref` to `$defs`, anchors or any JSON pointer within the schema.
+
+mod infer;
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use anyhow::{Result, anyhow};
+use regex::Regex;
+use crate::json_value::{json_equal, JsonValue};
+use crate::pointer::{escape_token, to_pointer};
+use crate::pretty_printer::{format_number, to_compact_string};
+
+pub use infer::{infer_schema, InferOptions, DRAFT_2020_12};
+
+/// A compiled JSON schema
+#[derive(Debug, Clone)]
+pub struct Schema {
+    /// Subschemas by the JSON pointer to their location in the schema document
+    nodes: HashMap<String, Node>,
+}
+
+/// A violation of a schema keyword
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    /// JSON pointer to the invalid value in the instance
+    pub instance_path: String,
+    /// JSON pointer to the violated keyword in the schema
+    pub schema_path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}: {} (schema: #{})", self.instance_path, self.message, self.schema_path)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Bool(bool),
+    Keywords(Vec<(String, Keyword)>),
+}
+
+/// A keyword with its arguments; subschemas are referred to by their location.
+#[derive(Debug, Clone)]
+enum Keyword {
+    Type(Vec<InstanceType>),
+    Enum(Vec<JsonValue>),
+    Const(JsonValue),
+    MultipleOf(f64),
+    Minimum(f64),
+    ExclusiveMinimum(f64),
+    Maximum(f64),
+    ExclusiveMaximum(f64),
+    MinLength(usize),
+    MaxLength(usize),
+    Pattern(Regex),
+    Format(String),
+    MinItems(usize),
+    MaxItems(usize),
+    UniqueItems,
+    PrefixItems(Vec<String>),
+    Items { schema: String, skip: usize },
+    Contains { schema: String, min: usize, max: Option<usize> },
+    MinProperties(usize),
+    MaxProperties(usize),
+    Required(Vec<String>),
+    Properties(Vec<(String, String)>),
+    PatternProperties(Vec<(Regex, String)>),
+    AdditionalProperties { schema: String, properties: Vec<String>, patterns: Vec<Regex> },
+    AllOf(Vec<String>),
+    AnyOf(Vec<String>),
+    OneOf(Vec<String>),
+    Not(String),
+    Ref(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum InstanceType {
+    Null,
+    Boolean,
+    Object,
+    Array,
+    Number,
+    Integer,
+    String,
+}
+
+impl InstanceType {
+    fn from_name(name: &str) -> Option<Self> {
+        let instance_type = match name {
+            "null" => InstanceType::Null,
+            "boolean" => InstanceType::Boolean,
+            "object" => InstanceType::Object,
+            "array" => InstanceType::Array,
+            "number" => InstanceType::Number,
+            "integer" => InstanceType::Integer,
+            "string" => InstanceType::String,
+            _ => return None,
+        };
+        Some(instance_type)
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            InstanceType::Null => "null",
+            InstanceType::Boolean => "boolean",
+            InstanceType::Object => "object",
+            InstanceType::Array => "array",
+            InstanceType::Number => "number",
+            InstanceType::Integer => "integer",
+            InstanceType::String => "string",
+        }
+    }
+
+    fn of(value: &JsonValue) -> Self {
+        match value {
+            JsonValue::Null => InstanceType::Null,
+            JsonValue::Bool(_) => InstanceType::Boolean,
+            JsonValue::Number(n) if n.fract() == 0.0 => InstanceType::Integer,
+            JsonValue::Number(_) => InstanceType::Number,
+            JsonValue::String(_) => InstanceType::String,
+            JsonValue::Array(_) => InstanceType::Array,
+            JsonValue::Object(_, _) => InstanceType::Object,
+        }
+    }
+
+    fn matches(&self, value: &JsonValue) -> bool {
+        let actual = InstanceType::of(value);
+        *self == actual || (*self == InstanceType::Number && actual == InstanceType::Integer)
+    }
+}
+
+impl Schema {
+    /// Compiles a schema document. Fails if a keyword has an invalid value, a pattern is not
+    /// a valid regular expression or a `$ref` cannot be resolved within the document.
+    pub fn compile(schema: &JsonValue) -> Result<Self> {
+        let mut compiler = Compiler {
+            nodes: HashMap::new(),
+            anchors: HashMap::new(),
+        };
+        compiler.compile(schema, String::new())?;
+        compiler.resolve_refs()?;
+        Ok(Schema { nodes: compiler.nodes })
+    }
+
+    /// Validates an instance and returns all violations; the result is empty if it is valid.
+    pub fn validate(&self, instance: &JsonValue) -> Vec<ValidationError> {
+        let mut validator = Validator {
+            schema: self,
+            instance_path: Vec::new(),
+            active_refs: Vec::new(),
+        };
+        let mut errors = Vec::new();
+        validator.validate("", instance, &mut errors);
+        errors
+    }
+
+    pub fn is_valid(&self, instance: &JsonValue) -> bool {
+        self.validate(instance).is_empty()
+    }
+}
+
+struct Compiler {
+    nodes: HashMap<String, Node>,
+    anchors: HashMap<String, String>,
+}
+
+impl Compiler {
+    fn compile(&mut self, schema: &JsonValue, location: String) -> Result<()> {
+        let (names, members) = match schema {
+            JsonValue::Bool(b) => {
+                self.nodes.insert(location, Node::Bool(*b));
+                return Ok(());
+            }
+            JsonValue::Object(names, members) => (names, members),
+            _ => return Err(invalid(&location, "A schema must be an object or a boolean")),
+        };
+
+        let mut keywords = Vec::new();
+        for name in names {
+            let value = &members[name];
+            let keyword_location = format!("{}/{}", location, escape_token(name));
+            let keyword = match name.as_str() {
+                "$defs" | "definitions" => {
+                    let JsonValue::Object(def_names, defs) = value else {
+                        return Err(invalid(&keyword_location, "Expected an object of schemas"));
+                    };
+                    for def_name in def_names {
+                        self.compile(&defs[def_name], format!("{}/{}", keyword_location, escape_token(def_name)))?;
+                    }
+                    continue;
+                }
+                "$anchor" => {
+                    let anchor = as_string(value, &keyword_location)?;
+                    self.anchors.insert(anchor.to_string(), location.clone());
+                    continue;
+                }
+                "$ref" => Keyword::Ref(as_string(value, &keyword_location)?.to_string()),
+                "type" => {
+                    let names = match value {
+                        JsonValue::Array(names) => names.iter().collect(),
+                        _ => vec![value],
+                    };
+                    let types = names
+                        .into_iter()
+                        .map(|name| match name {
+                            JsonValue::String(name) => InstanceType::from_name(name),
+                            _ => None,
+                        })
+                        .collect::<Option<_>>()
+                        .ok_or_else(|| invalid(&keyword_location, "Expected a type name or an array of type names"))?;
+                    Keyword::Type(types)
+                }
+                "enum" => match value {
+                    JsonValue::Array(values) => Keyword::Enum(values.clone()),
+                    _ => return Err(invalid(&keyword_location, "Expected an array")),
+                },
+                "const" => Keyword::Const(value.clone()),
+                "multipleOf" => {
+                    let divisor = as_number(value, &keyword_location)?;
+                    if divisor <= 0.0 {
+                        return Err(invalid(&keyword_location, "Expected a number greater than 0"));
+                    }
+                    Keyword::MultipleOf(divisor)
+                }
+                "minimum" => Keyword::Minimum(as_number(value, &keyword_location)?),
+                "exclusiveMinimum" => Keyword::ExclusiveMinimum(as_number(value, &keyword_location)?),
+                "maximum" => Keyword::Maximum(as_number(value, &keyword_location)?),
+                "exclusiveMaximum" => Keyword::ExclusiveMaximum(as_number(value, &keyword_location)?),
+                "minLength" => Keyword::MinLength(as_count(value, &keyword_location)?),
+                "maxLength" => Keyword::MaxLength(as_count(value, &keyword_location)?),
+                "pattern" => Keyword::Pattern(as_regex(value, &keyword_location)?),
+                "format" => Keyword::Format(as_string(value, &keyword_location)?.to_string()),
+                "minItems" => Keyword::MinItems(as_count(value, &keyword_location)?),
+                "maxItems" => Keyword::MaxItems(as_count(value, &keyword_location)?),
+                "uniqueItems" => match value {
+                    JsonValue::Bool(true) => Keyword::UniqueItems,
+                    JsonValue::Bool(false) => continue,
+                    _ => return Err(invalid(&keyword_location, "Expected a boolean")),
+                },
+                "prefixItems" => Keyword::PrefixItems(self.compile_array(value, &keyword_location)?),
+                "items" => {
+                    self.compile(value, keyword_location.clone())?;
+                    let skip = match members.get("prefixItems") {
+                        Some(JsonValue::Array(prefix)) => prefix.len(),
+                        _ => 0,
+                    };
+                    Keyword::Items { schema: keyword_location.clone(), skip }
+                }
+                "contains" => {
+                    self.compile(value, keyword_location.clone())?;
+                    let count = |name: &str| {
+                        members.get(name).map(|value| as_count(value, &format!("{}/{}", location, name))).transpose()
+                    };
+                    Keyword::Contains {
+                        schema: keyword_location.clone(),
+                        min: count("minContains")?.unwrap_or(1),
+                        max: count("maxContains")?,
+                    }
+                }
+                "minProperties" => Keyword::MinProperties(as_count(value, &keyword_location)?),
+                "maxProperties" => Keyword::MaxProperties(as_count(value, &keyword_location)?),
+                "required" => {
+                    let required = match value {
+                        JsonValue::Array(names) => names
+                            .iter()
+                            .map(|name| match name {
+                                JsonValue::String(name) => Some(name.clone()),
+                                _ => None,
+                            })
+                            .collect::<Option<_>>(),
+                        _ => None,
+                    };
+                    Keyword::Required(required.ok_or_else(|| invalid(&keyword_location, "Expected an array of strings"))?)
+                }
+                "properties" => {
+                    let JsonValue::Object(property_names, properties) = value else {
+                        return Err(invalid(&keyword_location, "Expected an object of schemas"));
+                    };
+                    let mut compiled = Vec::new();
+                    for property_name in property_names {
+                        let property_location = format!("{}/{}", keyword_location, escape_token(property_name));
+                        self.compile(&properties[property_name], property_location.clone())?;
+                        compiled.push((property_name.clone(), property_location));
+                    }
+                    Keyword::Properties(compiled)
+                }
+                "patternProperties" => {
+                    let JsonValue::Object(patterns, properties) = value else {
+                        return Err(invalid(&keyword_location, "Expected an object of schemas"));
+                    };
+                    let mut compiled = Vec::new();
+                    for pattern in patterns {
+                        let property_location = format!("{}/{}", keyword_location, escape_token(pattern));
+                        let regex = Regex::new(pattern)
+                            .map_err(|_| invalid(&property_location, "Invalid regular expression"))?;
+                        self.compile(&properties[pattern], property_location.clone())?;
+                        compiled.push((regex, property_location));
+                    }
+                    Keyword::PatternProperties(compiled)
+                }
+                "additionalProperties" => {
+                    self.compile(value, keyword_location.clone())?;
+                    let properties = match members.get("properties") {
+                        Some(JsonValue::Object(property_names, _)) => property_names.clone(),
+                        _ => Vec::new(),
+                    };
+                    let patterns = match members.get("patternProperties") {
+                        Some(JsonValue::Object(patterns, _)) => {
+                            patterns.iter().filter_map(|pattern| Regex::new(pattern).ok()).collect()
+                        }
+                        _ => Vec::new(),
+                    };
+                    Keyword::AdditionalProperties { schema: keyword_location.clone(), properties, patterns }
+                }
+                "allOf" => Keyword::AllOf(self.compile_array(value, &keyword_location)?),
+                "anyOf" => Keyword::AnyOf(self.compile_array(value, &keyword_location)?),
+                "oneOf" => Keyword::OneOf(self.compile_array(value, &keyword_location)?),
+                "not" => {
+                    self.compile(value, keyword_location.clone())?;
+                    Keyword::Not(keyword_location.clone())
+                }
+                // Annotations and unsupported keywords
+                _ => continue,
+            };
+            keywords.push((keyword_location, keyword));
+        }
+
+        self.nodes.insert(location, Node::Keywords(keywords));
+        Ok(())
+    }
+
+    fn compile_array(&mut self, value: &JsonValue, location: &str) -> Result<Vec<String>> {
+        let JsonValue::Array(schemas) = value else {
+            return Err(invalid(location, "Expected an array of schemas"));
+        };
+        if schemas.is_empty() {
+            return Err(invalid(location, "Expected at least one schema"));
+        }
+        let mut locations = Vec::new();
+        for (index, schema) in schemas.iter().enumerate() {
+            let schema_location = format!("{}/{}", location, index);
+            self.compile(schema, schema_location.clone())?;
+            locations.push(schema_location);
+        }
+        Ok(locations)
+    }
+
+    /// Replaces the references of all `$ref` keywords by the location of their target.
+    fn resolve_refs(&mut self) -> Result<()> {
+        let locations: HashSet<String> = self.nodes.keys().cloned().collect();
+        for node in self.nodes.values_mut() {
+            let Node::Keywords(keywords) = node else {
+                continue;
+            };
+            for (keyword_location, keyword) in keywords {
+                let Keyword::Ref(reference) = keyword else {
+                    continue;
+                };
+                let Some(fragment) = reference.strip_prefix('#') else {
+                    return Err(invalid(keyword_location, "Only references within the schema ('#...') are supported"));
+                };
+                let target = if fragment.is_empty() || fragment.starts_with('/') {
+                    percent_decode(fragment).filter(|target| locations.contains(target))
+                } else {
+                    self.anchors.get(fragment).cloned()
+                };
+                *reference = target.ok_or_else(|| invalid(keyword_location, &format!("Cannot resolve reference '{}'", reference)))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Validator<'a> {
+    schema: &'a Schema,
+    instance_path: Vec<String>,
+    /// References being followed with the instance they are applied to, to stop cycles
+    active_refs: Vec<(String, Vec<String>)>,
+}
+
+impl Validator<'_> {
+    fn validate(&mut self, location: &str, instance: &JsonValue, errors: &mut Vec<ValidationError>) {
+        let keywords = match &self.schema.nodes[location] {
+            Node::Bool(true) => return,
+            Node::Bool(false) => {
+                errors.push(self.error(location, "No value is allowed here".to_string()));
+                return;
+            }
+            Node::Keywords(keywords) => keywords,
+        };
+        for (keyword_location, keyword) in keywords {
+            self.validate_keyword(keyword_location, keyword, instance, errors);
+        }
+    }
+
+    fn validate_keyword(&mut self, location: &str, keyword: &Keyword, instance: &JsonValue, errors: &mut Vec<ValidationError>) {
+        let mut fail = |validator: &Self, message: String| errors.push(validator.error(location, message));
+
+        match (keyword, instance) {
+            (Keyword::Type(types), _) if !types.iter().any(|t| t.matches(instance)) => {
+                let expected: Vec<&str> = types.iter().map(InstanceType::name).collect();
+                let actual = match InstanceType::of(instance) {
+                    InstanceType::Integer => InstanceType::Number,
+                    actual => actual,
+                };
+                fail(self, format!("Expected {}, found {}", expected.join(" or "), actual.name()));
+            }
+            (Keyword::Enum(values), _) if !values.iter().any(|value| json_equal(value, instance)) => {
+                let allowed: Vec<String> = values.iter().map(to_compact_string).collect();
+                fail(self, format!("Value must be one of {}", allowed.join(", ")));
+            }
+            (Keyword::Const(value), _) if !json_equal(value, instance) => {
+                fail(self, format!("Value must be {}", to_compact_string(value)));
+            }
+            (Keyword::MultipleOf(divisor), JsonValue::Number(n)) => {
+                let quotient = n / divisor;
+                if quotient.is_finite() && (quotient - quotient.round()).abs() > 1e-9 {
+                    fail(self, format!("{} is not a multiple of {}", format_number(*n), format_number(*divisor)));
+                }
+            }
+            (Keyword::Minimum(minimum), JsonValue::Number(n)) if n < minimum => {
+                fail(self, format!("{} is less than the minimum of {}", format_number(*n), format_number(*minimum)));
+            }
+            (Keyword::ExclusiveMinimum(minimum), JsonValue::Number(n)) if n <= minimum => {
+                fail(self, format!("{} is not greater than {}", format_number(*n), format_number(*minimum)));
+            }
+            (Keyword::Maximum(maximum), JsonValue::Number(n)) if n > maximum => {
+                fail(self, format!("{} is greater than the maximum of {}", format_number(*n), format_number(*maximum)));
+            }
+            (Keyword::ExclusiveMaximum(maximum), JsonValue::Number(n)) if n >= maximum => {
+                fail(self, format!("{} is not less than {}", format_number(*n), format_number(*maximum)));
+            }
+            (Keyword::MinLength(min), JsonValue::String(s)) if s.chars().count() < *min => {
+                fail(self, format!("String is shorter than {} characters", min));
+            }
+            (Keyword::MaxLength(max), JsonValue::String(s)) if s.chars().count() > *max => {
+                fail(self, format!("String is longer than {} characters", max));
+            }
+            (Keyword::Pattern(regex), JsonValue::String(s)) if !regex.is_match(s) => {
+                fail(self, format!("String does not match the pattern '{}'", regex.as_str()));
+            }
+            (Keyword::Format(format), JsonValue::String(s)) if check_format(format, s) == Some(false) => {
+                fail(self, format!("String is not a valid {}", format));
+            }
+            (Keyword::MinItems(min), JsonValue::Array(elements)) if elements.len() < *min => {
+                fail(self, format!("Array has fewer than {} items", min));
+            }
+            (Keyword::MaxItems(max), JsonValue::Array(elements)) if elements.len() > *max => {
+                fail(self, format!("Array has more than {} items", max));
+            }
+            (Keyword::UniqueItems, JsonValue::Array(elements)) => {
+                let duplicate = (0..elements.len())
+                    .flat_map(|i| (i + 1..elements.len()).map(move |j| (i, j)))
+                    .find(|&(i, j)| json_equal(&elements[i], &elements[j]));
+                if let Some((i, j)) = duplicate {
+                    fail(self, format!("Array items {} and {} are equal", i, j));
+                }
+            }
+            (Keyword::PrefixItems(schemas), JsonValue::Array(elements)) => {
+                for (index, (schema, element)) in schemas.iter().zip(elements).enumerate() {
+                    self.validate_child(schema, index.to_string(), element, errors);
+                }
+            }
+            (Keyword::Items { schema, skip }, JsonValue::Array(elements)) => {
+                for (index, element) in elements.iter().enumerate().skip(*skip) {
+                    self.validate_child(schema, index.to_string(), element, errors);
+                }
+            }
+            (Keyword::Contains { schema, min, max }, JsonValue::Array(elements)) => {
+                let count = elements.iter().filter(|element| self.matches(schema, element)).count();
+                if count < *min {
+                    fail(self, format!("Array contains {} matching items, expected at least {}", count, min));
+                } else if max.is_some_and(|max| count > max) {
+                    fail(self, format!("Array contains {} matching items, expected at most {}", count, max.unwrap_or_default()));
+                }
+            }
+            (Keyword::MinProperties(min), JsonValue::Object(names, _)) if names.len() < *min => {
+                fail(self, format!("Object has fewer than {} properties", min));
+            }
+            (Keyword::MaxProperties(max), JsonValue::Object(names, _)) if names.len() > *max => {
+                fail(self, format!("Object has more than {} properties", max));
+            }
+            (Keyword::Required(required), JsonValue::Object(_, members)) => {
+                for name in required.iter().filter(|name| !members.contains_key(*name)) {
+                    fail(self, format!("Missing required property '{}'", name));
+                }
+            }
+            (Keyword::Properties(properties), JsonValue::Object(_, members)) => {
+                for (name, schema) in properties {
+                    if let Some(member) = members.get(name) {
+                        self.validate_child(schema, name.clone(), member, errors);
+                    }
+                }
+            }
+            (Keyword::PatternProperties(patterns), JsonValue::Object(names, members)) => {
+                for (regex, schema) in patterns {
+                    for name in names.iter().filter(|name| regex.is_match(name)) {
+                        self.validate_child(schema, name.clone(), &members[name], errors);
+                    }
+                }
+            }
+            (Keyword::AdditionalProperties { schema, properties, patterns }, JsonValue::Object(names, members)) => {
+                let additional = names
+                    .iter()
+                    .filter(|name| !properties.contains(name) && !patterns.iter().any(|regex| regex.is_match(name)));
+                for name in additional {
+                    if matches!(self.schema.nodes[schema], Node::Bool(false)) {
+                        errors.push(self.error(location, format!("Property '{}' is not allowed", name)));
+                    } else {
+                        self.validate_child(schema, name.clone(), &members[name], errors);
+                    }
+                }
+            }
+            (Keyword::AllOf(schemas), _) => {
+                for schema in schemas {
+                    self.validate(schema, instance, errors);
+                }
+            }
+            (Keyword::AnyOf(schemas), _) if !schemas.iter().any(|schema| self.matches(schema, instance)) => {
+                fail(self, "Value does not match any schema in anyOf".to_string());
+            }
+            (Keyword::OneOf(schemas), _) => {
+                let matching = schemas.iter().filter(|schema| self.matches(schema, instance)).count();
+                if matching != 1 {
+                    fail(self, format!("Value matches {} schemas in oneOf, expected exactly one", matching));
+                }
+            }
+            (Keyword::Not(schema), _) if self.matches(schema, instance) => {
+                fail(self, "Value must not match the schema in not".to_string());
+            }
+            (Keyword::Ref(target), _) => {
+                let active = (target.clone(), self.instance_path.clone());
+                if self.active_refs.contains(&active) {
+                    return;
+                }
+                self.active_refs.push(active);
+                self.validate(target, instance, errors);
+                self.active_refs.pop();
+            }
+            // Keywords that do not apply to the type of the instance
+            _ => (),
+        }
+    }
+
+    fn validate_child(&mut self, location: &str, token: String, instance: &JsonValue, errors: &mut Vec<ValidationError>) {
+        self.instance_path.push(token);
+        self.validate(location, instance, errors);
+        self.instance_path.pop();
+    }
+
+    fn matches(&mut self, location: &str, instance: &JsonValue) -> bool {
+        let mut errors = Vec::new();
+        self.validate(location, instance, &mut errors);
+        errors.is_empty()
+    }
+
+    fn error(&self, location: &str, message: String) -> ValidationError {
+        ValidationError {
+            instance_path: to_pointer(&self.instance_path),
+            schema_path: location.to_string(),
+            message,
+        }
+    }
+}
+
+/// Checks a string against a format; returns None for unknown formats, which are not asserted.
+fn check_format(format: &str, s: &str) -> Option<bool> {
+    let matches = |pattern: &str| Regex::new(pattern).is_ok_and(|regex| regex.is_match(s));
+    let valid = match format {
+        "date" => is_date(s),
+        "time" => is_time(s),
+        "date-time" => match s.find(['T', 't']) {
+            Some(index) => is_date(&s[..index]) && is_time(&s[index + 1..]),
+            None => false,
+        },
+        "email" => matches(r"^[^@\s]+@[^@\s]+\.[^@\s]+$"),
+        "hostname" => {
+            s.len() <= 253 && matches(r"^[A-Za-z0-9]([A-Za-z0-9-]{0,61}[A-Za-z0-9])?(\.[A-Za-z0-9]([A-Za-z0-9-]{0,61}[A-Za-z0-9])?)*$")
+        }
+        "ipv4" => s.parse::<Ipv4Addr>().is_ok(),
+        "ipv6" => s.parse::<Ipv6Addr>().is_ok(),
+        "uri" => matches(r"^[A-Za-z][A-Za-z0-9+.-]*:[^\s]*$"),
+        "uuid" => matches(r"^[0-9A-Fa-f]{8}-[0-9A-Fa-f]{4}-[0-9A-Fa-f]{4}-[0-9A-Fa-f]{4}-[0-9A-Fa-f]{12}$"),
+        "regex" => Regex::new(s).is_ok(),
+        _ => return None,
+    };
+    Some(valid)
+}
+
+/// Full date according to RFC 3339, e.g. 2024-02-29
+fn is_date(s: &str) -> bool {
+    let Some(captures) = Regex::new(r"^(\d{4})-(\d{2})-(\d{2})$").ok().and_then(|regex| regex.captures(s)) else {
+        return false;
+    };
+    let number = |index: usize| captures[index].parse::<u32>().unwrap_or_default();
+    let (year, month, day) = (number(1), number(2), number(3));
+    let leap_year = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap_year => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days_in_month).contains(&day)
+}
+
+/// Full time with offset according to RFC 3339, e.g. 12:30:00.5+02:00
+fn is_time(s: &str) -> bool {
+    let pattern = r"^(\d{2}):(\d{2}):(\d{2})(\.\d+)?([Zz]|[+-](\d{2}):(\d{2}))$";
+    let Some(captures) = Regex::new(pattern).ok().and_then(|regex| regex.captures(s)) else {
+        return false;
+    };
+    let number = |index: usize| captures.get(index).map_or(0, |m| m.as_str().parse::<u32>().unwrap_or_default());
+    number(1) < 24 && number(2) < 60 && number(3) <= 60 && number(6) < 24 && number(7) < 60
+}
+
+fn percent_decode(s: &str) -> Option<String> {
+    let mut bytes = Vec::new();
+    let mut chars = s.bytes();
+    while let Some(b) = chars.next() {
+        if b == b'%' {
+            let hex = [chars.next()?, chars.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(b);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+fn invalid(location: &str, message: &str) -> anyhow::Error {
+    anyhow!("Invalid schema at #{}: {}", location, message)
+}
+
+fn as_string<'a>(value: &'a JsonValue, location: &str) -> Result<&'a str> {
+    match value {
+        JsonValue::String(s) => Ok(s),
+        _ => Err(invalid(location, "Expected a string")),
+    }
+}
+
+fn as_number(value: &JsonValue, location: &str) -> Result<f64> {
+    match value {
+        JsonValue::Number(n) => Ok(*n),
+        _ => Err(invalid(location, "Expected a number")),
+    }
+}
+
+fn as_count(value: &JsonValue, location: &str) -> Result<usize> {
+    match value {
+        JsonValue::Number(n) if *n >= 0.0 && n.fract() == 0.0 => Ok(*n as usize),
+        _ => Err(invalid(location, "Expected a non-negative integer")),
+    }
+}
+
+fn as_regex(value: &JsonValue, location: &str) -> Result<Regex> {
+    Regex::new(as_string(value, location)?).map_err(|_| invalid(location, "Invalid regular expression"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    fn errors(schema: &str, instance: &str) -> Vec<String> {
+        let schema = Schema::compile(&parse_json(schema).unwrap()).unwrap();
+        schema.validate(&parse_json(instance).unwrap()).iter().map(|error| error.to_string()).collect()
+    }
+
+    fn assert_valid(schema: &str, instance: &str) {
+        assert_eq!(errors(schema, instance), Vec::<String>::new(), "{} should be valid", instance);
+    }
+
+    const PERSON: &str = r##"{
+        "type": "object",
+        "required": ["name", "age"],
+        "properties": {
+            "name": {"type": "string", "minLength": 1},
+            "age": {"type": "integer", "minimum": 0, "maximum": 150},
+            "email": {"type": "string", "format": "email"},
+            "tags": {"type": "array", "items": {"type": "string"}, "uniqueItems": true, "maxItems": 3},
+            "address": {"$ref": "#/$defs/address"}
+        },
+        "additionalProperties": false,
+        "$defs": {
+            "address": {
+                "type": "object",
+                "properties": {"zip": {"type": "string", "pattern": "^[0-9]{5}$"}}
+            }
+        }
+    }"##;
+
+    #[test]
+    fn test_object_keywords() {
+        assert_valid(PERSON, r#"{"name": "Ann", "age": 42, "tags": ["a", "b"], "address": {"zip": "12345"}}"#);
+        assert_eq!(errors(PERSON, r#"{"name": "", "age": 42.5, "tags": ["a", 1, "a", "b"], "address": {"zip": "123"}, "x": 1}"#), vec![
+            "#/name: String is shorter than 1 characters (schema: #/properties/name/minLength)",
+            "#/age: Expected integer, found number (schema: #/properties/age/type)",
+            "#/tags/1: Expected string, found number (schema: #/properties/tags/items/type)",
+            "#/tags: Array items 0 and 2 are equal (schema: #/properties/tags/uniqueItems)",
+            "#/tags: Array has more than 3 items (schema: #/properties/tags/maxItems)",
+            "#/address/zip: String does not match the pattern '^[0-9]{5}$' (schema: #/$defs/address/properties/zip/pattern)",
+            "#: Property 'x' is not allowed (schema: #/additionalProperties)",
+        ]);
+        assert_eq!(errors(PERSON, r#"{"email": "nobody"}"#), vec![
+            "#: Missing required property 'name' (schema: #/required)",
+            "#: Missing required property 'age' (schema: #/required)",
+            "#/email: String is not a valid email (schema: #/properties/email/format)",
+        ]);
+        assert_eq!(errors(PERSON, "[]"), vec!["#: Expected object, found array (schema: #/type)"]);
+
+        let schema = r#"{"patternProperties": {"^x-": {"type": "number"}}, "additionalProperties": {"type": "string"},
+            "minProperties": 1}"#;
+        assert_valid(schema, r#"{"x-a": 1, "b": "c"}"#);
+        assert_eq!(errors(schema, r#"{"x-a": "1", "b": 2}"#), vec![
+            "#/x-a: Expected number, found string (schema: #/patternProperties/^x-/type)",
+            "#/b: Expected string, found number (schema: #/additionalProperties/type)",
+        ]);
+        assert_eq!(errors(schema, "{}"), vec!["#: Object has fewer than 1 properties (schema: #/minProperties)"]);
+    }
+
+    #[test]
+    fn test_values() {
+        let schema = r#"{"type": ["number", "null"], "exclusiveMinimum": 0, "exclusiveMaximum": 10, "multipleOf": 0.5}"#;
+        assert_valid(schema, "null");
+        assert_valid(schema, "9.5");
+        assert_eq!(errors(schema, "0"), vec!["#: 0 is not greater than 0 (schema: #/exclusiveMinimum)"]);
+        assert_eq!(errors(schema, "10.25"), vec![
+            "#: 10.25 is not less than 10 (schema: #/exclusiveMaximum)",
+            "#: 10.25 is not a multiple of 0.5 (schema: #/multipleOf)",
+        ]);
+        assert_eq!(errors(schema, "\"1\""), vec!["#: Expected number or null, found string (schema: #/type)"]);
+
+        assert_eq!(errors(r#"{"enum": ["red", 1, {"a": [true]}]}"#, r#"{"a": [false]}"#),
+            vec![r#"#: Value must be one of "red", 1, {"a":[true]} (schema: #/enum)"#]);
+        assert_valid(r#"{"enum": [{"a": 1, "b": 2}]}"#, r#"{"b": 2, "a": 1.0}"#);
+        assert_eq!(errors(r#"{"const": "x"}"#, "\"y\""), vec![r#"#: Value must be "x" (schema: #/const)"#]);
+        assert_eq!(errors("false", "1"), vec!["#: No value is allowed here (schema: #)"]);
+        assert_valid("true", "1");
+    }
+
+    #[test]
+    fn test_arrays() {
+        let schema = r#"{"prefixItems": [{"type": "string"}, {"type": "number"}], "items": {"type": "boolean"},
+            "contains": {"const": true}, "maxContains": 1, "minItems": 2}"#;
+        assert_valid(schema, r#"["a", 1, true, false]"#);
+        assert_eq!(errors(schema, r#"[1, "a", 3]"#), vec![
+            "#/0: Expected string, found number (schema: #/prefixItems/0/type)",
+            "#/1: Expected number, found string (schema: #/prefixItems/1/type)",
+            "#/2: Expected boolean, found number (schema: #/items/type)",
+            "#: Array contains 0 matching items, expected at least 1 (schema: #/contains)",
+        ]);
+        assert_eq!(errors(schema, r#"["a", 1, true, true]"#),
+            vec!["#: Array contains 2 matching items, expected at most 1 (schema: #/contains)"]);
+    }
+
+    #[test]
+    fn test_applicators() {
+        let schema = r#"{"anyOf": [{"type": "string"}, {"type": "integer"}], "not": {"const": 3}}"#;
+        assert_valid(schema, "\"a\"");
+        assert_eq!(errors(schema, "1.5"), vec!["#: Value does not match any schema in anyOf (schema: #/anyOf)"]);
+        assert_eq!(errors(schema, "3"), vec!["#: Value must not match the schema in not (schema: #/not)"]);
+
+        let schema = r#"{"oneOf": [{"minimum": 5}, {"multipleOf": 2}]}"#;
+        assert_valid(schema, "7");
+        assert_eq!(errors(schema, "8"), vec!["#: Value matches 2 schemas in oneOf, expected exactly one (schema: #/oneOf)"]);
+        assert_eq!(errors(schema, "3"), vec!["#: Value matches 0 schemas in oneOf, expected exactly one (schema: #/oneOf)"]);
+
+        let schema = r#"{"allOf": [{"minLength": 2}, {"maxLength": 3}]}"#;
+        assert_eq!(errors(schema, "\"a\""), vec!["#: String is shorter than 2 characters (schema: #/allOf/0/minLength)"]);
+    }
+
+    #[test]
+    fn test_refs() {
+        let tree = r##"{
+            "$defs": {"node": {"$anchor": "node", "type": "object", "properties": {
+                "value": {"type": "number"},
+                "children": {"type": "array", "items": {"$ref": "#node"}}
+            }}},
+            "$ref": "#/$defs/node"
+        }"##;
+        assert_valid(tree, r#"{"value": 1, "children": [{"value": 2, "children": []}]}"#);
+        assert_eq!(errors(tree, r#"{"value": 1, "children": [{"value": "2"}]}"#),
+            vec!["#/children/0/value: Expected number, found string (schema: #/$defs/node/properties/value/type)"]);
+
+        assert_valid(r##"{"$ref": "#", "type": "object"}"##, "{}");
+        assert_valid(r##"{"properties": {"a~b": {"type": "string"}, "c": {"$ref": "#/properties/a~0b"}}}"##, r#"{"c": "x"}"#);
+    }
+
+    #[test]
+    fn test_formats() {
+        let valid = [
+            ("date", "2024-02-29"), ("time", "23:59:60Z"), ("date-time", "2024-01-01T12:30:00.5+02:00"),
+            ("ipv4", "192.168.0.1"), ("ipv6", "::1"), ("uri", "https://example.com/a?b"),
+            ("uuid", "123e4567-e89b-12d3-a456-426614174000"), ("hostname", "example.com"), ("unknown", "anything"),
+        ];
+        for (format, value) in valid {
+            assert_eq!(check_format(format, value), if format == "unknown" { None } else { Some(true) }, "{}", value);
+        }
+        let invalid = [
+            ("date", "2023-02-29"), ("date", "2024-13-01"), ("time", "24:00:00Z"), ("date-time", "2024-01-01 12:30:00Z"),
+            ("ipv4", "256.1.1.1"), ("ipv6", "1:::2"), ("uri", "no scheme"), ("uuid", "123"), ("hostname", "-bad-"),
+            ("email", "a@b"),
+        ];
+        for (format, value) in invalid {
+            assert_eq!(check_format(format, value), Some(false), "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_invalid_schemas() {
+        let error = |schema: &str| Schema::compile(&parse_json(schema).unwrap()).unwrap_err().to_string();
+        assert_eq!(error(r#"{"type": "text"}"#), "Invalid schema at #/type: Expected a type name or an array of type names");
+        assert_eq!(error(r#"{"properties": {"a": {"minLength": -1}}}"#),
+            "Invalid schema at #/properties/a/minLength: Expected a non-negative integer");
+        assert_eq!(error(r#"{"pattern": "("}"#), "Invalid schema at #/pattern: Invalid regular expression");
+        assert_eq!(error(r##"{"$ref": "#/$defs/missing"}"##), "Invalid schema at #/$ref: Cannot resolve reference '#/$defs/missing'");
+        assert_eq!(error(r#"{"$ref": "other.json"}"#),
+            "Invalid schema at #/$ref: Only references within the schema ('#...') are supported");
+        assert_eq!(error(r#"{"anyOf": []}"#), "Invalid schema at #/anyOf: Expected at least one schema");
+        assert_eq!(error("1"), "Invalid schema at #: A schema must be an object or a boolean");
+    }
+}
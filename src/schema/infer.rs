@@ -0,0 +1,172 @@
+use anyhow::{Result, anyhow};
+use crate::json_value::{object_from_entries, JsonValue};
+use crate::shape::Shape;
+
+pub const DRAFT_2020_12: &str = "https://json-schema.org/draft/2020-12/schema";
+
+#[derive(Debug, Clone)]
+pub struct InferOptions {
+    /// Strings with at most this many distinct values become an enum, provided that values
+    /// repeat across the samples; 0 disables enums
+    pub max_enum_values: usize,
+}
+
+impl Default for InferOptions {
+    fn default() -> Self {
+        InferOptions { max_enum_values: 5 }
+    }
+}
+
+/// Infers a draft 2020-12 schema all samples conform to.
+///
+/// Types observed at the same path are merged, object members present in every sample are
+/// required, numbers get the observed range and repeating strings with few distinct values
+/// become enums.
+pub fn infer_schema(samples: &[JsonValue], options: &InferOptions) -> Result<JsonValue> {
+    if samples.is_empty() {
+        return Err(anyhow!("At least one sample is needed to infer a schema"));
+    }
+    let shape = Shape::from_samples(samples);
+
+    let JsonValue::Object(mut names, mut members) = to_schema(&shape, options) else {
+        unreachable!("schemas are objects");
+    };
+    names.insert(0, "$schema".to_string());
+    members.insert("$schema".to_string(), JsonValue::String(DRAFT_2020_12.to_string()));
+    Ok(JsonValue::Object(names, members))
+}
+
+fn to_schema(shape: &Shape, options: &InferOptions) -> JsonValue {
+    let mut types = Vec::new();
+    let mut keywords: Vec<(String, JsonValue)> = Vec::new();
+
+    if let Some(object) = &shape.object {
+        types.push("object");
+        let properties = object
+            .names
+            .iter()
+            .map(|name| (name.clone(), to_schema(object.property(name), options)))
+            .collect();
+        keywords.push(("properties".to_string(), object_from_entries(properties)));
+        let required: Vec<JsonValue> = object
+            .names
+            .iter()
+            .filter(|name| object.is_required(name))
+            .map(|name| JsonValue::String(name.clone()))
+            .collect();
+        if !required.is_empty() {
+            keywords.push(("required".to_string(), JsonValue::Array(required)));
+        }
+    }
+    if let Some(items) = &shape.array {
+        types.push("array");
+        if !items.is_empty() {
+            keywords.push(("items".to_string(), to_schema(items, options)));
+        }
+    }
+    if let Some(strings) = &shape.strings {
+        types.push("string");
+        // An enum restricts all values, so it can only be combined with null
+        if let Some(values) = strings.enum_values(options.max_enum_values).filter(|_| shape.kinds() == 1) {
+            let mut values: Vec<JsonValue> = values.iter().cloned().map(JsonValue::String).collect();
+            if shape.null {
+                values.push(JsonValue::Null);
+            }
+            keywords.push(("enum".to_string(), JsonValue::Array(values)));
+        }
+    }
+    if let Some(range) = &shape.numbers {
+        types.push(if range.integers { "integer" } else { "number" });
+        keywords.push(("minimum".to_string(), JsonValue::Number(range.min)));
+        keywords.push(("maximum".to_string(), JsonValue::Number(range.max)));
+    }
+    if shape.boolean {
+        types.push("boolean");
+    }
+    if shape.null {
+        types.push("null");
+    }
+
+    let mut entries = Vec::new();
+    match types.as_slice() {
+        [] => (),
+        [single] => entries.push(("type".to_string(), JsonValue::String(single.to_string()))),
+        _ => {
+            let types = types.iter().map(|t| JsonValue::String(t.to_string())).collect();
+            entries.push(("type".to_string(), JsonValue::Array(types)));
+        }
+    }
+    entries.extend(keywords);
+    object_from_entries(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+    use crate::schema::Schema;
+
+    fn infer(samples: &[&str]) -> JsonValue {
+        let samples: Vec<JsonValue> = samples.iter().map(|sample| parse_json(sample).unwrap()).collect();
+        infer_schema(&samples, &InferOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn test_infer_objects() {
+        let samples = [
+            r#"{"id": 1, "name": "a", "status": "open", "tags": ["x"], "owner": {"login": "ann"}}"#,
+            r#"{"id": 2, "name": "b", "status": "closed", "tags": [], "owner": null, "score": 0.5}"#,
+            r#"{"id": 3, "name": "c", "status": "open", "tags": ["y", "z"], "owner": {"login": "bob", "admin": true}}"#,
+        ];
+        let schema = infer(&samples);
+        assert_eq!(schema, parse_json(r#"{
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "properties": {
+                "id": {"type": "integer", "minimum": 1, "maximum": 3},
+                "name": {"type": "string"},
+                "status": {"type": "string", "enum": ["open", "closed"]},
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "owner": {
+                    "type": ["object", "null"],
+                    "properties": {"login": {"type": "string"}, "admin": {"type": "boolean"}},
+                    "required": ["login"]
+                },
+                "score": {"type": "number", "minimum": 0.5, "maximum": 0.5}
+            },
+            "required": ["id", "name", "status", "tags", "owner"]
+        }"#).unwrap());
+
+        let compiled = Schema::compile(&schema).unwrap();
+        for sample in samples {
+            assert!(compiled.is_valid(&parse_json(sample).unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_infer_mixed_types() {
+        assert_eq!(infer(&["[1, 2.5, \"a\", [], null]"]), parse_json(r#"{
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "array",
+            "items": {"type": ["array", "string", "number", "null"], "minimum": 1, "maximum": 2.5}
+        }"#).unwrap());
+        assert_eq!(infer(&[r#"["a", "a", null]"#]), parse_json(r#"{
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "array",
+            "items": {"type": ["string", "null"], "enum": ["a", null]}
+        }"#).unwrap());
+        assert!(infer_schema(&[], &InferOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_enum_options() {
+        let samples = [r#"["a", "b", "a"]"#];
+        let samples: Vec<JsonValue> = samples.iter().map(|sample| parse_json(sample).unwrap()).collect();
+        let schema = infer_schema(&samples, &InferOptions { max_enum_values: 0 }).unwrap();
+        assert_eq!(schema.pointer("/items"), Some(&parse_json(r#"{"type": "string"}"#).unwrap()));
+        let schema = infer_schema(&samples, &InferOptions { max_enum_values: 1 }).unwrap();
+        assert_eq!(schema.pointer("/items/enum"), None);
+        let schema = infer_schema(&samples, &InferOptions::default()).unwrap();
+        assert_eq!(schema.pointer("/items/enum"), Some(&parse_json(r#"["a", "b"]"#).unwrap()));
+    }
+}
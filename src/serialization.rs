@@ -0,0 +1,156 @@
+mod de;
+mod ser;
+
+use std::fmt;
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use crate::json_value::JsonValue;
+use crate::parser::parse_json;
+use crate::pretty_printer::{to_compact_string, PrettyPrinter};
+use crate::format_options::{FormatOptions, Indent};
+
+/// Deserializes a value from JSON text
+pub fn from_str<T: DeserializeOwned>(input: &str) -> Result<T> {
+    from_value(parse_json(input)?)
+}
+
+/// Deserializes a value from a `JsonValue`
+pub fn from_value<T: DeserializeOwned>(value: JsonValue) -> Result<T> {
+    Ok(T::deserialize(value)?)
+}
+
+/// Serializes a value into a `JsonValue`. Non-finite floats become null, map keys must be
+/// strings, numbers or booleans, and integers beyond 2^53 that a JSON number cannot hold
+/// exactly fail.
+pub fn to_value<T: ?Sized + Serialize>(value: &T) -> Result<JsonValue> {
+    Ok(value.serialize(ser::ValueSerializer)?)
+}
+
+/// Serializes a value into compact JSON text
+pub fn to_string<T: ?Sized + Serialize>(value: &T) -> Result<String> {
+    Ok(to_compact_string(&to_value(value)?))
+}
+
+/// Serializes a value into JSON text indented by two spaces
+pub fn to_string_pretty<T: ?Sized + Serialize>(value: &T) -> Result<String> {
+    let options = FormatOptions {
+        indent: Indent::Spaces(2),
+        final_newline: false,
+        ..FormatOptions::default()
+    };
+    let mut printer = PrettyPrinter::new(0, false).with_format_options(options);
+    Ok(printer.pretty_print(&to_value(value)?).iter().map(|cs| cs.to_string()).collect())
+}
+
+/// Error of serializing or deserializing a value
+#[derive(Debug)]
+pub struct SerdeError(String);
+
+impl SerdeError {
+    fn new(message: impl Into<String>) -> Self {
+        SerdeError(message.into())
+    }
+}
+
+impl fmt::Display for SerdeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for SerdeError {}
+
+impl serde::ser::Error for SerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        SerdeError::new(msg.to_string())
+    }
+}
+
+impl serde::de::Error for SerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        SerdeError::new(msg.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+    use serde::Deserialize;
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Person {
+        name: String,
+        age: u8,
+        #[serde(rename = "e-mail")]
+        email: Option<String>,
+        scores: Vec<f64>,
+        role: Role,
+        #[serde(default)]
+        tags: BTreeMap<u32, bool>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Role {
+        Guest,
+        Member(u32),
+        Admin { level: i64 },
+        Pair(String, char),
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let person = Person {
+            name: "Ann \"A\"".to_string(),
+            age: 42,
+            email: None,
+            scores: vec![1.5, -2.0],
+            role: Role::Admin { level: -1 },
+            tags: BTreeMap::from([(1, true), (20, false)]),
+        };
+        let text = to_string(&person).unwrap();
+        assert_eq!(
+            text,
+            r#"{"name":"Ann \"A\"","age":42,"e-mail":null,"scores":[1.5,-2],"role":{"Admin":{"level":-1}},"tags":{"1":true,"20":false}}"#
+        );
+        assert_eq!(from_str::<Person>(&text).unwrap(), person);
+
+        for role in [Role::Guest, Role::Member(7), Role::Pair("x".to_string(), 'y')] {
+            assert_eq!(from_str::<Role>(&to_string(&role).unwrap()).unwrap(), role);
+        }
+        assert_eq!(to_string(&Role::Guest).unwrap(), r#""Guest""#);
+        assert_eq!(to_string_pretty(&vec![(1, "a")]).unwrap(), "[\n  [\n    1,\n    \"a\"\n  ]\n]");
+    }
+
+    #[test]
+    fn test_json_value() {
+        let value = parse_json(r#"{"b": [1, 2.5, null], "a": {"c": true}}"#).unwrap();
+        assert_eq!(to_value(&value).unwrap(), value);
+        assert_eq!(from_value::<JsonValue>(value.clone()).unwrap(), value);
+        assert_eq!(to_string(&value).unwrap(), r#"{"b":[1,2.5,null],"a":{"c":true}}"#);
+
+        let map: HashMap<String, JsonValue> = from_str(r#"{"x": [1], "y": "z"}"#).unwrap();
+        assert_eq!(map["x"], JsonValue::Array(vec![JsonValue::Number(1.0)]));
+        assert_eq!(to_value(&f64::NAN).unwrap(), JsonValue::Null);
+        assert_eq!(to_string(&0.1f32).unwrap(), "0.1");
+        assert_eq!(to_string(&[1u64 << 53, (1u64 << 53) + 2]).unwrap(), "[9007199254740992,9007199254740994]");
+        assert_eq!(to_string(&i64::MIN).unwrap(), "-9223372036854776000");
+        assert_eq!(to_string(&HashMap::from([(u64::MAX, 1)])).unwrap(), r#"{"18446744073709551615":1}"#);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(from_str::<u8>("256").is_err());
+        assert!(from_str::<u8>("1.5").is_err());
+        assert!(from_str::<i32>("-3").is_ok());
+        assert!(from_str::<Person>(r#"{"name": "a"}"#).unwrap_err().to_string().contains("missing field `age`"));
+        assert!(from_str::<(u8, u8)>("[1, 2, 3]").is_err());
+        assert!(from_str::<Role>(r#"{"Guest": null, "Member": 1}"#).is_err());
+        assert!(from_str::<u8>("[1").is_err());
+        assert!(to_string(&HashMap::from([(vec![1], 1)])).is_err());
+        assert_eq!(to_value(&u64::MAX).unwrap_err().to_string(), "18446744073709551615 cannot be represented exactly as a JSON number");
+        assert!(to_value(&((1i64 << 53) + 1)).is_err());
+        assert!(to_value(&i64::MAX).is_err());
+    }
+}
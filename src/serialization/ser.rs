@@ -0,0 +1,439 @@
+use serde::ser::{self, Impossible, Serialize};
+use crate::json_value::{object_from_entries, JsonValue};
+use crate::pretty_printer::format_number;
+use super::SerdeError;
+
+impl Serialize for JsonValue {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use ser::{SerializeMap, SerializeSeq};
+
+        match self {
+            JsonValue::Null => serializer.serialize_unit(),
+            JsonValue::Bool(b) => serializer.serialize_bool(*b),
+            JsonValue::Number(n) if n.fract() == 0.0 && (i64::MIN as f64..i64::MAX as f64).contains(n) => {
+                serializer.serialize_i64(*n as i64)
+            }
+            JsonValue::Number(n) => serializer.serialize_f64(*n),
+            JsonValue::String(s) => serializer.serialize_str(s),
+            JsonValue::Array(elements) => {
+                let mut seq = serializer.serialize_seq(Some(elements.len()))?;
+                for element in elements {
+                    seq.serialize_element(element)?;
+                }
+                seq.end()
+            }
+            JsonValue::Object(names, members) => {
+                let mut map = serializer.serialize_map(Some(names.len()))?;
+                for name in names {
+                    map.serialize_entry(name, &members[name])?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+/// Serializes Rust values into `JsonValue`s. Non-finite floats become null and enums are
+/// externally tagged.
+pub(super) struct ValueSerializer;
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = JsonValue;
+    type Error = SerdeError;
+    type SerializeSeq = ArraySerializer;
+    type SerializeTuple = ArraySerializer;
+    type SerializeTupleStruct = ArraySerializer;
+    type SerializeTupleVariant = VariantSerializer<ArraySerializer>;
+    type SerializeMap = ObjectSerializer;
+    type SerializeStruct = ObjectSerializer;
+    type SerializeStructVariant = VariantSerializer<ObjectSerializer>;
+
+    fn serialize_bool(self, v: bool) -> Result<JsonValue, SerdeError> {
+        Ok(JsonValue::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<JsonValue, SerdeError> {
+        self.serialize_f64(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<JsonValue, SerdeError> {
+        self.serialize_f64(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<JsonValue, SerdeError> {
+        self.serialize_f64(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<JsonValue, SerdeError> {
+        exact_integer(v.into())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<JsonValue, SerdeError> {
+        self.serialize_f64(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<JsonValue, SerdeError> {
+        self.serialize_f64(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<JsonValue, SerdeError> {
+        self.serialize_f64(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<JsonValue, SerdeError> {
+        exact_integer(v.into())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<JsonValue, SerdeError> {
+        // The shortest decimal of the f32 rather than its binary value: 0.1, not 0.10000000149011612
+        self.serialize_f64(v.to_string().parse().unwrap_or_else(|_| v.into()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<JsonValue, SerdeError> {
+        Ok(if v.is_finite() { JsonValue::Number(v) } else { JsonValue::Null })
+    }
+
+    fn serialize_char(self, v: char) -> Result<JsonValue, SerdeError> {
+        Ok(JsonValue::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<JsonValue, SerdeError> {
+        Ok(JsonValue::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<JsonValue, SerdeError> {
+        Ok(JsonValue::Array(v.iter().map(|b| JsonValue::Number((*b).into())).collect()))
+    }
+
+    fn serialize_none(self) -> Result<JsonValue, SerdeError> {
+        Ok(JsonValue::Null)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<JsonValue, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<JsonValue, SerdeError> {
+        Ok(JsonValue::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<JsonValue, SerdeError> {
+        Ok(JsonValue::Null)
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<JsonValue, SerdeError> {
+        Ok(JsonValue::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _name: &'static str, value: &T) -> Result<JsonValue, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<JsonValue, SerdeError> {
+        Ok(tagged(variant, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<ArraySerializer, SerdeError> {
+        Ok(ArraySerializer { elements: Vec::with_capacity(len.unwrap_or(0)) })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<ArraySerializer, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<ArraySerializer, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<VariantSerializer<ArraySerializer>, SerdeError> {
+        Ok(VariantSerializer { variant, inner: self.serialize_seq(Some(len))? })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<ObjectSerializer, SerdeError> {
+        Ok(ObjectSerializer { entries: Vec::new(), next_key: None })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<ObjectSerializer, SerdeError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<VariantSerializer<ObjectSerializer>, SerdeError> {
+        Ok(VariantSerializer { variant, inner: self.serialize_map(Some(len))? })
+    }
+}
+
+/// Numbers are f64, so integers beyond 2^53 that would be rounded are rejected
+fn exact_integer(v: i128) -> Result<JsonValue, SerdeError> {
+    if v as f64 as i128 != v {
+        return Err(SerdeError::new(format!("{} cannot be represented exactly as a JSON number", v)));
+    }
+    Ok(JsonValue::Number(v as f64))
+}
+
+pub(super) struct ArraySerializer {
+    elements: Vec<JsonValue>,
+}
+
+impl ser::SerializeSeq for ArraySerializer {
+    type Ok = JsonValue;
+    type Error = SerdeError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.elements.push(value.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<JsonValue, SerdeError> {
+        Ok(JsonValue::Array(self.elements))
+    }
+}
+
+impl ser::SerializeTuple for ArraySerializer {
+    type Ok = JsonValue;
+    type Error = SerdeError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerdeError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<JsonValue, SerdeError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for ArraySerializer {
+    type Ok = JsonValue;
+    type Error = SerdeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerdeError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<JsonValue, SerdeError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+pub(super) struct ObjectSerializer {
+    entries: Vec<(String, JsonValue)>,
+    next_key: Option<String>,
+}
+
+impl ser::SerializeMap for ObjectSerializer {
+    type Ok = JsonValue;
+    type Error = SerdeError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), SerdeError> {
+        self.next_key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let key = self.next_key.take().ok_or_else(|| SerdeError::new("Map value serialized before its key"))?;
+        self.entries.push((key, value.serialize(ValueSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<JsonValue, SerdeError> {
+        Ok(object_from_entries(self.entries))
+    }
+}
+
+impl ser::SerializeStruct for ObjectSerializer {
+    type Ok = JsonValue;
+    type Error = SerdeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), SerdeError> {
+        self.entries.push((key.to_string(), value.serialize(ValueSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<JsonValue, SerdeError> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+/// Wraps the content of a tuple or struct variant in an object with the variant name as key
+pub(super) struct VariantSerializer<S> {
+    variant: &'static str,
+    inner: S,
+}
+
+/// Externally tagged representation of an enum variant with content
+fn tagged(variant: &str, content: JsonValue) -> JsonValue {
+    object_from_entries(vec![(variant.to_string(), content)])
+}
+
+impl ser::SerializeTupleVariant for VariantSerializer<ArraySerializer> {
+    type Ok = JsonValue;
+    type Error = SerdeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerdeError> {
+        ser::SerializeSeq::serialize_element(&mut self.inner, value)
+    }
+
+    fn end(self) -> Result<JsonValue, SerdeError> {
+        Ok(tagged(self.variant, ser::SerializeSeq::end(self.inner)?))
+    }
+}
+
+impl ser::SerializeStructVariant for VariantSerializer<ObjectSerializer> {
+    type Ok = JsonValue;
+    type Error = SerdeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), SerdeError> {
+        ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    fn end(self) -> Result<JsonValue, SerdeError> {
+        Ok(tagged(self.variant, ser::SerializeMap::end(self.inner)?))
+    }
+}
+
+/// Serializes map keys. Strings, numbers, booleans and unit variants are allowed since JSON
+/// object member names are strings.
+struct KeySerializer;
+
+impl KeySerializer {
+    fn key(value: JsonValue) -> Result<String, SerdeError> {
+        match value {
+            JsonValue::String(s) => Ok(s),
+            JsonValue::Number(n) => Ok(format_number(n)),
+            JsonValue::Bool(b) => Ok(b.to_string()),
+            _ => Err(invalid_key()),
+        }
+    }
+}
+
+fn invalid_key() -> SerdeError {
+    SerdeError::new("Map keys must be strings, numbers or booleans")
+}
+
+macro_rules! serialize_key_with_value_serializer {
+    ($($method:ident($type:ty)),*) => {
+        $(
+            fn $method(self, v: $type) -> Result<String, SerdeError> {
+                KeySerializer::key(ValueSerializer.$method(v)?)
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = SerdeError;
+    type SerializeSeq = Impossible<String, SerdeError>;
+    type SerializeTuple = Impossible<String, SerdeError>;
+    type SerializeTupleStruct = Impossible<String, SerdeError>;
+    type SerializeTupleVariant = Impossible<String, SerdeError>;
+    type SerializeMap = Impossible<String, SerdeError>;
+    type SerializeStruct = Impossible<String, SerdeError>;
+    type SerializeStructVariant = Impossible<String, SerdeError>;
+
+    serialize_key_with_value_serializer!(
+        serialize_bool(bool), serialize_i8(i8), serialize_i16(i16), serialize_i32(i32), serialize_u8(u8),
+        serialize_u16(u16), serialize_u32(u32), serialize_f32(f32), serialize_f64(f64), serialize_char(char),
+        serialize_str(&str), serialize_bytes(&[u8])
+    );
+
+    // Member names are strings, so large integers keep all their digits
+    fn serialize_i64(self, v: i64) -> Result<String, SerdeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<String, SerdeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_none(self) -> Result<String, SerdeError> {
+        Err(invalid_key())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<String, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<String, SerdeError> {
+        Err(invalid_key())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, SerdeError> {
+        Err(invalid_key())
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<String, SerdeError> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _name: &'static str, value: &T) -> Result<String, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, SerdeError> {
+        Err(invalid_key())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SerdeError> {
+        Err(invalid_key())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, SerdeError> {
+        Err(invalid_key())
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct, SerdeError> {
+        Err(invalid_key())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, SerdeError> {
+        Err(invalid_key())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, SerdeError> {
+        Err(invalid_key())
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, SerdeError> {
+        Err(invalid_key())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, SerdeError> {
+        Err(invalid_key())
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::{HashMap, HashSet};
+use crate::json_value::JsonValue;
+
+/// Everything observed at one path of a set of sample documents
+#[derive(Debug, Default)]
+pub(crate) struct Shape {
+    pub null: bool,
+    pub boolean: bool,
+    pub numbers: Option<NumberRange>,
+    pub strings: Option<Strings>,
+    /// Merged shape of the elements of all arrays
+    pub array: Option<Box<Shape>>,
+    pub object: Option<ObjectShape>,
+}
+
+#[derive(Debug)]
+pub(crate) struct NumberRange {
+    pub min: f64,
+    pub max: f64,
+    pub integers: bool,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Strings {
+    pub count: usize,
+    /// Distinct values in order of appearance
+    pub values: Vec<String>,
+    /// The same values for quick lookup
+    seen: HashSet<String>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct ObjectShape {
+    /// Number of objects observed
+    pub count: usize,
+    /// Member names in order of appearance
+    pub names: Vec<String>,
+    /// Number of objects containing the member and the shape of its values
+    pub properties: HashMap<String, (usize, Shape)>,
+}
+
+impl Shape {
+    pub fn from_samples(samples: &[JsonValue]) -> Self {
+        let mut shape = Shape::default();
+        for sample in samples {
+            shape.observe(sample);
+        }
+        shape
+    }
+
+    pub fn observe(&mut self, value: &JsonValue) {
+        match value {
+            JsonValue::Null => self.null = true,
+            JsonValue::Bool(_) => self.boolean = true,
+            JsonValue::Number(n) => {
+                let range = self.numbers.get_or_insert(NumberRange { min: *n, max: *n, integers: true });
+                range.min = range.min.min(*n);
+                range.max = range.max.max(*n);
+                range.integers &= n.fract() == 0.0;
+            }
+            JsonValue::String(s) => {
+                let strings = self.strings.get_or_insert_with(Strings::default);
+                strings.count += 1;
+                if strings.seen.insert(s.clone()) {
+                    strings.values.push(s.clone());
+                }
+            }
+            JsonValue::Array(elements) => {
+                let items = self.array.get_or_insert_with(Box::default);
+                for element in elements {
+                    items.observe(element);
+                }
+            }
+            JsonValue::Object(names, members) => {
+                let object = self.object.get_or_insert_with(ObjectShape::default);
+                object.count += 1;
+                for name in names {
+                    let (count, shape) = object.properties.entry(name.clone()).or_insert_with(|| {
+                        object.names.push(name.clone());
+                        (0, Shape::default())
+                    });
+                    *count += 1;
+                    shape.observe(&members[name]);
+                }
+            }
+        }
+    }
+
+    /// Number of different kinds of values observed besides null; integers and other
+    /// numbers count as one kind
+    pub fn kinds(&self) -> usize {
+        [self.boolean, self.numbers.is_some(), self.strings.is_some(), self.array.is_some(), self.object.is_some()]
+            .iter()
+            .filter(|observed| **observed)
+            .count()
+    }
+
+    /// True if nothing was observed, e.g. for the items of arrays that were always empty
+    pub fn is_empty(&self) -> bool {
+        !self.null && self.kinds() == 0
+    }
+}
+
+impl Strings {
+    /// The values if they look like an enumeration: at most `max_values` distinct values
+    /// that repeat across the samples
+    pub fn enum_values(&self, max_values: usize) -> Option<&[String]> {
+        let distinct = self.values.len();
+        (distinct <= max_values && self.count > distinct).then_some(self.values.as_slice())
+    }
+}
+
+impl ObjectShape {
+    pub fn property(&self, name: &str) -> &Shape {
+        &self.properties[name].1
+    }
+
+    /// True if all objects observed contain the member
+    pub fn is_required(&self, name: &str) -> bool {
+        self.properties[name].0 == self.count
+    }
+}
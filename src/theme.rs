@@ -0,0 +1,297 @@
+use std::path::{Path, PathBuf};
+use anyhow::{Result, anyhow};
+use colored::{Color, ColoredString, Colorize};
+
+/// Name of the environment variable holding a theme specification
+pub const THEME_ENV_VAR: &str = "MEDEA_COLORS";
+
+/// When to use colors in output
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorChoice {
+    Always,
+    Never,
+    /// Use colors if the output stream is a terminal, honoring `NO_COLOR` and `CLICOLOR_FORCE`
+    Auto,
+}
+
+impl ColorChoice {
+    /// Decides whether an output stream should be colorized, `is_terminal` tells whether the
+    /// stream is connected to a terminal.
+    pub fn should_colorize(self, is_terminal: bool) -> bool {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        let force = std::env::var_os("CLICOLOR_FORCE").is_some_and(|v| !v.is_empty() && v != "0");
+        self.decide(is_terminal, no_color, force)
+    }
+
+    fn decide(self, is_terminal: bool, no_color: bool, force: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto if no_color => false,
+            ColorChoice::Auto => force || is_terminal,
+        }
+    }
+}
+
+/// Syntactic category of a piece of printed JSON that determines its color
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorCategory {
+    Null,
+    Boolean,
+    Number,
+    String,
+    Key,
+    Bracket,
+    Punctuation,
+}
+
+impl ColorCategory {
+    fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "null" => Ok(ColorCategory::Null),
+            "boolean" | "bool" => Ok(ColorCategory::Boolean),
+            "number" => Ok(ColorCategory::Number),
+            "string" => Ok(ColorCategory::String),
+            "key" => Ok(ColorCategory::Key),
+            "bracket" => Ok(ColorCategory::Bracket),
+            "punctuation" => Ok(ColorCategory::Punctuation),
+            _ => Err(anyhow!("Unknown color category: {}", name)),
+        }
+    }
+}
+
+/// Foreground color and text attributes
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Style {
+    pub color: Option<Color>,
+    pub bold: bool,
+    pub dimmed: bool,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+impl Style {
+    pub fn new(color: Color) -> Self {
+        Style { color: Some(color), ..Style::default() }
+    }
+
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    /// Parses a comma separated list of attributes. An attribute is either a text attribute
+    /// (`bold`, `dimmed`, `italic`, `underline`), a color name (`blue`, `bright blue`), a
+    /// 256-color palette index (`208`) or a truecolor value (`#ff8700`).
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut style = Style::default();
+        for attribute in spec.split(',').map(str::trim).filter(|a| !a.is_empty()) {
+            match attribute {
+                "bold" => style.bold = true,
+                "dimmed" => style.dimmed = true,
+                "italic" => style.italic = true,
+                "underline" => style.underline = true,
+                "normal" | "none" => style.color = None,
+                _ => {
+                    let color = match attribute.parse::<u8>() {
+                        Ok(index) => Color::AnsiColor(index),
+                        Err(_) => attribute
+                            .parse::<Color>()
+                            .map_err(|_| anyhow!("Invalid color: {}", attribute))?,
+                    };
+                    style.color = Some(color);
+                }
+            }
+        }
+        Ok(style)
+    }
+
+    pub fn apply(&self, text: ColoredString) -> ColoredString {
+        let mut text = match self.color {
+            Some(color) => text.color(color),
+            None => text,
+        };
+        if self.bold {
+            text = text.bold();
+        }
+        if self.dimmed {
+            text = text.dimmed();
+        }
+        if self.italic {
+            text = text.italic();
+        }
+        if self.underline {
+            text = text.underline();
+        }
+        text
+    }
+}
+
+/// Styles for all color categories
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub null: Style,
+    pub boolean: Style,
+    pub number: Style,
+    pub string: Style,
+    pub key: Style,
+    pub bracket: Style,
+    pub punctuation: Style,
+}
+
+impl Default for Theme {
+    /// Basic ANSI colors: blue keys, green strings and yellow delimiters
+    fn default() -> Self {
+        Theme {
+            null: Style::default(),
+            boolean: Style::default(),
+            number: Style::default(),
+            string: Style::new(Color::Green),
+            key: Style::new(Color::Blue),
+            bracket: Style::new(Color::Yellow),
+            punctuation: Style::new(Color::Yellow),
+        }
+    }
+}
+
+impl Theme {
+    /// 256-color theme for terminals with a dark background
+    pub fn dark() -> Self {
+        Theme {
+            null: Style::new(Color::AnsiColor(244)),
+            boolean: Style::new(Color::AnsiColor(176)),
+            number: Style::new(Color::AnsiColor(215)),
+            string: Style::new(Color::AnsiColor(114)),
+            key: Style::new(Color::AnsiColor(75)).bold(),
+            bracket: Style::new(Color::AnsiColor(252)),
+            punctuation: Style::new(Color::AnsiColor(245)),
+        }
+    }
+
+    /// 256-color theme for terminals with a light background
+    pub fn light() -> Self {
+        Theme {
+            null: Style::new(Color::AnsiColor(245)),
+            boolean: Style::new(Color::AnsiColor(127)),
+            number: Style::new(Color::AnsiColor(130)),
+            string: Style::new(Color::AnsiColor(28)),
+            key: Style::new(Color::AnsiColor(25)).bold(),
+            bracket: Style::new(Color::AnsiColor(236)),
+            punctuation: Style::new(Color::AnsiColor(240)),
+        }
+    }
+
+    pub fn style(&self, category: ColorCategory) -> &Style {
+        match category {
+            ColorCategory::Null => &self.null,
+            ColorCategory::Boolean => &self.boolean,
+            ColorCategory::Number => &self.number,
+            ColorCategory::String => &self.string,
+            ColorCategory::Key => &self.key,
+            ColorCategory::Bracket => &self.bracket,
+            ColorCategory::Punctuation => &self.punctuation,
+        }
+    }
+
+    fn style_mut(&mut self, category: ColorCategory) -> &mut Style {
+        match category {
+            ColorCategory::Null => &mut self.null,
+            ColorCategory::Boolean => &mut self.boolean,
+            ColorCategory::Number => &mut self.number,
+            ColorCategory::String => &mut self.string,
+            ColorCategory::Key => &mut self.key,
+            ColorCategory::Bracket => &mut self.bracket,
+            ColorCategory::Punctuation => &mut self.punctuation,
+        }
+    }
+
+    /// Overrides styles from a specification like `key=bold,blue:string=#87d787:number=208`.
+    ///
+    /// Entries are separated by colons or newlines, lines starting with `#` are comments and may
+    /// contain colons.
+    pub fn apply_spec(&mut self, spec: &str) -> Result<()> {
+        let lines = spec.lines().filter(|line| !line.trim_start().starts_with('#'));
+        for entry in lines.flat_map(|line| line.split(':')).map(str::trim) {
+            if entry.is_empty() {
+                continue;
+            }
+            let (name, style) = entry
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Expected 'category=style', found '{}'", entry))?;
+            let category = ColorCategory::from_name(name.trim())?;
+            *self.style_mut(category) = Style::parse(style)?;
+        }
+        Ok(())
+    }
+
+    /// Overrides styles from a theme file containing one `category=style` entry per line.
+    pub fn apply_file(&mut self, path: &Path) -> Result<()> {
+        let spec = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read theme file {}: {}", path.display(), e))?;
+        self.apply_spec(&spec)
+    }
+
+    /// Overrides styles from the `MEDEA_COLORS` environment variable if it is set.
+    pub fn apply_env(&mut self) -> Result<()> {
+        match std::env::var(THEME_ENV_VAR) {
+            Ok(spec) => self
+                .apply_spec(&spec)
+                .map_err(|e| anyhow!("Invalid {}: {}", THEME_ENV_VAR, e)),
+            Err(_) => Ok(()),
+        }
+    }
+}
+
+/// Default location of the theme file: `$XDG_CONFIG_HOME/medea/theme` or `~/.config/medea/theme`
+pub fn default_theme_file() -> Option<PathBuf> {
+    let config_dir = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(config_dir.join("medea").join("theme"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_style() {
+        assert_eq!(Style::parse("bold,blue").unwrap(), Style::new(Color::Blue).bold());
+        assert_eq!(Style::parse("bright red").unwrap(), Style::new(Color::BrightRed));
+        assert_eq!(Style::parse("208").unwrap(), Style::new(Color::AnsiColor(208)));
+        assert_eq!(
+            Style::parse("#ff8700, underline").unwrap(),
+            Style { underline: true, ..Style::new(Color::TrueColor { r: 255, g: 135, b: 0 }) }
+        );
+        assert!(Style::parse("blurple").is_err());
+    }
+
+    #[test]
+    fn test_apply_spec() {
+        let mut theme = Theme::default();
+        theme.apply_spec("key=bold,red:null=244").unwrap();
+        assert_eq!(theme.key, Style::new(Color::Red).bold());
+        assert_eq!(theme.null, Style::new(Color::AnsiColor(244)));
+        assert_eq!(theme.string, Theme::default().string);
+
+        theme.apply_spec("# Colors: green numbers\nnumber = #00ff00\n\n  # key=blue\nbool=italic").unwrap();
+        assert_eq!(theme.number, Style::new(Color::TrueColor { r: 0, g: 255, b: 0 }));
+        assert_eq!(theme.key, Style::new(Color::Red).bold());
+        assert_eq!(theme.boolean, Style { italic: true, ..Style::default() });
+
+        assert!(theme.apply_spec("keys=red").is_err());
+        assert!(theme.apply_spec("key").is_err());
+    }
+
+    #[test]
+    fn test_color_choice() {
+        assert!(ColorChoice::Always.decide(false, true, false));
+        assert!(!ColorChoice::Never.decide(true, false, true));
+        assert!(ColorChoice::Auto.decide(true, false, false));
+        assert!(!ColorChoice::Auto.decide(false, false, false));
+        assert!(ColorChoice::Auto.decide(false, false, true));
+        assert!(!ColorChoice::Auto.decide(true, true, false));
+        assert!(!ColorChoice::Auto.decide(true, true, true));
+    }
+}
\ No newline at end of file
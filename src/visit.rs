@@ -0,0 +1,251 @@
+use crate::json_path::{NormalizedPath, PathElement};
+use crate::json_value::JsonValue;
+
+/// How to continue after visiting a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    Continue,
+    /// Do not visit the children of the node
+    SkipChildren,
+    /// Do not visit any further nodes
+    Stop,
+}
+
+/// Visits the nodes of a document in document order, see [`JsonValue::visit`]. Closures
+/// taking the path and the node implement it.
+pub trait Visitor {
+    /// Called for a node before its children
+    fn visit(&mut self, path: &NormalizedPath, value: &JsonValue) -> Control;
+
+    /// Called for a node after its children
+    fn leave(&mut self, _path: &NormalizedPath, _value: &JsonValue) {}
+}
+
+/// Visits the nodes of a document in document order and may change them, see
+/// [`JsonValue::visit_mut`]. Closures taking the path and the node implement it.
+pub trait VisitorMut {
+    /// Called for a node before its children. Replacing the node causes the children of the
+    /// new node to be visited.
+    fn visit(&mut self, path: &NormalizedPath, value: &mut JsonValue) -> Control;
+
+    /// Called for a node after its children
+    fn leave(&mut self, _path: &NormalizedPath, _value: &mut JsonValue) {}
+}
+
+impl<F: FnMut(&NormalizedPath, &JsonValue) -> Control> Visitor for F {
+    fn visit(&mut self, path: &NormalizedPath, value: &JsonValue) -> Control {
+        self(path, value)
+    }
+}
+
+impl<F: FnMut(&NormalizedPath, &mut JsonValue) -> Control> VisitorMut for F {
+    fn visit(&mut self, path: &NormalizedPath, value: &mut JsonValue) -> Control {
+        self(path, value)
+    }
+}
+
+impl JsonValue {
+    /// Visits the value and all values nested in it, parents before their children and
+    /// object members in document order
+    pub fn visit<V: Visitor + ?Sized>(&self, visitor: &mut V) {
+        visit(self, &mut NormalizedPath::default(), visitor);
+    }
+
+    /// Like [`JsonValue::visit`], but the visitor may change or replace the nodes
+    pub fn visit_mut<V: VisitorMut + ?Sized>(&mut self, visitor: &mut V) {
+        visit_mut(self, &mut NormalizedPath::default(), visitor);
+    }
+
+    /// Iterates over the value and all values nested in it together with their paths, parents
+    /// before their children and object members in document order
+    pub fn walk(&self) -> Walk<'_> {
+        Walk { stack: vec![(NormalizedPath::default(), self)], pending: None }
+    }
+}
+
+/// Returns false if the visitor stopped
+fn visit<V: Visitor + ?Sized>(value: &JsonValue, path: &mut NormalizedPath, visitor: &mut V) -> bool {
+    match visitor.visit(path, value) {
+        Control::Stop => return false,
+        Control::SkipChildren => (),
+        Control::Continue => match value {
+            JsonValue::Array(elements) => {
+                for (index, element) in elements.iter().enumerate() {
+                    path.0.push(PathElement::Index(index));
+                    let proceed = visit(element, path, visitor);
+                    path.0.pop();
+                    if !proceed {
+                        return false;
+                    }
+                }
+            }
+            JsonValue::Object(names, members) => {
+                for name in names {
+                    path.0.push(PathElement::Name(name.clone()));
+                    let proceed = visit(&members[name], path, visitor);
+                    path.0.pop();
+                    if !proceed {
+                        return false;
+                    }
+                }
+            }
+            _ => (),
+        },
+    }
+    visitor.leave(path, value);
+    true
+}
+
+fn visit_mut<V: VisitorMut + ?Sized>(value: &mut JsonValue, path: &mut NormalizedPath, visitor: &mut V) -> bool {
+    match visitor.visit(path, value) {
+        Control::Stop => return false,
+        Control::SkipChildren => (),
+        Control::Continue => match value {
+            JsonValue::Array(elements) => {
+                for (index, element) in elements.iter_mut().enumerate() {
+                    path.0.push(PathElement::Index(index));
+                    let proceed = visit_mut(element, path, visitor);
+                    path.0.pop();
+                    if !proceed {
+                        return false;
+                    }
+                }
+            }
+            JsonValue::Object(names, members) => {
+                for name in names.iter() {
+                    path.0.push(PathElement::Name(name.clone()));
+                    let proceed = visit_mut(members.get_mut(name).unwrap(), path, visitor);
+                    path.0.pop();
+                    if !proceed {
+                        return false;
+                    }
+                }
+            }
+            _ => (),
+        },
+    }
+    visitor.leave(path, value);
+    true
+}
+
+/// Iterator over the nodes of a document, see [`JsonValue::walk`]
+pub struct Walk<'a> {
+    /// Nodes still to be yielded, the next one last
+    stack: Vec<(NormalizedPath, &'a JsonValue)>,
+    /// The node yielded last; its children are pushed when the next node is requested
+    pending: Option<(NormalizedPath, &'a JsonValue)>,
+}
+
+impl Walk<'_> {
+    /// Skips the children of the node yielded last
+    pub fn skip_subtree(&mut self) {
+        self.pending = None;
+    }
+}
+
+impl<'a> Iterator for Walk<'a> {
+    type Item = (NormalizedPath, &'a JsonValue);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((path, value)) = self.pending.take() {
+            match value {
+                JsonValue::Array(elements) => {
+                    for (index, element) in elements.iter().enumerate().rev() {
+                        self.stack.push((path.child(PathElement::Index(index)), element));
+                    }
+                }
+                JsonValue::Object(names, members) => {
+                    for name in names.iter().rev() {
+                        self.stack.push((path.child(PathElement::Name(name.clone())), &members[name]));
+                    }
+                }
+                _ => (),
+            }
+        }
+        let node = self.stack.pop()?;
+        self.pending = Some(node.clone());
+        Some(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    fn document() -> JsonValue {
+        parse_json(r#"{"name": "Ann", "tags": ["a", {"b": null}], "address": {"city": "Berlin"}, "age": 42}"#).unwrap()
+    }
+
+    #[test]
+    fn test_walk() {
+        let document = document();
+        let pointers: Vec<String> = document.walk().map(|(path, _)| path.to_pointer()).collect();
+        assert_eq!(pointers, ["", "/name", "/tags", "/tags/0", "/tags/1", "/tags/1/b", "/address", "/address/city", "/age"]);
+
+        let mut walk = document.walk();
+        let mut visited = Vec::new();
+        while let Some((path, value)) = walk.next() {
+            if path.0.len() == 1 && !value.is_empty() {
+                walk.skip_subtree();
+            }
+            visited.push(path.to_string());
+        }
+        assert_eq!(visited, ["$", "$['name']", "$['tags']", "$['address']", "$['age']"]);
+        assert_eq!(JsonValue::Null.walk().count(), 1);
+    }
+
+    #[test]
+    fn test_visitor() {
+        struct Collector {
+            events: Vec<String>,
+        }
+
+        impl Visitor for Collector {
+            fn visit(&mut self, path: &NormalizedPath, value: &JsonValue) -> Control {
+                self.events.push(format!("enter {}", path.to_pointer()));
+                match value {
+                    JsonValue::Object(names, _) if names.contains(&"city".to_string()) => Control::SkipChildren,
+                    JsonValue::Number(_) => Control::Stop,
+                    _ => Control::Continue,
+                }
+            }
+
+            fn leave(&mut self, path: &NormalizedPath, _value: &JsonValue) {
+                self.events.push(format!("leave {}", path.to_pointer()));
+            }
+        }
+
+        let mut collector = Collector { events: Vec::new() };
+        document().visit(&mut collector);
+        assert_eq!(collector.events, [
+            "enter ", "enter /name", "leave /name", "enter /tags", "enter /tags/0", "leave /tags/0", "enter /tags/1",
+            "enter /tags/1/b", "leave /tags/1/b", "leave /tags/1", "leave /tags", "enter /address", "leave /address",
+            "enter /age",
+        ]);
+
+        let mut strings = 0;
+        document().visit(&mut |_: &NormalizedPath, value: &JsonValue| {
+            strings += usize::from(value.as_str().is_some());
+            Control::Continue
+        });
+        assert_eq!(strings, 3);
+    }
+
+    #[test]
+    fn test_visitor_mut() {
+        let mut document = document();
+        document.visit_mut(&mut |path: &NormalizedPath, value: &mut JsonValue| {
+            match value {
+                JsonValue::String(s) => *s = s.to_uppercase(),
+                JsonValue::Null => *value = parse_json(r#"{"replaced": "x"}"#).unwrap(),
+                JsonValue::Number(_) if path.to_pointer() == "/age" => *value = JsonValue::Bool(true),
+                _ => (),
+            }
+            Control::Continue
+        });
+        assert_eq!(document, parse_json(
+            r#"{"name": "ANN", "tags": ["A", {"b": {"replaced": "X"}}], "address": {"city": "BERLIN"}, "age": true}"#
+        ).unwrap());
+    }
+}
@@ -0,0 +1,379 @@
+mod parser;
+
+use anyhow::Result;
+use crate::format_options::LineEnding;
+use crate::json_value::JsonValue;
+use crate::pretty_printer::{escape_string, format_number};
+
+/// Layout of the YAML output
+#[derive(Debug, Clone)]
+pub struct YamlOptions {
+    /// Number of spaces per nesting level of block mappings and sequences
+    pub indent: usize,
+    /// Nested arrays and objects of scalars that fit into this line width are printed in flow
+    /// style, e.g. `tags: [a, b]`
+    pub max_width: usize,
+    pub line_ending: LineEnding,
+    /// Double-quote strings with non-ASCII characters and escape those characters
+    pub ascii_only: bool,
+}
+
+impl Default for YamlOptions {
+    fn default() -> Self {
+        YamlOptions { indent: 2, max_width: 80, line_ending: LineEnding::Lf, ascii_only: false }
+    }
+}
+
+impl JsonValue {
+    /// Serializes the value as a YAML 1.2 document. Strings that a YAML 1.2 or 1.1 reader would
+    /// take for another type or that contain indicators are double-quoted. Non-finite numbers
+    /// become `.inf`, `-.inf` and `.nan`.
+    pub fn to_yaml(&self, options: &YamlOptions) -> String {
+        let mut emitter = Emitter { options, output: String::new() };
+        emitter.block(self, 0);
+        emitter.output.push('\n');
+        match options.line_ending {
+            LineEnding::Lf => emitter.output,
+            LineEnding::CrLf => emitter.output.replace('\n', "\r\n"),
+        }
+    }
+}
+
+/// Parses a YAML document that uses the JSON-compatible subset of YAML 1.2: block and flow
+/// mappings and sequences, plain, quoted and block scalars, and comments. Plain scalars are
+/// resolved with the core schema. Anchors, aliases, tags, complex keys and multiple documents
+/// are rejected.
+pub fn parse_yaml(input: &str) -> Result<JsonValue> {
+    parser::parse_yaml(input)
+}
+
+struct Emitter<'a> {
+    options: &'a YamlOptions,
+    output: String,
+}
+
+impl Emitter<'_> {
+    /// Prints a value in block style; the current line is already indented to `column`
+    fn block(&mut self, value: &JsonValue, column: usize) {
+        match value {
+            JsonValue::Array(elements) if !elements.is_empty() => {
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        self.newline(column);
+                    }
+                    self.output.push_str("- ");
+                    if self.literal(element, column + self.indent()) {
+                        continue;
+                    }
+                    match self.flow(element, column + 2) {
+                        Some(flow) => self.output.push_str(&flow),
+                        None => self.block(element, column + 2),
+                    }
+                }
+            }
+            JsonValue::Object(names, members) if !names.is_empty() => {
+                for (index, name) in names.iter().enumerate() {
+                    if index > 0 {
+                        self.newline(column);
+                    }
+                    let key = scalar(&JsonValue::String(name.clone()), self.options.ascii_only);
+                    let member = &members[name];
+                    self.output.push_str(&format!("{}:", key));
+                    if literal_header(member, self.options.ascii_only).is_some() {
+                        self.output.push(' ');
+                        self.literal(member, column + self.indent());
+                        continue;
+                    }
+                    match self.flow(member, column + key.chars().count() + 2) {
+                        Some(flow) => self.output.push_str(&format!(" {}", flow)),
+                        None => {
+                            self.newline(column + self.indent());
+                            self.block(member, column + self.indent());
+                        }
+                    }
+                }
+            }
+            _ => self.output.push_str(&inline(value, self.options.ascii_only)),
+        }
+    }
+
+    /// Flow style of scalars and of arrays and objects of scalars that fit into the line
+    fn flow(&self, value: &JsonValue, column: usize) -> Option<String> {
+        // `is_empty` is true for scalars as well
+        let nested = match value {
+            JsonValue::Array(elements) => elements.iter().any(|element| !element.is_empty()),
+            JsonValue::Object(_, members) => members.values().any(|member| !member.is_empty()),
+            _ => return Some(scalar(value, self.options.ascii_only)),
+        };
+        let flow = inline(value, self.options.ascii_only);
+        (value.is_empty() || !nested && column + flow.chars().count() <= self.options.max_width).then_some(flow)
+    }
+
+    /// Prints a multi-line string as a literal block scalar with its lines indented to
+    /// `column`, if it can be written as one
+    fn literal(&mut self, value: &JsonValue, column: usize) -> bool {
+        let (Some(header), JsonValue::String(s)) = (literal_header(value, self.options.ascii_only), value) else {
+            return false;
+        };
+        self.output.push_str(header);
+        let content = s.trim_end_matches('\n');
+        for line in content.split('\n') {
+            self.output.push('\n');
+            if !line.is_empty() {
+                self.output.push_str(&" ".repeat(column));
+                self.output.push_str(line);
+            }
+        }
+        // Line breaks beyond the final one become empty lines kept by the `+` indicator
+        for _ in content.len() + 1..s.len() {
+            self.output.push('\n');
+        }
+        true
+    }
+
+    /// Nested levels must be indented for the output to stay valid
+    fn indent(&self) -> usize {
+        self.options.indent.max(1)
+    }
+
+    fn newline(&mut self, column: usize) {
+        self.output.push('\n');
+        self.output.push_str(&" ".repeat(column));
+    }
+}
+
+/// The header of the literal block scalar for strings containing line breaks, with the
+/// chomping indicator that preserves the final line breaks
+fn literal_header(value: &JsonValue, ascii_only: bool) -> Option<&'static str> {
+    let JsonValue::String(s) = value else {
+        return None;
+    };
+    let content = s.trim_end_matches('\n');
+    // Leading spaces would require an indentation indicator
+    if !s.contains('\n') || content.is_empty() || content.trim_start_matches('\n').starts_with(' ') {
+        return None;
+    }
+    if !s.chars().all(|c| c == '\n' || is_printable(c) && (c.is_ascii() || !ascii_only)) {
+        return None;
+    }
+    match s.len() - content.len() {
+        0 => Some("|-"),
+        1 => Some("|"),
+        _ => Some("|+"),
+    }
+}
+
+/// Flow style of a value
+fn inline(value: &JsonValue, ascii_only: bool) -> String {
+    match value {
+        JsonValue::Array(elements) => {
+            let elements: Vec<String> = elements.iter().map(|element| inline(element, ascii_only)).collect();
+            format!("[{}]", elements.join(", "))
+        }
+        JsonValue::Object(names, members) => {
+            let members: Vec<String> = names
+                .iter()
+                .map(|name| {
+                    format!("{}: {}", scalar(&JsonValue::String(name.clone()), ascii_only), inline(&members[name], ascii_only))
+                })
+                .collect();
+            format!("{{{}}}", members.join(", "))
+        }
+        _ => scalar(value, ascii_only),
+    }
+}
+
+fn scalar(value: &JsonValue, ascii_only: bool) -> String {
+    match value {
+        JsonValue::Null => "null".to_string(),
+        JsonValue::Bool(b) => b.to_string(),
+        JsonValue::Number(n) if n.is_nan() => ".nan".to_string(),
+        JsonValue::Number(n) if n.is_infinite() => if *n > 0.0 { ".inf" } else { "-.inf" }.to_string(),
+        JsonValue::Number(n) => format_number(*n),
+        JsonValue::String(s) if needs_quotes(s) || ascii_only && !s.is_ascii() => quote(s, ascii_only),
+        JsonValue::String(s) => s.clone(),
+        JsonValue::Array(_) | JsonValue::Object(_, _) => inline(value, ascii_only),
+    }
+}
+
+/// Whether a string cannot be written as a plain scalar, either because it contains
+/// indicators or because it would be read as another type
+fn needs_quotes(s: &str) -> bool {
+    let (Some(first), Some(last)) = (s.chars().next(), s.chars().last()) else {
+        return true;
+    };
+    if "-?:,[]{}#&*!|>'\"%@`".contains(first) || first.is_whitespace() || last.is_whitespace() || last == ':' {
+        return true;
+    }
+    if s.contains(": ") || s.contains(" #") || s.starts_with("...") {
+        return true;
+    }
+    if s.chars().any(|c| ",[]{}".contains(c) || !is_printable(c)) {
+        return true;
+    }
+    // Booleans of YAML 1.1, which many readers still use, and anything that looks like a number
+    const YAML_1_1: [&str; 17] =
+        ["y", "Y", "yes", "Yes", "YES", "n", "N", "no", "No", "NO", "on", "On", "ON", "off", "Off", "OFF", "<<"];
+    let unsigned = s.strip_prefix(['-', '+']).unwrap_or(s);
+    let numeric = unsigned.starts_with(|c: char| c.is_ascii_digit())
+        || unsigned.strip_prefix('.').is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()));
+    YAML_1_1.contains(&s) || numeric || !matches!(resolve_plain(s), JsonValue::String(_))
+}
+
+fn is_printable(c: char) -> bool {
+    !c.is_control() && !matches!(c, '\u{2028}' | '\u{2029}' | '\u{feff}' | '\u{fffe}' | '\u{ffff}')
+}
+
+/// Double-quotes a string with the escapes of JSON, which YAML shares. Characters beyond the
+/// Basic Multilingual Plane are escaped with YAML's `\U` instead of surrogate pairs.
+fn quote(s: &str, ascii_only: bool) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        if (ascii_only || !is_printable(c)) && c > '\u{ffff}' {
+            quoted.push_str(&format!("\\U{:08x}", c as u32));
+        } else if (ascii_only || !is_printable(c)) && c > '\u{7e}' {
+            quoted.push_str(&format!("\\u{:04x}", c as u32));
+        } else {
+            quoted.push_str(&escape_string(c.encode_utf8(&mut [0; 4])));
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Resolves a plain scalar with the YAML 1.2 core schema
+fn resolve_plain(s: &str) -> JsonValue {
+    match s {
+        "" | "~" | "null" | "Null" | "NULL" => JsonValue::Null,
+        "true" | "True" | "TRUE" => JsonValue::Bool(true),
+        "false" | "False" | "FALSE" => JsonValue::Bool(false),
+        ".inf" | ".Inf" | ".INF" | "+.inf" | "+.Inf" | "+.INF" => JsonValue::Number(f64::INFINITY),
+        "-.inf" | "-.Inf" | "-.INF" => JsonValue::Number(f64::NEG_INFINITY),
+        ".nan" | ".NaN" | ".NAN" => JsonValue::Number(f64::NAN),
+        _ => {
+            let radix = match s.get(..2) {
+                Some("0o") => Some(8),
+                Some("0x") => Some(16),
+                _ => None,
+            };
+            let number = match radix {
+                Some(radix) => u64::from_str_radix(&s[2..], radix).ok().map(|n| n as f64),
+                None if is_decimal(s) => s.parse().ok(),
+                None => None,
+            };
+            number.map_or_else(|| JsonValue::String(s.to_string()), JsonValue::Number)
+        }
+    }
+}
+
+/// Matches `[-+]?(\.[0-9]+|[0-9]+(\.[0-9]*)?)([eE][-+]?[0-9]+)?`
+fn is_decimal(s: &str) -> bool {
+    let digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    let s = s.strip_prefix(['-', '+']).unwrap_or(s);
+    let (mantissa, exponent) = match s.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent.strip_prefix(['-', '+']).unwrap_or(exponent))),
+        None => (s, None),
+    };
+    let (integer, fraction) = match mantissa.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (mantissa, None),
+    };
+    let mantissa_valid = match fraction {
+        Some(fraction) => digits(integer) && digits(fraction) && !(integer.is_empty() && fraction.is_empty()),
+        None => digits(integer) && !integer.is_empty(),
+    };
+    mantissa_valid && exponent.is_none_or(|exponent| digits(exponent) && !exponent.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    #[test]
+    fn test_to_yaml() {
+        let document = parse_json(r##"{
+            "name": "John",
+            "address": {"city": "Anytown", "zip": "01234"},
+            "tags": ["a", "b c"],
+            "skills": [{"name": "Rust", "level": 3}, ["x", {"y": []}]],
+            "empty": {},
+            "nothing": null,
+            "quoted": ["", "yes", "true", "1.5", "-.5", "a: b", "#x", "- x", " x", "x\ny", "\u0085", "null", "~", "x,y"],
+            "plain": ["Yes sir", "a:b", "1a", "C#", "über", ".x"],
+            "numbers": [1, -2.5, 1e21]
+        }"##).unwrap();
+        assert_eq!(document.to_yaml(&YamlOptions::default()), r##"name: John
+address: {city: Anytown, zip: "01234"}
+tags: [a, b c]
+skills:
+  - {name: Rust, level: 3}
+  - - x
+    - {"y": []}
+empty: {}
+nothing: null
+quoted:
+  - ""
+  - "yes"
+  - "true"
+  - "1.5"
+  - "-.5"
+  - "a: b"
+  - "#x"
+  - "- x"
+  - " x"
+  - |-
+    x
+    y
+  - "\u0085"
+  - "null"
+  - "~"
+  - "x,y"
+plain: [Yes sir, a:b, "1a", C#, über, .x]
+numbers: [1, -2.5, 1e+21]
+"##);
+
+        let options = YamlOptions { indent: 4, max_width: 20, ..YamlOptions::default() };
+        let document = parse_json(r#"{"a": {"b": [1, 2, 3, 4, 5, 6, 7, 8]}, "c": [[]]}"#).unwrap();
+        assert_eq!(document.to_yaml(&options), "a:\n    b:\n        - 1\n        - 2\n        - 3\n        - 4\n        - 5\n        - 6\n        - 7\n        - 8\nc: [[]]\n");
+        assert_eq!(JsonValue::Number(f64::NEG_INFINITY).to_yaml(&options), "-.inf\n");
+        let document = parse_json(r#"{"a": "x\n\n  y\n", "b": ["z\n\n", " z\n", "z\t\n"]}"#).unwrap();
+        assert_eq!(document.to_yaml(&options), "a: |\n    x\n\n      y\nb:\n    - |+\n        z\n\n    - \" z\\n\"\n    - \"z\\t\\n\"\n");
+        assert_eq!(parse_json("[]").unwrap().to_yaml(&options), "[]\n");
+
+        let options = YamlOptions { line_ending: LineEnding::CrLf, ascii_only: true, ..YamlOptions::default() };
+        let document = parse_json(r#"{"ä": ["x\ny", "ö\n", ["\ud83d\ude00"]]}"#).unwrap();
+        assert_eq!(document.to_yaml(&options), "\"\\u00e4\":\r\n  - |-\r\n    x\r\n    y\r\n  - \"\\u00f6\\n\"\r\n  - [\"\\U0001f600\"]\r\n");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let document = parse_json(r#"{
+            "name": "John \"J\" O'Neil",
+            "nested": [[1, [2, [3]]], {"a": {"b": {"c": null}}}, [], {}],
+            "strings": ["", " ", "yes", "0x1F", "a # b", "multi\nline\n", "tab\there", "\u0001", "ü", "\ud83d\ude00 x"],
+            "numbers": [0, -1, 1.5, 1e-7, 123456789012],
+            "keys": {"": 1, "a b": 2, "- c": 3, "true": 4, "1": 5, "x: y": 6}
+        }"#).unwrap();
+        let ascii = YamlOptions { ascii_only: true, ..YamlOptions::default() };
+        for options in [YamlOptions::default(), YamlOptions { indent: 3, max_width: 0, ..YamlOptions::default() }, ascii] {
+            let yaml = document.to_yaml(&options);
+            assert_eq!(parse_yaml(&yaml).unwrap(), document, "{}", yaml);
+        }
+    }
+
+    #[test]
+    fn test_resolve_plain() {
+        for (plain, json) in [
+            ("~", "null"), ("", "null"), ("True", "true"), ("FALSE", "false"), ("0o17", "15"), ("0x1f", "31"),
+            ("+12", "12"), ("-1.5e3", "-1500"), (".5", "0.5"), ("1.", "1"), ("1.e2", "100"),
+        ] {
+            assert_eq!(resolve_plain(plain), parse_json(json).unwrap(), "{}", plain);
+        }
+        for plain in ["yes", "0b1", "1e", ".", "1_000", "0x", "e5", "nan", "inf", "+"] {
+            assert_eq!(resolve_plain(plain), JsonValue::String(plain.to_string()), "{}", plain);
+        }
+        assert!(matches!(resolve_plain(".NaN"), JsonValue::Number(n) if n.is_nan()));
+        assert_eq!(resolve_plain("-.inf"), JsonValue::Number(f64::NEG_INFINITY));
+    }
+}
@@ -0,0 +1,721 @@
+use std::collections::HashMap;
+use anyhow::{anyhow, Result};
+use crate::json_value::JsonValue;
+use super::resolve_plain;
+
+pub(super) fn parse_yaml(input: &str) -> Result<JsonValue> {
+    let mut parser = Parser::new(input);
+    parser.parse_document()
+}
+
+/// Where a node starts, which decides what may follow on the same line
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Context {
+    Document,
+    /// After `key:`, where a sequence may also start at the indentation of the key
+    MappingValue,
+    /// After `- `, where a mapping or sequence may start on the same line
+    SequenceEntry,
+}
+
+enum Chomping {
+    Strip,
+    Clip,
+    Keep,
+}
+
+struct Parser {
+    input: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn new(input: &str) -> Self {
+        Parser {
+            input: input.strip_prefix('\u{feff}').unwrap_or(input).replace("\r\n", "\n").chars().collect(),
+            position: 0,
+        }
+    }
+
+    fn parse_document(&mut self) -> Result<JsonValue> {
+        self.skip_to_content();
+        while self.peek() == Some('%') {
+            if !self.consume_str("%YAML") {
+                return Err(self.error("Directives other than %YAML are not supported"));
+            }
+            while !self.at_line_end() {
+                self.advance();
+            }
+            self.skip_to_content();
+        }
+        if self.at_document_marker("---") {
+            self.position += 3;
+        }
+        let value = self.parse_value(-1, Context::Document)?;
+        self.skip_to_content();
+        if self.at_document_marker("...") {
+            self.position += 3;
+            self.skip_to_content();
+        }
+        match self.peek() {
+            None => Ok(value),
+            Some(_) if self.at_document_marker("---") => Err(self.error("Multiple documents are not supported")),
+            Some(_) => Err(self.error("Unexpected content, check the indentation")),
+        }
+    }
+
+    /// Parses the value following `---`, `key:` or `- `, which may start on the same line or
+    /// on one of the following lines. `parent` is the indentation of the enclosing collection.
+    fn parse_value(&mut self, parent: isize, context: Context) -> Result<JsonValue> {
+        let compact = context != Context::MappingValue;
+        self.skip_spaces();
+        self.skip_comment();
+        if !self.at_line_end() {
+            let column = self.column();
+            if self.at_sequence_entry() {
+                return match compact {
+                    true => self.parse_sequence(column),
+                    false => Err(self.error("A sequence cannot start on the line of its key")),
+                };
+            }
+            if compact && self.at_mapping_key() {
+                return self.parse_mapping(column);
+            }
+            return self.parse_scalar(parent);
+        }
+
+        self.skip_to_content();
+        if self.peek().is_none() || self.at_document_marker("---") || self.at_document_marker("...") {
+            return Ok(JsonValue::Null);
+        }
+        let column = self.column();
+        if column as isize > parent {
+            if self.at_sequence_entry() {
+                return self.parse_sequence(column);
+            }
+            if self.at_mapping_key() {
+                return self.parse_mapping(column);
+            }
+            return self.parse_scalar(parent);
+        }
+        if column as isize == parent && context == Context::MappingValue && self.at_sequence_entry() {
+            return self.parse_sequence(column);
+        }
+        Ok(JsonValue::Null)
+    }
+
+    fn parse_sequence(&mut self, indent: usize) -> Result<JsonValue> {
+        let mut elements = Vec::new();
+        loop {
+            self.advance();
+            elements.push(self.parse_value(indent as isize, Context::SequenceEntry)?);
+            if !self.next_entry(indent)? {
+                return Ok(JsonValue::Array(elements));
+            }
+            // Otherwise the sequence may be the value of a key with the same indentation
+            if !self.at_sequence_entry() {
+                return Ok(JsonValue::Array(elements));
+            }
+        }
+    }
+
+    fn parse_mapping(&mut self, indent: usize) -> Result<JsonValue> {
+        let mut names = Vec::new();
+        let mut members = HashMap::new();
+        loop {
+            let start = self.position;
+            let key = self.parse_key()?;
+            if members.contains_key(&key) {
+                self.position = start;
+                return Err(self.error(&format!("Duplicate key '{}'", key)));
+            }
+            let value = self.parse_value(indent as isize, Context::MappingValue)?;
+            names.push(key.clone());
+            members.insert(key, value);
+            if !self.next_entry(indent)? {
+                return Ok(JsonValue::Object(names, members));
+            }
+            if !self.at_mapping_key() {
+                return Err(self.error("Expected a mapping key"));
+            }
+        }
+    }
+
+    /// Moves to the next entry of a block collection and returns whether it has the
+    /// indentation of the collection
+    fn next_entry(&mut self, indent: usize) -> Result<bool> {
+        self.skip_to_content();
+        if self.peek().is_none() || self.at_document_marker("---") || self.at_document_marker("...") {
+            return Ok(false);
+        }
+        match self.column().cmp(&indent) {
+            std::cmp::Ordering::Less => Ok(false),
+            std::cmp::Ordering::Equal => Ok(true),
+            std::cmp::Ordering::Greater => Err(self.error("Unexpected indentation")),
+        }
+    }
+
+    /// Parses an implicit key and the colon following it
+    fn parse_key(&mut self) -> Result<String> {
+        let key = match self.peek() {
+            Some('"') | Some('\'') => self.parse_quoted()?,
+            _ => {
+                self.check_node_start()?;
+                let mut key = String::new();
+                while !(self.peek() == Some(':') && self.at_separator(1)) {
+                    key.push(self.advance().ok_or_else(|| self.error("Expected ':'"))?);
+                }
+                key.trim_end().to_string()
+            }
+        };
+        self.skip_spaces();
+        match self.consume(':') {
+            true => Ok(key),
+            false => Err(self.error("Expected ':'")),
+        }
+    }
+
+    /// Parses a scalar or flow collection in block context
+    fn parse_scalar(&mut self, parent: isize) -> Result<JsonValue> {
+        let value = match self.peek() {
+            Some('|') | Some('>') => return self.parse_block_scalar(parent),
+            Some('[') | Some('{') | Some('"') | Some('\'') => self.parse_flow_node()?,
+            _ => {
+                self.check_node_start()?;
+                self.parse_plain(parent)
+            }
+        };
+        self.skip_spaces();
+        self.skip_comment();
+        match self.at_line_end() {
+            true => Ok(value),
+            false => Err(self.error("Unexpected content after the value")),
+        }
+    }
+
+    /// Parses a plain scalar in block context, which continues on more indented lines
+    fn parse_plain(&mut self, parent: isize) -> JsonValue {
+        let mut text = self.plain_line(false);
+        loop {
+            let end = self.position;
+            let mut breaks = 0;
+            while self.consume('\n') {
+                breaks += 1;
+                self.skip_spaces();
+            }
+            let continues = breaks > 0
+                && !matches!(self.peek(), None | Some('#'))
+                && self.column() as isize > parent
+                && !self.at_document_marker("---")
+                && !self.at_document_marker("...");
+            if !continues {
+                self.position = end;
+                return resolve_plain(&text);
+            }
+            match breaks {
+                1 => text.push(' '),
+                _ => text.push_str(&"\n".repeat(breaks - 1)),
+            }
+            text.push_str(&self.plain_line(false));
+        }
+    }
+
+    /// Reads a plain scalar up to the end of the line, a comment or a `: `. In flow context
+    /// flow indicators end it as well.
+    fn plain_line(&mut self, flow: bool) -> String {
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            let end = match c {
+                '\n' => true,
+                ':' => self.at_separator(1) || flow && matches!(self.peek_at(1), Some(',' | '[' | ']' | '{' | '}')),
+                '#' => self.position == 0 || matches!(self.input[self.position - 1], ' ' | '\t' | '\n'),
+                ',' | '[' | ']' | '{' | '}' => flow,
+                _ => false,
+            };
+            if end {
+                break;
+            }
+            text.push(c);
+            self.advance();
+        }
+        text.trim_end().to_string()
+    }
+
+    fn parse_block_scalar(&mut self, parent: isize) -> Result<JsonValue> {
+        let folded = self.advance() == Some('>');
+        let mut chomping = Chomping::Clip;
+        let mut explicit_indent = None;
+        loop {
+            match self.peek() {
+                Some('-') => chomping = Chomping::Strip,
+                Some('+') => chomping = Chomping::Keep,
+                Some(c @ '1'..='9') if explicit_indent.is_none() => explicit_indent = c.to_digit(10),
+                _ => break,
+            }
+            self.advance();
+        }
+        self.skip_spaces();
+        self.skip_comment();
+        if !self.at_line_end() {
+            return Err(self.error("Expected a line break after the block scalar header"));
+        }
+        self.consume('\n');
+
+        let indent = match explicit_indent {
+            Some(indent) => (parent + indent as isize).max(0) as usize,
+            None => self.detect_indent(),
+        };
+        let mut lines = Vec::new();
+        while self.peek().is_some() && !self.at_document_marker("---") && !self.at_document_marker("...") {
+            let start = self.position;
+            let mut spaces = 0;
+            while self.peek() == Some(' ') && spaces < indent {
+                self.advance();
+                spaces += 1;
+            }
+            if spaces < indent && !self.at_line_end() || indent as isize <= parent && !self.at_line_end() {
+                self.position = start;
+                break;
+            }
+            let mut line = String::new();
+            while let Some(c) = self.peek().filter(|c| *c != '\n') {
+                line.push(c);
+                self.advance();
+            }
+            lines.push(if line.trim().is_empty() && spaces < indent { String::new() } else { line });
+            self.consume('\n');
+        }
+
+        let trailing = lines.iter().rev().take_while(|line| line.is_empty()).count();
+        let content = &lines[..lines.len() - trailing];
+        let mut text = match folded {
+            true => fold(content),
+            false => content.join("\n"),
+        };
+        match chomping {
+            Chomping::Strip => (),
+            Chomping::Clip if content.is_empty() => (),
+            Chomping::Clip => text.push('\n'),
+            Chomping::Keep => text.push_str(&"\n".repeat(trailing + usize::from(!content.is_empty()))),
+        }
+        Ok(JsonValue::String(text))
+    }
+
+    /// Indentation of the first non-empty line of a block scalar
+    fn detect_indent(&self) -> usize {
+        let mut indent = 0;
+        for &c in &self.input[self.position..] {
+            match c {
+                ' ' => indent += 1,
+                '\n' => indent = 0,
+                _ => return indent,
+            }
+        }
+        indent
+    }
+
+    fn parse_flow_node(&mut self) -> Result<JsonValue> {
+        match self.peek() {
+            Some('[') => self.parse_flow_sequence(),
+            Some('{') => self.parse_flow_mapping(),
+            Some('"') | Some('\'') => Ok(JsonValue::String(self.parse_quoted()?)),
+            _ => {
+                self.check_node_start()?;
+                match self.plain_line(true) {
+                    text if text.is_empty() => Err(self.error("Expected a value")),
+                    text => Ok(resolve_plain(&text)),
+                }
+            }
+        }
+    }
+
+    fn parse_flow_sequence(&mut self) -> Result<JsonValue> {
+        self.advance();
+        let mut elements = Vec::new();
+        loop {
+            self.skip_to_content();
+            if self.consume(']') {
+                return Ok(JsonValue::Array(elements));
+            }
+            elements.push(self.parse_flow_node()?);
+            self.skip_to_content();
+            if !self.consume(',') && self.peek() != Some(']') {
+                return Err(self.error("Expected ',' or ']'"));
+            }
+        }
+    }
+
+    fn parse_flow_mapping(&mut self) -> Result<JsonValue> {
+        self.advance();
+        let mut names = Vec::new();
+        let mut members = HashMap::new();
+        loop {
+            self.skip_to_content();
+            if self.consume('}') {
+                return Ok(JsonValue::Object(names, members));
+            }
+            let key = match self.peek() {
+                Some('"') | Some('\'') => self.parse_quoted()?,
+                Some('[') | Some('{') => return Err(self.error("Collections as keys are not supported")),
+                _ => {
+                    self.check_node_start()?;
+                    self.plain_line(true)
+                }
+            };
+            if members.contains_key(&key) {
+                return Err(self.error(&format!("Duplicate key '{}'", key)));
+            }
+            self.skip_to_content();
+            let value = match self.consume(':') {
+                true => {
+                    self.skip_to_content();
+                    match self.peek() {
+                        Some(',') | Some('}') => JsonValue::Null,
+                        _ => self.parse_flow_node()?,
+                    }
+                }
+                false => JsonValue::Null,
+            };
+            names.push(key.clone());
+            members.insert(key, value);
+            self.skip_to_content();
+            if !self.consume(',') && self.peek() != Some('}') {
+                return Err(self.error("Expected ',' or '}'"));
+            }
+        }
+    }
+
+    fn parse_quoted(&mut self) -> Result<String> {
+        let quote = self.advance().unwrap();
+        let mut text = String::new();
+        loop {
+            match self.advance() {
+                None => return Err(self.error("Unterminated string")),
+                Some('\'') if quote == '\'' && self.peek() == Some('\'') => {
+                    self.advance();
+                    text.push('\'');
+                }
+                Some(c) if c == quote => return Ok(text),
+                Some('\\') if quote == '"' => self.parse_escape(&mut text)?,
+                Some('\n') => {
+                    // Line breaks are folded into a space, empty lines into line breaks
+                    text.truncate(text.trim_end_matches([' ', '\t']).len());
+                    let mut breaks = 0;
+                    self.skip_spaces();
+                    while self.consume('\n') {
+                        breaks += 1;
+                        self.skip_spaces();
+                    }
+                    match breaks {
+                        0 => text.push(' '),
+                        _ => text.push_str(&"\n".repeat(breaks)),
+                    }
+                }
+                Some(c) => text.push(c),
+            }
+        }
+    }
+
+    fn parse_escape(&mut self, text: &mut String) -> Result<()> {
+        let c = match self.advance().ok_or_else(|| self.error("Unterminated string"))? {
+            '0' => '\0',
+            'a' => '\u{7}',
+            'b' => '\u{8}',
+            't' | '\t' => '\t',
+            'n' => '\n',
+            'v' => '\u{b}',
+            'f' => '\u{c}',
+            'r' => '\r',
+            'e' => '\u{1b}',
+            ' ' => ' ',
+            '"' => '"',
+            '/' => '/',
+            '\\' => '\\',
+            'N' => '\u{85}',
+            '_' => '\u{a0}',
+            'L' => '\u{2028}',
+            'P' => '\u{2029}',
+            'x' => self.parse_code_point(2)?,
+            'u' => self.parse_code_point(4)?,
+            'U' => self.parse_code_point(8)?,
+            '\n' => {
+                // An escaped line break joins the lines without a space
+                self.skip_spaces();
+                return Ok(());
+            }
+            c => return Err(self.error(&format!("Invalid escape sequence '\\{}'", c))),
+        };
+        text.push(c);
+        Ok(())
+    }
+
+    /// Parses the hex digits of an escape sequence, combining the surrogate pairs JSON uses
+    fn parse_code_point(&mut self, digits: usize) -> Result<char> {
+        let mut code = self.parse_hex(digits)?;
+        if (0xD800..0xDC00).contains(&code) && self.consume_str("\\u") {
+            let low = self.parse_hex(4)?;
+            if (0xDC00..0xE000).contains(&low) {
+                code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+            }
+        }
+        char::from_u32(code).ok_or_else(|| self.error("Invalid code point in escape sequence"))
+    }
+
+    fn parse_hex(&mut self, digits: usize) -> Result<u32> {
+        let hex: String = (0..digits).filter_map(|_| self.advance()).collect();
+        u32::from_str_radix(&hex, 16)
+            .ok()
+            .filter(|_| hex.len() == digits)
+            .ok_or_else(|| self.error("Invalid escape sequence"))
+    }
+
+    /// Rejects the indicators of YAML features outside of the JSON-compatible subset
+    fn check_node_start(&self) -> Result<()> {
+        match self.peek() {
+            Some('&') => Err(self.error("Anchors are not supported")),
+            Some('*') => Err(self.error("Aliases are not supported")),
+            Some('!') => Err(self.error("Tags are not supported")),
+            Some('?') if self.at_separator(1) => Err(self.error("Complex mapping keys are not supported")),
+            Some('@') | Some('`') => Err(self.error("Reserved indicator")),
+            _ => Ok(()),
+        }
+    }
+
+    fn at_sequence_entry(&self) -> bool {
+        self.peek() == Some('-') && self.at_separator(1)
+    }
+
+    /// Whether the line continues with an implicit key followed by `: `
+    fn at_mapping_key(&mut self) -> bool {
+        match self.peek() {
+            Some('"') | Some('\'') => {
+                let start = self.position;
+                let is_key = self.parse_quoted().is_ok() && {
+                    self.skip_spaces();
+                    self.peek() == Some(':') && self.at_separator(1)
+                };
+                self.position = start;
+                is_key
+            }
+            Some('[') | Some('{') | Some('#') => false,
+            _ if self.at_sequence_entry() => false,
+            _ => {
+                let start = self.position;
+                let is_key = {
+                    self.plain_line(false);
+                    self.peek() == Some(':')
+                };
+                self.position = start;
+                is_key
+            }
+        }
+    }
+
+    fn at_document_marker(&self, marker: &str) -> bool {
+        self.column() == 0 && self.input[self.position..].iter().take(3).copied().eq(marker.chars()) && self.at_separator(3)
+    }
+
+    /// Whether the character at the offset separates tokens
+    fn at_separator(&self, offset: usize) -> bool {
+        matches!(self.peek_at(offset), None | Some(' ' | '\t' | '\n'))
+    }
+
+    fn at_line_end(&self) -> bool {
+        matches!(self.peek(), None | Some('\n'))
+    }
+
+    /// Skips whitespace, line breaks and comments
+    fn skip_to_content(&mut self) {
+        loop {
+            self.skip_spaces();
+            self.skip_comment();
+            if !self.consume('\n') {
+                return;
+            }
+        }
+    }
+
+    fn skip_spaces(&mut self) {
+        while matches!(self.peek(), Some(' ') | Some('\t')) {
+            self.advance();
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('#') {
+            while !self.at_line_end() {
+                self.advance();
+            }
+        }
+    }
+
+    fn column(&self) -> usize {
+        self.input[..self.position].iter().rev().take_while(|c| **c != '\n').count()
+    }
+
+    fn error(&self, message: &str) -> anyhow::Error {
+        let line = self.input[..self.position].iter().filter(|c| **c == '\n').count() + 1;
+        anyhow!("Invalid YAML at line {}, column {}: {}", line, self.column() + 1, message)
+    }
+
+    fn consume(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn consume_str(&mut self, expected: &str) -> bool {
+        let len = expected.chars().count();
+        let matches = self.input.len() >= self.position + len
+            && self.input[self.position..self.position + len].iter().copied().eq(expected.chars());
+        if matches {
+            self.position += len;
+        }
+        matches
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input.get(self.position).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.input.get(self.position + offset).copied()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+        Some(c)
+    }
+}
+
+/// Joins the lines of a folded block scalar: lines are separated by a space unless they are
+/// empty or more indented
+fn fold(lines: &[String]) -> String {
+    let mut text = String::new();
+    let mut breaks = 0;
+    let mut previous_indented = false;
+    for (index, line) in lines.iter().enumerate() {
+        if line.is_empty() {
+            breaks += 1;
+            continue;
+        }
+        let indented = line.starts_with([' ', '\t']);
+        if index == breaks {
+            text.push_str(&"\n".repeat(breaks));
+        } else if indented || previous_indented {
+            text.push_str(&"\n".repeat(breaks + 1));
+        } else if breaks == 0 {
+            text.push(' ');
+        } else {
+            text.push_str(&"\n".repeat(breaks));
+        }
+        text.push_str(line);
+        breaks = 0;
+        previous_indented = indented;
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    fn assert_yaml(yaml: &str, json: &str) {
+        assert_eq!(parse_yaml(yaml).unwrap(), parse_json(json).unwrap(), "{}", yaml);
+    }
+
+    #[test]
+    fn test_block_collections() {
+        assert_yaml("
+# Service configuration
+name: web   # trailing comment
+replicas: 3
+enabled: yes
+ports:
+- 80
+- 443
+env:
+  DEBUG: false
+  empty:
+  'quoted key': ~
+servers:
+  - host: a.example.com
+    tags: [blue, green]
+  -   host: b.example.com
+      weight: 0.5
+  - - nested
+    - -1
+  -
+  - {x: 1, \"y\": [], z: }
+", r#"{
+    "name": "web", "replicas": 3, "enabled": "yes", "ports": [80, 443],
+    "env": {"DEBUG": false, "empty": null, "quoted key": null},
+    "servers": [
+        {"host": "a.example.com", "tags": ["blue", "green"]},
+        {"host": "b.example.com", "weight": 0.5},
+        ["nested", -1],
+        null,
+        {"x": 1, "y": [], "z": null}
+    ]
+}"#);
+        assert_yaml("---\n- a\n- b: c\n  d: e\n...\n", r#"["a", {"b": "c", "d": "e"}]"#);
+        assert_yaml("%YAML 1.2\n--- text\n", r#""text""#);
+        assert_yaml("", "null");
+        assert_yaml("# only a comment\n", "null");
+        assert_yaml("{\"a\": [1, 2.5, true, null, \"\\u00e4\\ud83d\\ude00\"],\n \"b\":{}}", r#"{"a": [1, 2.5, true, null, "ä😀"], "b": {}}"#);
+        assert_yaml("url: http://x.org/a#b\ntime: 12:30\n", r#"{"url": "http://x.org/a#b", "time": "12:30"}"#);
+        assert_yaml("[a, [b, c], {d: e}, ]", r#"["a", ["b", "c"], {"d": "e"}]"#);
+        assert_yaml("{\n  a: 1, # one\n  b: 2\n}", r#"{"a": 1, "b": 2}"#);
+    }
+
+    #[test]
+    fn test_scalars() {
+        assert_yaml("a: one\n  two\n\n  three\nb: x", r#"{"a": "one two\nthree", "b": "x"}"#);
+        assert_yaml("- 'it''s'\n- \"tab\\tquote\\\"\\x41\\u00e4\\U0001F600\"", r#"["it's", "tab\tquote\"Aä😀"]"#);
+        assert_yaml("\"a\n  b\n\n  c \\\n  d\"", r#""a b\nc d""#);
+        assert_yaml("a: |\n  line 1\n    indented\n\n  line 3\n\nb: 1", r#"{"a": "line 1\n  indented\n\nline 3\n", "b": 1}"#);
+        assert_yaml("a: >-\n  folded\n  text\n\n  new\n    kept\n  end\n", r#"{"a": "folded text\nnew\n  kept\nend"}"#);
+        assert_yaml("- |+\n  keep\n\n- |-\n  strip\n- |2\n    two\n", r#"["keep\n\n", "strip", "  two\n"]"#);
+        assert_yaml("- >\n\n  leading\n- |\n- end", r#"["\nleading\n", "", "end"]"#);
+        assert_yaml("[1, 0x10, 0o10, 1e3, .5, 1_000, 2001-12-14, Null, TRUE, '1']",
+            r#"[1, 16, 8, 1000, 0.5, "1_000", "2001-12-14", null, true, "1"]"#);
+    }
+
+    #[test]
+    fn test_errors() {
+        for (yaml, error) in [
+            ("a: &x 1", "line 1, column 4: Anchors are not supported"),
+            ("- *x", "line 1, column 3: Aliases are not supported"),
+            ("a: !!str 1", "Tags are not supported"),
+            ("&x a: 1", "line 1, column 1: Anchors are not supported"),
+            ("b: 1\n*a: 1", "line 2, column 1: Aliases are not supported"),
+            ("!t k: v", "line 1, column 1: Tags are not supported"),
+            ("a:\n  - &x b: 1", "line 2, column 5: Anchors are not supported"),
+            ("{&x a: 1}", "Anchors are not supported"),
+            ("? a\n: b", "Complex mapping keys are not supported"),
+            ("a: 1\n---\nb: 2", "line 2, column 1: Multiple documents are not supported"),
+            ("a: 1\na: 2", "line 2, column 1: Duplicate key 'a'"),
+            ("a: 1\n  b: 2", "line 2, column 4: Unexpected content after the value"),
+            ("a:\n  b: 1\n c: 2", "line 3, column 2: Unexpected indentation"),
+            ("  a: 1\nb: 2", "line 2, column 1: Unexpected content, check the indentation"),
+            ("a: - b", "A sequence cannot start on the line of its key"),
+            ("- a\nb: 1", "line 2, column 1: Unexpected content, check the indentation"),
+            ("a: 1\n- b", "line 2, column 1: Expected a mapping key"),
+            ("a: b: c", "Unexpected content after the value"),
+            ("[a, b", "Expected ',' or ']'"),
+            ("{a: 1 b: 2}", "Expected ',' or '}'"),
+            ("\"abc", "Unterminated string"),
+            ("\"\\q\"", "Invalid escape sequence '\\q'"),
+            ("a: | x", "Expected a line break after the block scalar header"),
+            ("%TAG ! x\n---\na", "Directives other than %YAML are not supported"),
+        ] {
+            let message = parse_yaml(yaml).unwrap_err().to_string();
+            assert!(message.ends_with(error), "{}: {}", yaml, message);
+        }
+    }
+}